@@ -157,7 +157,7 @@ fn benchmark_process_e2e(c: &mut Criterion) {
     }
 
     let rt = tokio::runtime::Runtime::new().unwrap();
-    let mut processor = photon_core::ImageProcessor::new(&config);
+    let mut processor = photon_core::ImageProcessor::new(&config).unwrap();
     if processor.load_embedding(&config).is_err() {
         eprintln!("Skipping e2e benchmark: failed to load embedding model");
         return;
@@ -195,7 +195,7 @@ fn benchmark_batch_throughput(c: &mut Criterion) {
     }
 
     let rt = tokio::runtime::Runtime::new().unwrap();
-    let mut processor = photon_core::ImageProcessor::new(&config);
+    let mut processor = photon_core::ImageProcessor::new(&config).unwrap();
     if processor.load_embedding(&config).is_err() {
         eprintln!("Skipping batch benchmark: failed to load embedding model");
         return;
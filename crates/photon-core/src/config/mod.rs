@@ -34,9 +34,24 @@ pub struct Config {
     /// Thumbnail generation settings
     pub thumbnail: ThumbnailConfig,
 
+    /// BlurHash and ThumbHash placeholder settings
+    pub placeholder: PlaceholderConfig,
+
+    /// Content and perceptual hashing settings
+    pub hashing: HashingConfig,
+
+    /// Color palette settings
+    pub palette: PaletteConfig,
+
+    /// Image-quality metrics settings
+    pub quality: QualityConfig,
+
     /// Tagging settings
     pub tagging: TaggingConfig,
 
+    /// Reverse geocoding settings
+    pub geocoding: GeocodingConfig,
+
     /// Output settings
     pub output: OutputConfig,
 
@@ -98,6 +113,12 @@ impl Config {
         PathBuf::from(expanded.into_owned())
     }
 
+    /// Get the resolved geocoding dataset directory path (with ~ expansion).
+    pub fn geocoding_dir(&self) -> PathBuf {
+        let expanded = shellexpand::tilde(&self.geocoding.dir);
+        PathBuf::from(expanded.into_owned())
+    }
+
     /// Get the taxonomy directory path (for cached label bank).
     ///
     /// Co-located with the models directory: if `model_dir` is `~/.photon/models`,
@@ -123,6 +144,7 @@ mod tests {
         assert_eq!(config.processing.parallel_workers, 4);
         assert_eq!(config.pipeline.buffer_size, 100);
         assert_eq!(config.limits.max_file_size_mb, 100);
+        assert!(config.processing.auto_orient);
     }
 
     #[test]
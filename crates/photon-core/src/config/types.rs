@@ -24,11 +24,46 @@ impl Default for GeneralConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ProcessingConfig {
-    /// Number of parallel workers
+    /// Number of parallel workers (`photon process` sets it from `--parallel`);
+    /// also sizes an automatic `embedding.pool_size`
     pub parallel_workers: usize,
 
-    /// Supported input formats
+    /// File extensions discovery picks up. Each needs a decoder: HEIC and
+    /// generic `.raw` files have none, so they aren't listed by default.
     pub supported_formats: Vec<String>,
+
+    /// Rotate/flip decoded pixels according to the EXIF orientation tag.
+    /// When false, images are processed in stored (raw-pixel) orientation.
+    pub auto_orient: bool,
+
+    /// Evenly spaced frames sampled from animated GIF/WebP files. The output
+    /// embedding is the mean of the frame embeddings (1 = first frame only).
+    pub animation_frames: u32,
+
+    /// Include per-frame embeddings for animations in the output.
+    pub frame_embeddings: bool,
+
+    /// Treat zip and tar(.gz) archives as directories and process the
+    /// supported images inside them.
+    pub archives: bool,
+
+    /// Gitignore-style globs a file must match to be processed when walking
+    /// a directory (empty = every supported file).
+    pub include: Vec<String>,
+
+    /// Gitignore-style globs for files and directories to skip when walking
+    /// (e.g. `@eaDir`, `.git`, `._*`).
+    pub exclude: Vec<String>,
+
+    /// Maximum directory depth to descend below the input path.
+    pub max_depth: usize,
+
+    /// Process hidden (dot-prefixed) files and descend into hidden directories.
+    /// Set to false to skip `.thumbnails`, `.git`, `._*` AppleDouble files etc.
+    pub include_hidden: bool,
+
+    /// Follow symbolic links while walking directories.
+    pub follow_links: bool,
 }
 
 impl Default for ProcessingConfig {
@@ -40,12 +75,21 @@ impl Default for ProcessingConfig {
                 "jpeg".to_string(),
                 "png".to_string(),
                 "webp".to_string(),
-                "heic".to_string(),
-                "raw".to_string(),
+                "gif".to_string(),
                 "cr2".to_string(),
                 "nef".to_string(),
                 "arw".to_string(),
+                "dng".to_string(),
             ],
+            auto_orient: true,
+            animation_frames: 8,
+            frame_embeddings: false,
+            archives: true,
+            include: vec![],
+            exclude: vec![],
+            max_depth: 256,
+            include_hidden: true,
+            follow_links: true,
         }
     }
 }
@@ -62,6 +106,14 @@ pub struct PipelineConfig {
 
     /// Delay between retries in milliseconds
     pub retry_delay_ms: u64,
+
+    /// Max images per embedding call in batch runs (1 = one call per image).
+    /// Capped at `processing.parallel_workers`, so the default of 8 becomes
+    /// min(8, `--parallel`).
+    pub max_batch_size: usize,
+
+    /// How long a partial embedding batch waits for more images, in milliseconds
+    pub max_wait_ms: u64,
 }
 
 impl Default for PipelineConfig {
@@ -70,6 +122,8 @@ impl Default for PipelineConfig {
             buffer_size: 100,
             retry_attempts: 3,
             retry_delay_ms: 1000,
+            max_batch_size: 8,
+            max_wait_ms: 10,
         }
     }
 }
@@ -84,10 +138,15 @@ pub struct LimitsConfig {
     /// Maximum image dimension (width or height)
     pub max_image_dimension: u32,
 
+    /// Maximum image area in megapixels, checked from the header before decoding
+    pub max_megapixels: u32,
+
     /// Decode timeout in milliseconds
     pub decode_timeout_ms: u64,
 
-    /// Embedding timeout in milliseconds
+    /// Embedding timeout in milliseconds, per image. In batch runs it covers
+    /// the wait for a batch to fill and for a free session as well as the
+    /// inference itself, so raise it with `--parallel` / `max_batch_size`.
     pub embed_timeout_ms: u64,
 
     /// LLM call timeout in milliseconds
@@ -99,6 +158,7 @@ impl Default for LimitsConfig {
         Self {
             max_file_size_mb: 100,
             max_image_dimension: 10000,
+            max_megapixels: 100,
             decode_timeout_ms: 5000,
             embed_timeout_ms: 30000,
             llm_timeout_ms: 60000,
@@ -110,25 +170,41 @@ impl Default for LimitsConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct EmbeddingConfig {
-    /// Model name/variant ("siglip-base-patch16" or "siglip-base-patch16-384")
+    /// Model name/variant ("siglip-base-patch16", "siglip-base-patch16-384",
+    /// or any model directory with a `manifest.json`)
     pub model: String,
 
-    /// Image input size — derived from model variant, not set directly.
+    /// Image input size — derived from the model's manifest, not set directly.
     /// 224 for base, 384 for 384 variant.
     pub image_size: u32,
+
+    /// Number of vision sessions; images are embedded concurrently, one per
+    /// session. Each holds its own copy of the model weights.
+    /// 0 = one per worker (`processing.parallel_workers` / `--parallel`),
+    /// capped at the number of CPUs.
+    pub pool_size: usize,
+
+    /// How a session is picked per image: "least_busy" or "round_robin"
+    pub pool_checkout: String,
+
+    /// ONNX Runtime session tuning, shared by the vision and text encoders
+    pub runtime: RuntimeConfig,
 }
 
-impl Default for EmbeddingConfig {
-    fn default() -> Self {
-        Self {
-            model: "siglip-base-patch16".to_string(),
-            image_size: 224,
+impl EmbeddingConfig {
+    /// `pool_size` with 0 (auto) resolved: one session per worker, capped at
+    /// the number of CPUs.
+    pub fn effective_pool_size(&self, workers: usize) -> usize {
+        match self.pool_size {
+            0 => workers.clamp(1, available_cpus()),
+            size => size,
         }
     }
-}
 
-impl EmbeddingConfig {
     /// Resolve image size from model name.
+    ///
+    /// Only knows the built-in SigLIP base models; the manifest covers any model.
+    #[deprecated(note = "use `ModelManifest::resolve(model_dir, model)?.image_size`")]
     pub fn image_size_for_model(model: &str) -> u32 {
         if model.contains("384") {
             384
@@ -138,6 +214,68 @@ impl EmbeddingConfig {
     }
 }
 
+/// Number of CPUs this process may use (1 if unknown).
+pub(crate) fn available_cpus() -> usize {
+    std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+}
+
+impl Default for EmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            model: "siglip-base-patch16".to_string(),
+            image_size: 224,
+            pool_size: 0,
+            pool_checkout: "least_busy".to_string(),
+            runtime: RuntimeConfig::default(),
+        }
+    }
+}
+
+/// ONNX Runtime session settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    /// Threads used inside a single operator, per session (0 = the CPUs split
+    /// evenly across the vision session pool; ONNX Runtime's default of one
+    /// per physical core for a single session).
+    pub intra_threads: usize,
+
+    /// Threads used to run independent graph nodes concurrently
+    /// (0 = default; only used with `parallel_execution`)
+    pub inter_threads: usize,
+
+    /// Run independent graph branches in parallel instead of sequentially
+    pub parallel_execution: bool,
+
+    /// Graph optimization level: "disable", "basic", "extended", "layout" or "all"
+    pub optimization_level: String,
+
+    /// Use the CPU memory arena (faster allocations, memory is not returned to the OS)
+    pub cpu_arena: bool,
+
+    /// Pre-plan tensor memory from the first run's shapes
+    pub memory_pattern: bool,
+
+    /// Directory to cache optimized models in (supports `~`; empty = no cache).
+    /// At `layout`/`all` the cached graphs are specific to this machine's CPU,
+    /// so don't share the directory between machines.
+    pub optimized_model_dir: String,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            intra_threads: 0,
+            inter_threads: 0,
+            parallel_execution: false,
+            optimization_level: "all".to_string(),
+            cpu_arena: true,
+            memory_pattern: true,
+            optimized_model_dir: String::new(),
+        }
+    }
+}
+
 /// Thumbnail generation settings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -148,8 +286,20 @@ pub struct ThumbnailConfig {
     /// Thumbnail size in pixels (longest edge)
     pub size: u32,
 
-    /// Output format
+    /// Output format: "webp" (lossless), "jpeg", "png" or "avif"
     pub format: String,
+
+    /// Encoder quality for "jpeg" and "avif" (1-100)
+    pub quality: u8,
+
+    /// Emit the thumbnail inline as base64 (`thumbnail` in the output)
+    pub inline: bool,
+
+    /// Directory rendition files are written to (supports `~`)
+    pub output_dir: String,
+
+    /// Named renditions written to `output_dir` as `<content_hash>_<name>.<ext>`
+    pub renditions: Vec<RenditionConfig>,
 }
 
 impl Default for ThumbnailConfig {
@@ -158,6 +308,159 @@ impl Default for ThumbnailConfig {
             enabled: true,
             size: 256,
             format: "webp".to_string(),
+            quality: 80,
+            inline: true,
+            output_dir: String::new(),
+            renditions: Vec::new(),
+        }
+    }
+}
+
+/// One named thumbnail rendition written to disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenditionConfig {
+    /// Name used in the file name and output (letters, digits, `-`, `_`)
+    pub name: String,
+
+    /// Width of the target box in pixels (longest edge for "fit" without a height)
+    pub size: u32,
+
+    /// Height of the target box for "fit" and "cover" (defaults to `size`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+
+    /// Output format: "webp" (lossless), "jpeg", "png" or "avif"
+    pub format: String,
+
+    /// "fit" (inside the box, aspect kept), "cover" (fill the box, center crop)
+    /// or "square" (center crop to `size` × `size`)
+    pub fit: String,
+
+    /// Encoder quality for "jpeg" and "avif" (1-100)
+    pub quality: u8,
+}
+
+impl Default for RenditionConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            size: 256,
+            height: None,
+            format: "webp".to_string(),
+            fit: "fit".to_string(),
+            quality: 80,
+        }
+    }
+}
+
+/// BlurHash and ThumbHash placeholder settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaceholderConfig {
+    /// Whether to compute a BlurHash string
+    pub blurhash: bool,
+
+    /// Horizontal BlurHash components (1-9); more keeps more detail
+    pub components_x: u32,
+
+    /// Vertical BlurHash components (1-9)
+    pub components_y: u32,
+
+    /// Whether to compute a ThumbHash (base64)
+    pub thumbhash: bool,
+}
+
+impl Default for PlaceholderConfig {
+    fn default() -> Self {
+        Self {
+            blurhash: false,
+            components_x: 4,
+            components_y: 3,
+            thumbhash: false,
+        }
+    }
+}
+
+/// Content and perceptual hashing settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HashingConfig {
+    /// Perceptual hashes to compute per image. The first is also emitted as
+    /// `perceptual_hash`; all are emitted in `perceptual_hashes`.
+    pub perceptual: Vec<PerceptualHashConfig>,
+}
+
+impl Default for HashingConfig {
+    fn default() -> Self {
+        Self {
+            perceptual: vec![PerceptualHashConfig::default()],
+        }
+    }
+}
+
+/// One perceptual hash algorithm and its size.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PerceptualHashConfig {
+    /// "phash", "dhash", "ahash", "blockhash", "median", "vert_gradient"
+    /// or "double_gradient"
+    pub algorithm: String,
+
+    /// Hash width and height in bits (8 → a 64-bit hash)
+    pub size: u32,
+}
+
+impl Default for PerceptualHashConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: "double_gradient".to_string(),
+            size: 16,
+        }
+    }
+}
+
+/// Dominant color palette settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaletteConfig {
+    /// Whether to extract a color palette
+    pub enabled: bool,
+
+    /// Number of colors (k-means clusters) per image
+    pub colors: usize,
+
+    /// Longest edge in pixels of the downscaled copy that is clustered
+    pub sample_size: u32,
+}
+
+impl Default for PaletteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            colors: 5,
+            sample_size: 64,
+        }
+    }
+}
+
+/// Technical image-quality metrics settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QualityConfig {
+    /// Whether to compute sharpness, exposure, noise and colorfulness
+    pub enabled: bool,
+
+    /// Longest edge in pixels of the downscaled copy that is analyzed.
+    /// Scores depend on it, so keep it fixed across runs you compare.
+    pub sample_size: u32,
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_size: 1024,
         }
     }
 }
@@ -260,6 +563,32 @@ impl Default for VocabularyConfig {
     }
 }
 
+/// Offline reverse geocoding settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeocodingConfig {
+    /// Resolve EXIF GPS coordinates to country, region and city names
+    /// (requires the dataset installed by `photon models download --geocoding`)
+    pub enabled: bool,
+
+    /// Directory containing the GeoNames dump and country polygons
+    pub dir: String,
+
+    /// Maximum distance to the nearest city in kilometers. Farther images
+    /// get only a country (when inside a country polygon).
+    pub max_distance_km: f64,
+}
+
+impl Default for GeocodingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: "~/.photon/geonames".to_string(),
+            max_distance_km: 50.0,
+        }
+    }
+}
+
 /// Output settings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -272,6 +601,9 @@ pub struct OutputConfig {
 
     /// Include embedding vectors in output
     pub include_embedding: bool,
+
+    /// Redaction of location and identifying metadata
+    pub privacy: PrivacyConfig,
 }
 
 impl Default for OutputConfig {
@@ -280,6 +612,43 @@ impl Default for OutputConfig {
             format: "json".to_string(),
             pretty: false,
             include_embedding: true,
+            privacy: PrivacyConfig::default(),
+        }
+    }
+}
+
+/// Privacy mode: redacts every processed image before it leaves the processor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// Whether to redact output records
+    pub enabled: bool,
+
+    /// GPS handling: "keep", "coarsen" (snap to `gps_grid_degrees`) or "drop"
+    pub gps: String,
+
+    /// Grid cell size in degrees for `gps = "coarsen"` (0.1° ≈ 11 km)
+    pub gps_grid_degrees: f64,
+
+    /// Remove camera/lens serial numbers and owner/creator names
+    pub strip_identifiers: bool,
+
+    /// Replace `file_path` and `file_name` with keyed BLAKE3 hashes
+    pub hash_paths: bool,
+
+    /// Secret key for path hashing (supports ${ENV_VAR} syntax)
+    pub path_hash_key: String,
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            gps: "drop".to_string(),
+            gps_grid_degrees: 0.1,
+            strip_identifiers: true,
+            hash_paths: true,
+            path_hash_key: "${PHOTON_PRIVACY_KEY}".to_string(),
         }
     }
 }
@@ -1,8 +1,15 @@
 //! Configuration validation with range checks.
 
+use crate::embedding::manifest::ModelManifest;
+use crate::embedding::pool::POOL_CHECKOUTS;
+use crate::embedding::runtime::OPTIMIZATION_LEVELS;
 use crate::error::ConfigError;
+use crate::pipeline::discovery::check_glob;
+use crate::pipeline::hash::PERCEPTUAL_ALGORITHMS;
+use crate::pipeline::privacy::path_hash_secret;
+use crate::pipeline::thumbnail::{RENDITION_FITS, THUMBNAIL_FORMATS};
 
-use super::{Config, EmbeddingConfig};
+use super::Config;
 
 impl Config {
     /// Validate configuration values are within acceptable ranges.
@@ -12,11 +19,38 @@ impl Config {
                 "processing.parallel_workers must be > 0".into(),
             ));
         }
+        if self.processing.animation_frames == 0 {
+            return Err(ConfigError::ValidationError(
+                "processing.animation_frames must be > 0".into(),
+            ));
+        }
+        if self.processing.max_depth == 0 {
+            return Err(ConfigError::ValidationError(
+                "processing.max_depth must be > 0".into(),
+            ));
+        }
+        for (key, globs) in [
+            ("include", &self.processing.include),
+            ("exclude", &self.processing.exclude),
+        ] {
+            for glob in globs {
+                if let Err(e) = check_glob(glob) {
+                    return Err(ConfigError::ValidationError(format!(
+                        "processing.{key} has an invalid glob {glob:?}: {e}"
+                    )));
+                }
+            }
+        }
         if self.pipeline.buffer_size == 0 {
             return Err(ConfigError::ValidationError(
                 "pipeline.buffer_size must be > 0".into(),
             ));
         }
+        if self.pipeline.max_batch_size == 0 {
+            return Err(ConfigError::ValidationError(
+                "pipeline.max_batch_size must be > 0".into(),
+            ));
+        }
         if self.limits.max_file_size_mb == 0 {
             return Err(ConfigError::ValidationError(
                 "limits.max_file_size_mb must be > 0".into(),
@@ -27,6 +61,11 @@ impl Config {
                 "limits.max_image_dimension must be > 0".into(),
             ));
         }
+        if self.limits.max_megapixels == 0 {
+            return Err(ConfigError::ValidationError(
+                "limits.max_megapixels must be > 0".into(),
+            ));
+        }
         if self.limits.decode_timeout_ms == 0 {
             return Err(ConfigError::ValidationError(
                 "limits.decode_timeout_ms must be > 0".into(),
@@ -42,27 +81,194 @@ impl Config {
                 "limits.llm_timeout_ms must be > 0".into(),
             ));
         }
+        if !POOL_CHECKOUTS.contains(&self.embedding.pool_checkout.as_str()) {
+            return Err(ConfigError::ValidationError(format!(
+                "embedding.pool_checkout must be one of {}, got {:?}",
+                POOL_CHECKOUTS.join(", "),
+                self.embedding.pool_checkout
+            )));
+        }
+        if !OPTIMIZATION_LEVELS.contains(&self.embedding.runtime.optimization_level.as_str()) {
+            return Err(ConfigError::ValidationError(format!(
+                "embedding.runtime.optimization_level must be one of {}, got {:?}",
+                OPTIMIZATION_LEVELS.join(", "),
+                self.embedding.runtime.optimization_level
+            )));
+        }
         if self.thumbnail.size == 0 {
             return Err(ConfigError::ValidationError(
                 "thumbnail.size must be > 0".into(),
             ));
         }
+        if !THUMBNAIL_FORMATS.contains(&self.thumbnail.format.as_str()) {
+            return Err(ConfigError::ValidationError(format!(
+                "thumbnail.format must be one of {}, got {:?}",
+                THUMBNAIL_FORMATS.join(", "),
+                self.thumbnail.format
+            )));
+        }
+        if !(1..=100).contains(&self.thumbnail.quality) {
+            return Err(ConfigError::ValidationError(format!(
+                "thumbnail.quality must be in 1..=100, got {}",
+                self.thumbnail.quality
+            )));
+        }
+        if !self.thumbnail.renditions.is_empty() && self.thumbnail.output_dir.is_empty() {
+            return Err(ConfigError::ValidationError(
+                "thumbnail.output_dir must be set when thumbnail.renditions are configured".into(),
+            ));
+        }
+        for (i, rendition) in self.thumbnail.renditions.iter().enumerate() {
+            let valid_name = !rendition.name.is_empty()
+                && rendition
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid_name {
+                return Err(ConfigError::ValidationError(format!(
+                    "thumbnail.renditions name must be non-empty letters, digits, '-' or '_', got {:?}",
+                    rendition.name
+                )));
+            }
+            if self.thumbnail.renditions[..i]
+                .iter()
+                .any(|other| other.name == rendition.name)
+            {
+                return Err(ConfigError::ValidationError(format!(
+                    "thumbnail.renditions lists {:?} more than once",
+                    rendition.name
+                )));
+            }
+            if rendition.size == 0 || rendition.height == Some(0) {
+                return Err(ConfigError::ValidationError(format!(
+                    "thumbnail.renditions {:?}: size and height must be > 0",
+                    rendition.name
+                )));
+            }
+            if !THUMBNAIL_FORMATS.contains(&rendition.format.as_str()) {
+                return Err(ConfigError::ValidationError(format!(
+                    "thumbnail.renditions {:?}: format must be one of {}, got {:?}",
+                    rendition.name,
+                    THUMBNAIL_FORMATS.join(", "),
+                    rendition.format
+                )));
+            }
+            if !RENDITION_FITS.contains(&rendition.fit.as_str()) {
+                return Err(ConfigError::ValidationError(format!(
+                    "thumbnail.renditions {:?}: fit must be one of {}, got {:?}",
+                    rendition.name,
+                    RENDITION_FITS.join(", "),
+                    rendition.fit
+                )));
+            }
+            if !(1..=100).contains(&rendition.quality) {
+                return Err(ConfigError::ValidationError(format!(
+                    "thumbnail.renditions {:?}: quality must be in 1..=100, got {}",
+                    rendition.name, rendition.quality
+                )));
+            }
+        }
+        for (key, value) in [
+            ("components_x", self.placeholder.components_x),
+            ("components_y", self.placeholder.components_y),
+        ] {
+            if !(1..=9).contains(&value) {
+                return Err(ConfigError::ValidationError(format!(
+                    "placeholder.{key} must be in 1..=9, got {value}"
+                )));
+            }
+        }
+        if self.hashing.perceptual.is_empty() {
+            return Err(ConfigError::ValidationError(
+                "hashing.perceptual must list at least one algorithm".into(),
+            ));
+        }
+        for (i, entry) in self.hashing.perceptual.iter().enumerate() {
+            if !PERCEPTUAL_ALGORITHMS.contains(&entry.algorithm.as_str()) {
+                return Err(ConfigError::ValidationError(format!(
+                    "hashing.perceptual algorithm must be one of {}, got {:?}",
+                    PERCEPTUAL_ALGORITHMS.join(", "),
+                    entry.algorithm
+                )));
+            }
+            if !(4..=64).contains(&entry.size) {
+                return Err(ConfigError::ValidationError(format!(
+                    "hashing.perceptual size for {} must be between 4 and 64",
+                    entry.algorithm
+                )));
+            }
+            if self.hashing.perceptual[..i]
+                .iter()
+                .any(|earlier| earlier.algorithm == entry.algorithm)
+            {
+                return Err(ConfigError::ValidationError(format!(
+                    "hashing.perceptual lists {} more than once",
+                    entry.algorithm
+                )));
+            }
+        }
+        if self.palette.colors == 0 || self.palette.colors > 16 {
+            return Err(ConfigError::ValidationError(
+                "palette.colors must be between 1 and 16".into(),
+            ));
+        }
+        if self.palette.sample_size == 0 {
+            return Err(ConfigError::ValidationError(
+                "palette.sample_size must be > 0".into(),
+            ));
+        }
+        if self.quality.sample_size < 3 {
+            return Err(ConfigError::ValidationError(
+                "quality.sample_size must be >= 3".into(),
+            ));
+        }
         if self.tagging.min_confidence < 0.0 || self.tagging.min_confidence > 1.0 {
             return Err(ConfigError::ValidationError(
                 "tagging.min_confidence must be between 0.0 and 1.0".into(),
             ));
         }
+        let max_distance = self.geocoding.max_distance_km;
+        if max_distance.is_nan() || max_distance <= 0.0 {
+            return Err(ConfigError::ValidationError(
+                "geocoding.max_distance_km must be > 0".into(),
+            ));
+        }
+        let privacy = &self.output.privacy;
+        if privacy.enabled {
+            if !matches!(privacy.gps.as_str(), "keep" | "coarsen" | "drop") {
+                return Err(ConfigError::ValidationError(format!(
+                    "output.privacy.gps must be \"keep\", \"coarsen\" or \"drop\", got {:?}",
+                    privacy.gps
+                )));
+            }
+            let grid = privacy.gps_grid_degrees;
+            if privacy.gps == "coarsen" && (grid.is_nan() || grid <= 0.0 || grid > 90.0) {
+                return Err(ConfigError::ValidationError(
+                    "output.privacy.gps_grid_degrees must be between 0 and 90".into(),
+                ));
+            }
+            if privacy.hash_paths && path_hash_secret(privacy).is_none() {
+                return Err(ConfigError::ValidationError(format!(
+                    "output.privacy.path_hash_key is not set ({}); \
+                     set it or disable output.privacy.hash_paths",
+                    privacy.path_hash_key
+                )));
+            }
+        }
 
-        // Auto-derive image_size from model name to prevent desync
-        let expected_size = EmbeddingConfig::image_size_for_model(&self.embedding.model);
-        if self.embedding.image_size != expected_size {
-            tracing::warn!(
-                "Overriding embedding.image_size {} → {} to match model '{}'",
-                self.embedding.image_size,
-                expected_size,
-                self.embedding.model
-            );
-            self.embedding.image_size = expected_size;
+        // Auto-derive image_size from the model's manifest to prevent desync.
+        // An unknown model is left alone; loading it reports the missing manifest,
+        // and loading its text encoder checks that one is usable.
+        if let Ok(manifest) = ModelManifest::resolve(&self.model_dir(), &self.embedding.model) {
+            if self.embedding.image_size != manifest.image_size {
+                tracing::warn!(
+                    "Overriding embedding.image_size {} → {} to match model '{}'",
+                    self.embedding.image_size,
+                    manifest.image_size,
+                    self.embedding.model
+                );
+                self.embedding.image_size = manifest.image_size;
+            }
         }
 
         if self.tagging.progressive.enabled && self.tagging.relevance.enabled {
@@ -80,6 +286,7 @@ impl Config {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::RenditionConfig;
 
     #[test]
     fn test_default_config_passes_validation() {
@@ -95,6 +302,93 @@ mod tests {
         assert!(err.to_string().contains("parallel_workers"));
     }
 
+    #[test]
+    fn test_validate_rejects_zero_animation_frames() {
+        let mut config = Config::default();
+        config.processing.animation_frames = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("animation_frames"));
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_max_depth() {
+        let mut config = Config::default();
+        config.processing.max_depth = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("max_depth"));
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_exclude_glob() {
+        let mut config = Config::default();
+        config.processing.exclude = vec!["@eaDir".into(), "*.{raw,jpg".into()];
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("processing.exclude"));
+        assert!(err.to_string().contains("*.{raw,jpg"));
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_perceptual_hashes() {
+        let mut config = Config::default();
+        config.hashing.perceptual.clear();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("hashing.perceptual"));
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_perceptual_algorithm() {
+        let mut config = Config::default();
+        config.hashing.perceptual[0].algorithm = "md5".into();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("md5"));
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_perceptual_size() {
+        let mut config = Config::default();
+        config.hashing.perceptual[0].size = 2;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("between 4 and 64"));
+    }
+
+    #[test]
+    fn test_validate_rejects_duplicate_perceptual_algorithm() {
+        let mut config = Config::default();
+        config
+            .hashing
+            .perceptual
+            .push(config.hashing.perceptual[0].clone());
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("more than once"));
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_palette_colors() {
+        let mut config = Config::default();
+        config.palette.colors = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("palette.colors"));
+
+        config.palette.colors = 17;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_palette_sample_size() {
+        let mut config = Config::default();
+        config.palette.sample_size = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("palette.sample_size"));
+    }
+
+    #[test]
+    fn test_validate_rejects_small_quality_sample_size() {
+        let mut config = Config::default();
+        config.quality.sample_size = 2;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("quality.sample_size"));
+    }
+
     #[test]
     fn test_validate_rejects_zero_thumbnail_size() {
         let mut config = Config::default();
@@ -103,6 +397,112 @@ mod tests {
         assert!(err.to_string().contains("thumbnail.size"));
     }
 
+    #[test]
+    fn test_validate_rejects_unknown_thumbnail_format() {
+        let mut config = Config::default();
+        config.thumbnail.format = "gif".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("thumbnail.format"));
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_thumbnail_quality() {
+        let mut config = Config::default();
+        config.thumbnail.quality = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("thumbnail.quality"));
+    }
+
+    fn rendition(name: &str) -> RenditionConfig {
+        RenditionConfig {
+            name: name.to_string(),
+            ..RenditionConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_renditions() {
+        let mut config = Config::default();
+        config.thumbnail.output_dir = "/tmp/thumbs".to_string();
+        config.thumbnail.renditions = vec![rendition("small"), rendition("card_2x")];
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_validate_rejects_renditions_without_output_dir() {
+        let mut config = Config::default();
+        config.thumbnail.renditions = vec![rendition("small")];
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("thumbnail.output_dir"));
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_rendition_names() {
+        for names in [vec!["../up"], vec![""], vec!["small", "small"]] {
+            let mut config = Config::default();
+            config.thumbnail.output_dir = "/tmp/thumbs".to_string();
+            config.thumbnail.renditions = names.into_iter().map(rendition).collect();
+            let err = config.validate().unwrap_err();
+            assert!(err.to_string().contains("thumbnail.renditions"));
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_rendition_fit() {
+        let mut config = Config::default();
+        config.thumbnail.output_dir = "/tmp/thumbs".to_string();
+        config.thumbnail.renditions = vec![RenditionConfig {
+            fit: "stretch".to_string(),
+            ..rendition("small")
+        }];
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("fit must be one of"));
+    }
+
+    #[test]
+    fn test_validate_rejects_out_of_range_blurhash_components() {
+        let mut config = Config::default();
+        config.placeholder.components_x = 10;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("placeholder.components_x"));
+
+        let mut config = Config::default();
+        config.placeholder.components_y = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("placeholder.components_y"));
+    }
+
+    #[test]
+    fn test_pool_size_zero_follows_workers() {
+        let mut config = Config::default();
+        config.embedding.pool_size = 0;
+        assert!(config.validate().is_ok());
+
+        let cpus = crate::config::available_cpus();
+        assert_eq!(config.embedding.effective_pool_size(1), 1);
+        assert_eq!(config.embedding.effective_pool_size(cpus + 8), cpus);
+        config.embedding.pool_size = 3;
+        assert_eq!(config.embedding.effective_pool_size(16), 3);
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_pool_checkout() {
+        let mut config = Config::default();
+        config.embedding.pool_checkout = "random".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("embedding.pool_checkout"));
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_optimization_level() {
+        let mut config = Config::default();
+        config.embedding.runtime.optimization_level = "max".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("embedding.runtime.optimization_level"));
+    }
+
     #[test]
     fn test_validate_rejects_zero_timeout() {
         let mut config = Config::default();
@@ -123,6 +523,63 @@ mod tests {
         assert!(err.to_string().contains("min_confidence"));
     }
 
+    #[test]
+    fn test_validate_rejects_non_positive_geocoding_distance() {
+        let mut config = Config::default();
+        config.geocoding.max_distance_km = 0.0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("max_distance_km"));
+
+        config.geocoding.max_distance_km = f64::NAN;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_privacy_gps_mode() {
+        let mut config = Config::default();
+        config.output.privacy.enabled = true;
+        config.output.privacy.path_hash_key = "secret".into();
+        config.output.privacy.gps = "blur".into();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("output.privacy.gps"));
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_privacy_grid() {
+        let mut config = Config::default();
+        config.output.privacy.enabled = true;
+        config.output.privacy.path_hash_key = "secret".into();
+        config.output.privacy.gps = "coarsen".into();
+        config.output.privacy.gps_grid_degrees = 0.0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("gps_grid_degrees"));
+    }
+
+    #[test]
+    fn test_validate_rejects_missing_privacy_key() {
+        let mut config = Config::default();
+        config.output.privacy.enabled = true;
+        config.output.privacy.path_hash_key = "${PHOTON_TEST_UNSET_PRIVACY_KEY}".into();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("path_hash_key"));
+
+        // Set but empty is as good as unset
+        std::env::set_var("PHOTON_TEST_EMPTY_VALIDATE_KEY", "");
+        config.output.privacy.path_hash_key = "${PHOTON_TEST_EMPTY_VALIDATE_KEY}".into();
+        assert!(config.validate().is_err());
+
+        config.output.privacy.hash_paths = false;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_max_batch_size() {
+        let mut config = Config::default();
+        config.pipeline.max_batch_size = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("pipeline.max_batch_size"));
+    }
+
     #[test]
     fn test_validate_rejects_zero_buffer_size() {
         let mut config = Config::default();
@@ -147,6 +604,14 @@ mod tests {
         assert!(err.to_string().contains("max_image_dimension"));
     }
 
+    #[test]
+    fn test_validate_rejects_zero_max_megapixels() {
+        let mut config = Config::default();
+        config.limits.max_megapixels = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("max_megapixels"));
+    }
+
     #[test]
     fn test_validate_rejects_zero_embed_timeout() {
         let mut config = Config::default();
@@ -189,4 +654,40 @@ mod tests {
         config.validate().unwrap();
         assert_eq!(config.embedding.image_size, 224);
     }
+
+    /// Model dir holding a 1152-d so400m manifest and no text encoder.
+    fn so400m_model_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let model = dir.path().join("siglip-so400m-patch14-384");
+        std::fs::create_dir_all(&model).unwrap();
+        std::fs::write(
+            model.join("manifest.json"),
+            r#"{"embedding_dim": 1152, "image_size": 384,
+                "mean": [0.5, 0.5, 0.5], "std": [0.5, 0.5, 0.5],
+                "text_max_length": 64, "vision_output": "pooler_output",
+                "text_output": "pooler_output", "logit_scale": 117.33, "logit_bias": -12.93}"#,
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_validate_takes_image_size_from_model_manifest() {
+        let dir = so400m_model_dir();
+        let mut config = Config::default();
+        config.general.model_dir = dir.path().to_path_buf();
+        config.embedding.model = "siglip-so400m-patch14-384".to_string();
+        config.validate().unwrap();
+        assert_eq!(config.embedding.image_size, 384);
+    }
+
+    #[test]
+    fn test_validate_leaves_text_encoder_to_load_time() {
+        // dedup and models list never load a text encoder
+        let dir = so400m_model_dir();
+        let mut config = Config::default();
+        config.general.model_dir = dir.path().to_path_buf();
+        config.embedding.model = "siglip-so400m-patch14-384".to_string();
+        assert!(config.validate().is_ok());
+    }
 }
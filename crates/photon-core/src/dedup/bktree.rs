@@ -0,0 +1,133 @@
+//! BK-tree for radius queries under Hamming distance.
+//!
+//! Each child edge is labelled with its distance to the parent; by the
+//! triangle inequality, a query within radius `r` of `q` only needs to follow
+//! edges labelled `d(q, parent) ± r`.
+
+/// A hash and its caller-supplied id, plus labelled child edges.
+struct Node {
+    hash: Vec<u8>,
+    id: usize,
+    children: Vec<(u32, usize)>,
+}
+
+/// BK-tree over equal-length byte hashes.
+#[derive(Default)]
+pub(crate) struct BkTree {
+    nodes: Vec<Node>,
+}
+
+impl BkTree {
+    /// Insert a hash under `id`. Identical hashes are kept as separate entries.
+    pub fn insert(&mut self, hash: Vec<u8>, id: usize) {
+        let new_index = self.nodes.len();
+        if new_index > 0 {
+            let mut current = 0;
+            loop {
+                let distance = hamming(&self.nodes[current].hash, &hash);
+                match self.nodes[current]
+                    .children
+                    .iter()
+                    .find(|&&(d, _)| d == distance)
+                {
+                    Some(&(_, child)) => current = child,
+                    None => {
+                        self.nodes[current].children.push((distance, new_index));
+                        break;
+                    }
+                }
+            }
+        }
+        self.nodes.push(Node {
+            hash,
+            id,
+            children: Vec::new(),
+        });
+    }
+
+    /// Ids and distances of every entry within `max_distance` of `hash`.
+    pub fn find(&self, hash: &[u8], max_distance: u32) -> Vec<(usize, u32)> {
+        let mut found = Vec::new();
+        if self.nodes.is_empty() {
+            return found;
+        }
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let distance = hamming(&node.hash, hash);
+            if distance <= max_distance {
+                found.push((node.id, distance));
+            }
+            let low = distance.saturating_sub(max_distance);
+            let high = distance + max_distance;
+            stack.extend(
+                node.children
+                    .iter()
+                    .filter(|&&(d, _)| (low..=high).contains(&d))
+                    .map(|&(_, child)| child),
+            );
+        }
+        found
+    }
+}
+
+/// Number of differing bits between two equal-length hashes.
+pub(crate) fn hamming(a: &[u8], b: &[u8]) -> u32 {
+    a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hamming() {
+        assert_eq!(hamming(&[0b1010, 0xFF], &[0b0101, 0xFF]), 4);
+        assert_eq!(hamming(&[1, 2, 3], &[1, 2, 3]), 0);
+    }
+
+    #[test]
+    fn test_find_matches_brute_force() {
+        // Deterministic pseudo-random 4-byte hashes
+        let mut state = 0x9E3779B9u32;
+        let hashes: Vec<Vec<u8>> = (0..300)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state.to_le_bytes().to_vec()
+            })
+            .collect();
+
+        let mut tree = BkTree::default();
+        for (id, hash) in hashes.iter().enumerate() {
+            tree.insert(hash.clone(), id);
+        }
+
+        for radius in [0, 3, 8, 12] {
+            for query in hashes.iter().step_by(17) {
+                let mut found = tree.find(query, radius);
+                found.sort_unstable();
+                let expected: Vec<(usize, u32)> = hashes
+                    .iter()
+                    .enumerate()
+                    .map(|(id, h)| (id, hamming(query, h)))
+                    .filter(|&(_, d)| d <= radius)
+                    .collect();
+                assert_eq!(found, expected, "radius {radius}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_duplicate_hashes_and_empty_tree() {
+        let mut tree = BkTree::default();
+        assert!(tree.find(&[0], 8).is_empty());
+
+        tree.insert(vec![7], 0);
+        tree.insert(vec![7], 1);
+        let mut found = tree.find(&[7], 0);
+        found.sort_unstable();
+        assert_eq!(found, vec![(0, 0), (1, 0)]);
+    }
+}
@@ -0,0 +1,451 @@
+//! Duplicate detection over processed images.
+//!
+//! Three passes feed one union-find: exact duplicates share a BLAKE3 content
+//! hash, near-duplicates have perceptual hashes within a Hamming distance
+//! (found with a BK-tree), and semantic duplicates have embeddings above a
+//! cosine similarity. Matching is single-linkage, so a cluster can chain
+//! images that are each close to a neighbor but not to every other member.
+
+pub(crate) mod bktree;
+
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use image_hasher::ImageHash;
+
+use crate::math::l2_normalize;
+use crate::types::{DuplicateCluster, DuplicateKind, DuplicateMember, ProcessedImage};
+
+use bktree::{hamming, BkTree};
+
+/// Default algorithm, and the one behind `perceptual_hash` in output written
+/// before `perceptual_hashes` existed.
+const LEGACY_ALGORITHM: &str = "double_gradient";
+
+/// Thresholds for [`find_duplicates`].
+#[derive(Debug, Clone)]
+pub struct DedupOptions {
+    /// Perceptual hash to compare (a key of `perceptual_hashes`). Records
+    /// hashed with other algorithms sit out the near-duplicate pass.
+    pub algorithm: String,
+
+    /// Maximum Hamming distance for near-duplicates; `None` disables the pass
+    pub max_distance: Option<u32>,
+
+    /// Minimum embedding cosine similarity for semantic duplicates; `None`
+    /// disables the pass
+    pub min_similarity: Option<f32>,
+}
+
+impl Default for DedupOptions {
+    fn default() -> Self {
+        Self {
+            algorithm: LEGACY_ALGORITHM.to_string(),
+            max_distance: Some(10),
+            min_similarity: Some(0.95),
+        }
+    }
+}
+
+/// Group duplicate images and pick the copy to keep in each group.
+///
+/// Images without the configured perceptual hash or without an embedding
+/// only take part in the passes they have data for. Clusters come back
+/// strongest kind first, then largest first.
+pub fn find_duplicates(images: &[ProcessedImage], options: &DedupOptions) -> Vec<DuplicateCluster> {
+    let mut sets = DisjointSets::new(images.len());
+
+    // Exact pass. Copies of the same content share every other signal too,
+    // so only the first of each goes on to the later passes.
+    let mut first_by_hash: HashMap<&str, usize> = HashMap::new();
+    let mut representatives = Vec::new();
+    for (i, image) in images.iter().enumerate() {
+        if image.content_hash.is_empty() {
+            representatives.push(i);
+            continue;
+        }
+        match first_by_hash.entry(&image.content_hash) {
+            Entry::Occupied(first) => sets.union(*first.get(), i, DuplicateKind::Exact),
+            Entry::Vacant(slot) => {
+                slot.insert(i);
+                representatives.push(i);
+            }
+        }
+    }
+
+    let hashes: Vec<Option<Vec<u8>>> = images
+        .iter()
+        .map(|image| decode_hash(image, &options.algorithm))
+        .collect();
+    if let Some(max_distance) = options.max_distance {
+        // Hashes of different sizes aren't comparable, so each size gets its own tree
+        let mut trees: HashMap<usize, BkTree> = HashMap::new();
+        for &i in &representatives {
+            let Some(hash) = &hashes[i] else {
+                continue;
+            };
+            let tree = trees.entry(hash.len()).or_default();
+            for (j, _) in tree.find(hash, max_distance) {
+                sets.union(j, i, DuplicateKind::Near);
+            }
+            tree.insert(hash.clone(), i);
+        }
+
+        let other_algorithm = representatives
+            .iter()
+            .filter(|&&i| hashes[i].is_none() && images[i].perceptual_hash.is_some())
+            .count();
+        if other_algorithm > 0 {
+            tracing::warn!(
+                "{other_algorithm} images have no {} perceptual hash and were left out of near-duplicate detection",
+                options.algorithm
+            );
+        }
+    }
+
+    let embeddings: Vec<Option<Vec<f32>>> = images
+        .iter()
+        .map(|image| (!image.embedding.is_empty()).then(|| l2_normalize(&image.embedding)))
+        .collect();
+    if let Some(min_similarity) = options.min_similarity {
+        // Brute force over all pairs: fine for tens of thousands of images
+        let candidates: Vec<usize> = representatives
+            .iter()
+            .copied()
+            .filter(|&i| embeddings[i].is_some())
+            .collect();
+        for (k, &i) in candidates.iter().enumerate() {
+            for &j in &candidates[k + 1..] {
+                if sets.find(i) == sets.find(j) {
+                    continue;
+                }
+                if cosine(&embeddings[i], &embeddings[j]).is_some_and(|s| s >= min_similarity) {
+                    sets.union(i, j, DuplicateKind::Semantic);
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..images.len() {
+        groups.entry(sets.find(i)).or_default().push(i);
+    }
+
+    let mut clusters: Vec<DuplicateCluster> = groups
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(root, mut members)| {
+            members.sort_by(|&a, &b| keep_order(&images[a], &images[b]));
+            let keep = members[0];
+            let member = |i: usize| {
+                let image = &images[i];
+                let compared = i != keep;
+                DuplicateMember {
+                    file_path: image.file_path.clone(),
+                    file_name: image.file_name.clone(),
+                    content_hash: image.content_hash.clone(),
+                    width: image.width,
+                    height: image.height,
+                    file_size: image.file_size,
+                    sharpness: image.quality.as_ref().map(|q| q.sharpness),
+                    hamming_distance: match (&hashes[keep], &hashes[i]) {
+                        (Some(a), Some(b)) if compared && a.len() == b.len() => Some(hamming(a, b)),
+                        _ => None,
+                    },
+                    similarity: cosine(&embeddings[keep], &embeddings[i])
+                        .filter(|_| compared)
+                        .map(|s| (s * 10_000.0).round() / 10_000.0),
+                }
+            };
+            DuplicateCluster {
+                kind: sets.kind[root],
+                keep: member(keep),
+                duplicates: members[1..].iter().map(|&i| member(i)).collect(),
+            }
+        })
+        .collect();
+
+    clusters.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then(b.duplicates.len().cmp(&a.duplicates.len()))
+            .then_with(|| a.keep.file_path.cmp(&b.keep.file_path))
+            .then_with(|| a.keep.content_hash.cmp(&b.keep.content_hash))
+    });
+    clusters
+}
+
+/// Raw bytes of the image's perceptual hash for `algorithm`, if present.
+///
+/// The algorithm is read from the `perceptual_hashes` key, never assumed.
+/// Output written before that map existed has only `perceptual_hash`, which
+/// was always `double_gradient`.
+fn decode_hash(image: &ProcessedImage, algorithm: &str) -> Option<Vec<u8>> {
+    let encoded = match image.perceptual_hashes.get(algorithm) {
+        Some(hash) => hash,
+        None if image.perceptual_hashes.is_empty() && algorithm == LEGACY_ALGORITHM => {
+            image.perceptual_hash.as_ref()?
+        }
+        None => return None,
+    };
+    ImageHash::<Vec<u8>>::from_base64(encoded)
+        .ok()
+        .map(|hash| hash.as_bytes().to_vec())
+}
+
+/// Cosine similarity of two L2-normalized embeddings of the same length.
+fn cosine(a: &Option<Vec<f32>>, b: &Option<Vec<f32>>) -> Option<f32> {
+    match (a, b) {
+        (Some(a), Some(b)) if a.len() == b.len() => Some(a.iter().zip(b).map(|(x, y)| x * y).sum()),
+        _ => None,
+    }
+}
+
+/// Best copy first: most pixels, then largest file, then sharpest.
+/// Ties fall back to path order so the choice is deterministic.
+fn keep_order(a: &ProcessedImage, b: &ProcessedImage) -> Ordering {
+    let pixels = |image: &ProcessedImage| u64::from(image.width) * u64::from(image.height);
+    let sharpness = |image: &ProcessedImage| image.quality.as_ref().map(|q| q.sharpness);
+    pixels(b)
+        .cmp(&pixels(a))
+        .then(b.file_size.cmp(&a.file_size))
+        .then_with(|| {
+            sharpness(b)
+                .partial_cmp(&sharpness(a))
+                .unwrap_or(Ordering::Equal)
+        })
+        .then_with(|| a.file_path.cmp(&b.file_path))
+}
+
+/// Union-find that tracks the weakest link joining each set.
+struct DisjointSets {
+    parent: Vec<usize>,
+    /// Valid at roots only
+    kind: Vec<DuplicateKind>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            kind: vec![DuplicateKind::Exact; len],
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize, kind: DuplicateKind) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return;
+        }
+        self.parent[root_b] = root_a;
+        self.kind[root_a] = kind.max(self.kind[root_a]).max(self.kind[root_b]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::QualityMetrics;
+    use std::path::PathBuf;
+
+    fn image(name: &str, content_hash: &str) -> ProcessedImage {
+        ProcessedImage {
+            file_path: Some(PathBuf::from(format!("/photos/{name}"))),
+            file_name: name.to_string(),
+            content_hash: content_hash.to_string(),
+            width: 100,
+            height: 100,
+            format: "jpeg".to_string(),
+            file_size: 1000,
+            ..ProcessedImage::default()
+        }
+    }
+
+    fn with_hash(mut image: ProcessedImage, bytes: &[u8]) -> ProcessedImage {
+        let hash = ImageHash::<Vec<u8>>::from_bytes(bytes).unwrap().to_base64();
+        image
+            .perceptual_hashes
+            .insert(LEGACY_ALGORITHM.to_string(), hash);
+        image
+    }
+
+    fn names(cluster: &DuplicateCluster) -> Vec<&str> {
+        std::iter::once(&cluster.keep)
+            .chain(&cluster.duplicates)
+            .map(|m| m.file_name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_exact_duplicates() {
+        let images = vec![
+            image("a.jpg", "h1"),
+            image("b.jpg", "h2"),
+            image("c.jpg", "h1"),
+        ];
+        let clusters = find_duplicates(&images, &DedupOptions::default());
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].kind, DuplicateKind::Exact);
+        assert_eq!(names(&clusters[0]), ["a.jpg", "c.jpg"]);
+    }
+
+    #[test]
+    fn test_keep_prefers_resolution_then_size_then_sharpness() {
+        let mut small = image("small.jpg", "h1");
+        small.width = 50;
+        small.file_size = 5000;
+        let mut big = image("big.jpg", "h1");
+        big.file_size = 800;
+        let mut sharp = image("sharp.jpg", "h1");
+        sharp.file_size = 800;
+        sharp.quality = Some(QualityMetrics {
+            sharpness: 500.0,
+            noise: 0.0,
+            colorfulness: 0.0,
+            mean_luminance: 0.0,
+            clipped_highlights_pct: 0.0,
+            clipped_shadows_pct: 0.0,
+            luminance_histogram: vec![],
+        });
+        let heavy = image("heavy.jpg", "h1");
+
+        let clusters = find_duplicates(&[small, big, sharp, heavy], &DedupOptions::default());
+        assert_eq!(
+            names(&clusters[0]),
+            ["heavy.jpg", "sharp.jpg", "big.jpg", "small.jpg"]
+        );
+        assert_eq!(clusters[0].keep.sharpness, None);
+        assert_eq!(clusters[0].duplicates[0].sharpness, Some(500.0));
+    }
+
+    #[test]
+    fn test_near_duplicates_within_distance() {
+        let images = vec![
+            with_hash(image("a.jpg", "h1"), &[0b0000_0000, 0]),
+            with_hash(image("b.jpg", "h2"), &[0b0000_0111, 0]),
+            with_hash(image("c.jpg", "h3"), &[0xFF, 0xFF]),
+        ];
+        let options = DedupOptions {
+            max_distance: Some(3),
+            ..DedupOptions::default()
+        };
+        let clusters = find_duplicates(&images, &options);
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].kind, DuplicateKind::Near);
+        assert_eq!(names(&clusters[0]), ["a.jpg", "b.jpg"]);
+        assert_eq!(clusters[0].duplicates[0].hamming_distance, Some(3));
+
+        let strict = DedupOptions {
+            max_distance: Some(2),
+            ..DedupOptions::default()
+        };
+        assert!(find_duplicates(&images, &strict).is_empty());
+    }
+
+    #[test]
+    fn test_near_ignores_other_algorithms_and_sizes() {
+        let mut phash_only = image("b.jpg", "h2");
+        phash_only
+            .perceptual_hashes
+            .insert("phash".to_string(), "AAA".to_string());
+        let images = vec![
+            with_hash(image("a.jpg", "h1"), &[0, 0]),
+            phash_only,
+            with_hash(image("c.jpg", "h3"), &[0, 0, 0]),
+        ];
+        assert!(find_duplicates(&images, &DedupOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_legacy_perceptual_hash_field() {
+        let mut a = image("a.jpg", "h1");
+        a.perceptual_hash = Some("AAA".to_string());
+        let mut b = image("b.jpg", "h2");
+        b.perceptual_hash = Some("AAE".to_string());
+        let images = [a, b];
+
+        let clusters = find_duplicates(&images, &DedupOptions::default());
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].duplicates[0].hamming_distance, Some(1));
+
+        // A bare hash is only ever double_gradient
+        let phash = DedupOptions {
+            algorithm: "phash".to_string(),
+            ..DedupOptions::default()
+        };
+        assert!(find_duplicates(&images, &phash).is_empty());
+    }
+
+    #[test]
+    fn test_near_skips_records_hashed_with_another_algorithm() {
+        // A phash-only run appended to a double_gradient run
+        let mut a = with_hash(image("a.jpg", "h1"), &[0, 0]);
+        a.perceptual_hash = a.perceptual_hashes.get(LEGACY_ALGORITHM).cloned();
+        let mut b = image("b.jpg", "h2");
+        b.perceptual_hash = Some("AAA".to_string());
+        b.perceptual_hashes
+            .insert("phash".to_string(), "AAA".to_string());
+
+        assert!(find_duplicates(&[a.clone(), b.clone()], &DedupOptions::default()).is_empty());
+        let phash = DedupOptions {
+            algorithm: "phash".to_string(),
+            ..DedupOptions::default()
+        };
+        assert!(find_duplicates(&[a, b], &phash).is_empty());
+    }
+
+    #[test]
+    fn test_semantic_duplicates() {
+        let mut a = image("a.jpg", "h1");
+        a.embedding = vec![1.0, 0.0, 0.0];
+        let mut b = image("b.jpg", "h2");
+        b.embedding = vec![0.99, 0.1, 0.0];
+        let mut c = image("c.jpg", "h3");
+        c.embedding = vec![0.0, 1.0, 0.0];
+
+        let clusters =
+            find_duplicates(&[a.clone(), b.clone(), c.clone()], &DedupOptions::default());
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].kind, DuplicateKind::Semantic);
+        assert_eq!(names(&clusters[0]), ["a.jpg", "b.jpg"]);
+        let similarity = clusters[0].duplicates[0].similarity.unwrap();
+        assert!(similarity > 0.99 && similarity < 1.0, "{similarity}");
+
+        let disabled = DedupOptions {
+            min_similarity: None,
+            ..DedupOptions::default()
+        };
+        assert!(find_duplicates(&[a, b, c], &disabled).is_empty());
+    }
+
+    #[test]
+    fn test_cluster_reports_weakest_link() {
+        // a and b are exact copies; c is only semantically close to them
+        let mut a = image("a.jpg", "h1");
+        a.embedding = vec![1.0, 0.0];
+        let mut b = image("b.jpg", "h1");
+        b.embedding = vec![1.0, 0.0];
+        let mut c = image("c.jpg", "h2");
+        c.embedding = vec![1.0, 0.05];
+        let d = image("d.jpg", "h3");
+        let e = image("e.jpg", "h3");
+
+        let clusters = find_duplicates(&[a, b, c, d, e], &DedupOptions::default());
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].kind, DuplicateKind::Exact);
+        assert_eq!(names(&clusters[0]), ["d.jpg", "e.jpg"]);
+        assert_eq!(clusters[1].kind, DuplicateKind::Semantic);
+        assert_eq!(names(&clusters[1]), ["a.jpg", "b.jpg", "c.jpg"]);
+    }
+}
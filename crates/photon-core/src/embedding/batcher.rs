@@ -0,0 +1,255 @@
+//! Cross-image dynamic batching for the embedding stage.
+//!
+//! Concurrent workers each submit one preprocessed tensor. A collector task
+//! queues them and flushes a batch when it holds `max_batch_size` tensors or
+//! when `max_wait` has passed since the first one arrived, whichever comes
+//! first. The batch runs as a single ONNX call on the blocking pool, and each
+//! worker gets its own vector back over a oneshot channel.
+//!
+//! Flushed batches run independently, so with a session pool several can be
+//! in flight at once.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ndarray::Array4;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::error::PipelineError;
+
+/// Runs one batch: tensors and their paths in, one embedding per tensor out.
+pub(crate) type BatchFn =
+    dyn Fn(&[Array4<f32>], &[PathBuf]) -> Result<Vec<Vec<f32>>, PipelineError> + Send + Sync;
+
+/// One image waiting for its embedding.
+struct Request {
+    tensor: Array4<f32>,
+    path: PathBuf,
+    reply: oneshot::Sender<Result<Vec<f32>, PipelineError>>,
+}
+
+/// Handle for submitting tensors to the collector task.
+pub(crate) struct EmbedBatcher {
+    sender: mpsc::Sender<Request>,
+    /// `batch_counts[n]` is how many batches of size `n` were run.
+    batch_counts: Arc<Vec<AtomicU64>>,
+}
+
+impl EmbedBatcher {
+    /// Spawn the collector on the current tokio runtime.
+    pub fn start(embed: Arc<BatchFn>, max_batch_size: usize, max_wait: Duration) -> Self {
+        let max_batch_size = max_batch_size.max(1);
+        let (sender, receiver) = mpsc::channel(max_batch_size * 4);
+        let batch_counts = Arc::new((0..=max_batch_size).map(|_| AtomicU64::new(0)).collect());
+        tokio::spawn(collect(
+            receiver,
+            embed,
+            max_batch_size,
+            max_wait,
+            Arc::clone(&batch_counts),
+        ));
+        Self {
+            sender,
+            batch_counts,
+        }
+    }
+
+    /// Queue a tensor and wait for its embedding.
+    pub async fn embed(
+        &self,
+        tensor: Array4<f32>,
+        path: PathBuf,
+    ) -> Result<Vec<f32>, PipelineError> {
+        let (reply, response) = oneshot::channel();
+        let closed = || PipelineError::Embedding {
+            path: path.clone(),
+            message: "Embedding batcher stopped".to_string(),
+        };
+        self.sender
+            .send(Request {
+                tensor,
+                path: path.clone(),
+                reply,
+            })
+            .await
+            .map_err(|_| closed())?;
+        response.await.map_err(|_| closed())?
+    }
+
+    /// `(batch size, number of batches)` for every size that occurred, ascending.
+    pub fn batch_sizes(&self) -> Vec<(usize, u64)> {
+        self.batch_counts
+            .iter()
+            .enumerate()
+            .map(|(size, count)| (size, count.load(Ordering::Relaxed)))
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+}
+
+/// Collector loop: gather requests into batches and dispatch them.
+async fn collect(
+    mut receiver: mpsc::Receiver<Request>,
+    embed: Arc<BatchFn>,
+    max_batch_size: usize,
+    max_wait: Duration,
+    batch_counts: Arc<Vec<AtomicU64>>,
+) {
+    while let Some(first) = receiver.recv().await {
+        let mut batch = vec![first];
+        let deadline = tokio::time::Instant::now() + max_wait;
+        while batch.len() < max_batch_size {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(request)) => batch.push(request),
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        batch_counts[batch.len()].fetch_add(1, Ordering::Relaxed);
+        let embed = Arc::clone(&embed);
+        tokio::task::spawn_blocking(move || run_batch(&*embed, batch));
+    }
+}
+
+/// Embed one batch and answer every request in it.
+///
+/// If the batched call fails, each image is retried on its own so one bad
+/// tensor only fails its own image.
+fn run_batch(embed: &BatchFn, batch: Vec<Request>) {
+    let mut tensors = Vec::with_capacity(batch.len());
+    let mut paths = Vec::with_capacity(batch.len());
+    let mut replies = Vec::with_capacity(batch.len());
+    for request in batch {
+        tensors.push(request.tensor);
+        paths.push(request.path);
+        replies.push(request.reply);
+    }
+
+    match embed(&tensors, &paths) {
+        Ok(embeddings) if embeddings.len() == replies.len() => {
+            for (reply, embedding) in replies.into_iter().zip(embeddings) {
+                let _ = reply.send(Ok(embedding));
+            }
+        }
+        Err(e) if replies.len() == 1 => {
+            if let Some(reply) = replies.pop() {
+                let _ = reply.send(Err(e));
+            }
+        }
+        result => {
+            if let Err(e) = &result {
+                tracing::debug!(
+                    "Batch of {} failed ({e}), retrying one by one",
+                    replies.len()
+                );
+            }
+            for ((tensor, path), reply) in tensors.iter().zip(&paths).zip(replies) {
+                let single = embed(std::slice::from_ref(tensor), std::slice::from_ref(path))
+                    .and_then(|mut embeddings| {
+                        embeddings.pop().ok_or_else(|| PipelineError::Embedding {
+                            path: path.clone(),
+                            message: "Model returned no embedding".to_string(),
+                        })
+                    });
+                let _ = reply.send(single);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Fake model: the embedding is the tensor's first value; records batch sizes.
+    fn fake_model(calls: Arc<Mutex<Vec<usize>>>) -> Arc<BatchFn> {
+        Arc::new(move |tensors: &[Array4<f32>], paths: &[PathBuf]| {
+            calls.lock().unwrap().push(tensors.len());
+            if tensors.len() > 1 && tensors.iter().any(|t| t[[0, 0, 0, 0]] < 0.0) {
+                return Err(PipelineError::Embedding {
+                    path: paths[0].clone(),
+                    message: "bad tensor in batch".to_string(),
+                });
+            }
+            tensors
+                .iter()
+                .zip(paths)
+                .map(|(t, path)| match t[[0, 0, 0, 0]] {
+                    v if v < 0.0 => Err(PipelineError::Embedding {
+                        path: path.clone(),
+                        message: "bad tensor".to_string(),
+                    }),
+                    v => Ok(vec![v]),
+                })
+                .collect()
+        })
+    }
+
+    fn tensor(value: f32) -> Array4<f32> {
+        Array4::from_elem((1, 1, 1, 1), value)
+    }
+
+    #[tokio::test]
+    async fn test_full_batch_flushes_without_waiting() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let batcher = Arc::new(EmbedBatcher::start(
+            fake_model(Arc::clone(&calls)),
+            4,
+            Duration::from_secs(60),
+        ));
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let batcher = Arc::clone(&batcher);
+                tokio::spawn(async move {
+                    batcher
+                        .embed(tensor(i as f32), PathBuf::from(format!("{i}.jpg")))
+                        .await
+                })
+            })
+            .collect();
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.await.unwrap().unwrap(), vec![i as f32]);
+        }
+
+        assert_eq!(*calls.lock().unwrap(), vec![4]);
+        assert_eq!(batcher.batch_sizes(), vec![(4, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_partial_batch_flushes_after_max_wait() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let batcher =
+            EmbedBatcher::start(fake_model(Arc::clone(&calls)), 8, Duration::from_millis(5));
+
+        let embedding = batcher.embed(tensor(3.0), PathBuf::from("a.jpg")).await;
+        assert_eq!(embedding.unwrap(), vec![3.0]);
+        assert_eq!(batcher.batch_sizes(), vec![(1, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_only_fails_the_bad_image() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let batcher = Arc::new(EmbedBatcher::start(
+            fake_model(Arc::clone(&calls)),
+            2,
+            Duration::from_secs(60),
+        ));
+
+        let good = {
+            let batcher = Arc::clone(&batcher);
+            tokio::spawn(async move { batcher.embed(tensor(1.0), PathBuf::from("good.jpg")).await })
+        };
+        let bad = {
+            let batcher = Arc::clone(&batcher);
+            tokio::spawn(async move { batcher.embed(tensor(-1.0), PathBuf::from("bad.jpg")).await })
+        };
+
+        assert_eq!(good.await.unwrap().unwrap(), vec![1.0]);
+        assert!(bad.await.unwrap().is_err());
+        assert_eq!(*calls.lock().unwrap(), vec![2, 1, 1]);
+    }
+}
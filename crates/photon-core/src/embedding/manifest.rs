@@ -0,0 +1,488 @@
+//! Model manifests and the model registry.
+//!
+//! A manifest describes what Photon needs to know to run a CLIP-family model:
+//! embedding width, input size, pixel normalization, text sequence length,
+//! output tensor names and the sigmoid calibration used for tag confidence.
+//!
+//! The built-in SigLIP models are listed in this crate's `data/registry.json`. Any
+//! model directory can carry its own `manifest.json`, which adds a model or
+//! replaces the built-in entry of the same name:
+//!
+//! ```text
+//! ~/.photon/models/
+//! ├── siglip-base-patch16/visual.onnx        built-in manifest
+//! ├── text_model.onnx, tokenizer.json        shared SigLIP base text encoder
+//! └── siglip-so400m-patch14-384/
+//!     ├── manifest.json
+//!     ├── visual.onnx
+//!     ├── text_model.onnx                    model-specific text encoder
+//!     └── tokenizer.json
+//! ```
+//!
+//! Only a manifest with `"text_encoder": "shared"` may use the shared
+//! encoder; matching its width is not enough, since a 768-d CLIP embeds
+//! into a different space than SigLIP base.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::PipelineError;
+
+/// Filename of a model's manifest inside its directory.
+pub const MANIFEST_FILENAME: &str = "manifest.json";
+
+/// Text encoder filename, shared or per model.
+const TEXT_MODEL_FILENAME: &str = "text_model.onnx";
+
+/// Model whose text encoder sits at the model dir root, shared by every
+/// model that doesn't ship its own.
+const SHARED_TEXT_MODEL: &str = "siglip-base-patch16";
+
+/// Manifests of the models that ship with Photon.
+const BUILTIN_REGISTRY: &str = include_str!("../../data/registry.json");
+
+/// Everything model-specific about a vision/text encoder pair.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelManifest {
+    /// Model id; also the directory name under the model dir.
+    /// Defaults to that directory's name in an on-disk manifest.
+    #[serde(default)]
+    pub name: String,
+
+    /// Short display name, e.g. "Base (224)".
+    #[serde(default)]
+    pub label: String,
+
+    /// Width of the image and text embeddings.
+    pub embedding_dim: usize,
+
+    /// Square input size in pixels.
+    pub image_size: u32,
+
+    /// Per-channel (RGB) normalization mean on the 0–1 pixel scale.
+    pub mean: [f32; 3],
+
+    /// Per-channel (RGB) normalization standard deviation.
+    pub std: [f32; 3],
+
+    /// Token sequence length the text encoder was exported with.
+    pub text_max_length: usize,
+
+    /// Vision encoder output holding the cross-modal embedding.
+    pub vision_output: String,
+
+    /// Text encoder output holding the cross-modal embedding.
+    pub text_output: String,
+
+    /// Whether the text encoder takes an `attention_mask` input.
+    #[serde(default)]
+    pub text_attention_mask: bool,
+
+    /// Which text encoder the model pairs with. Defaults to its own.
+    #[serde(default)]
+    pub text_encoder: TextEncoderSource,
+
+    /// Tag confidence is `sigmoid(logit_scale * cosine + logit_bias)`.
+    pub logit_scale: f32,
+
+    /// See `logit_scale`.
+    pub logit_bias: f32,
+
+    /// Where `photon models download` fetches the vision encoder from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download: Option<ModelSource>,
+
+    /// Model `--quality high` switches to, if any (e.g. a larger input size
+    /// in the same embedding space).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub high_quality: Option<String>,
+}
+
+/// Where a model's text encoder lives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextEncoderSource {
+    /// The SigLIP base encoder at the model dir root, for models trained
+    /// into the same embedding space.
+    Shared,
+    /// `text_model.onnx` and `tokenizer.json` in the model's own directory.
+    #[default]
+    Own,
+}
+
+/// Hugging Face location of a downloadable vision encoder.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelSource {
+    /// Repository, e.g. "Xenova/siglip-base-patch16-224".
+    pub repo: String,
+
+    /// Path of the ONNX file inside the repository.
+    pub remote_path: String,
+
+    /// Expected BLAKE3 checksum; the download is verified when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blake3: Option<String>,
+
+    /// Approximate download size, for display.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_mb: Option<u32>,
+}
+
+impl ModelManifest {
+    /// Find the manifest for `name`: its `manifest.json` if the model
+    /// directory has one, otherwise the built-in entry.
+    pub fn resolve(model_dir: &Path, name: &str) -> Result<Self, PipelineError> {
+        let path = model_dir.join(name).join(MANIFEST_FILENAME);
+        if path.is_file() {
+            return Self::from_file(&path);
+        }
+        ModelRegistry::builtin()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| PipelineError::Model {
+                message: format!(
+                    "Unknown model '{name}': add a manifest at {path:?} describing it"
+                ),
+            })
+    }
+
+    /// Read and check a `manifest.json`.
+    pub fn from_file(path: &Path) -> Result<Self, PipelineError> {
+        let content = std::fs::read_to_string(path).map_err(|e| PipelineError::Model {
+            message: format!("Failed to read model manifest {path:?}: {e}"),
+        })?;
+        let mut manifest: Self =
+            serde_json::from_str(&content).map_err(|e| PipelineError::Model {
+                message: format!("Invalid model manifest {path:?}: {e}"),
+            })?;
+
+        let dir_name = path
+            .parent()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if manifest.name.is_empty() {
+            manifest.name = dir_name;
+        } else if manifest.name != dir_name {
+            return Err(PipelineError::Model {
+                message: format!(
+                    "Model manifest {path:?} names '{}' but sits in '{dir_name}'",
+                    manifest.name
+                ),
+            });
+        }
+        if manifest.label.is_empty() {
+            manifest.label = manifest.name.clone();
+        }
+
+        manifest.check().map_err(|message| PipelineError::Model {
+            message: format!("Invalid model manifest {path:?}: {message}"),
+        })?;
+        Ok(manifest)
+    }
+
+    /// Reject values no model could have.
+    fn check(&self) -> Result<(), String> {
+        if self.embedding_dim == 0 {
+            return Err("embedding_dim must be > 0".into());
+        }
+        if self.image_size == 0 {
+            return Err("image_size must be > 0".into());
+        }
+        if self.text_max_length == 0 {
+            return Err("text_max_length must be > 0".into());
+        }
+        if self.std.iter().any(|&s| s <= 0.0) {
+            return Err("std values must be > 0".into());
+        }
+        if self.vision_output.is_empty() || self.text_output.is_empty() {
+            return Err("vision_output and text_output must be set".into());
+        }
+        Ok(())
+    }
+
+    /// Check that the text encoder this model names is usable.
+    ///
+    /// A model on the shared encoder must have its width; one with its own
+    /// must have `text_model.onnx` in its directory.
+    pub fn check_text_encoder(&self, model_dir: &Path) -> Result<(), String> {
+        match self.text_encoder {
+            TextEncoderSource::Shared => {
+                let shared_dim = ModelRegistry::builtin()
+                    .get(SHARED_TEXT_MODEL)
+                    .expect("built-in model registry must list the shared text model")
+                    .embedding_dim;
+                if self.embedding_dim != shared_dim {
+                    return Err(format!(
+                        "model '{}' has {}-d embeddings but the shared text encoder is {shared_dim}-d; \
+                         set \"text_encoder\": \"own\" and put its {TEXT_MODEL_FILENAME} and \
+                         tokenizer.json in {:?}",
+                        self.name,
+                        self.embedding_dim,
+                        model_dir.join(&self.name)
+                    ));
+                }
+            }
+            TextEncoderSource::Own => {
+                let own_dir = model_dir.join(&self.name);
+                if !own_dir.join(TEXT_MODEL_FILENAME).exists() {
+                    return Err(format!(
+                        "model '{}' needs its own {TEXT_MODEL_FILENAME} and tokenizer.json in {own_dir:?}",
+                        self.name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Directory holding the text encoder and tokenizer for this model: its
+    /// own directory, or the model dir root for the shared encoder.
+    ///
+    /// Fails when that encoder can't be used with this model.
+    pub fn text_model_dir(&self, model_dir: &Path) -> Result<PathBuf, PipelineError> {
+        self.check_text_encoder(model_dir)
+            .map_err(|message| PipelineError::Model {
+                message: format!("No usable text encoder: {message}"),
+            })?;
+        match self.text_encoder {
+            TextEncoderSource::Shared => Ok(model_dir.to_path_buf()),
+            TextEncoderSource::Own => Ok(model_dir.join(&self.name)),
+        }
+    }
+
+    /// Label bank cache filename. Models on the shared text encoder share
+    /// `label_bank.bin`; one with its own encoder gets its own file.
+    pub fn label_bank_filename(&self) -> String {
+        match self.text_encoder {
+            TextEncoderSource::Shared => "label_bank.bin".to_string(),
+            TextEncoderSource::Own => format!("label_bank.{}.bin", self.name),
+        }
+    }
+}
+
+/// The set of known models: built-ins plus manifests found on disk.
+#[derive(Debug, Clone)]
+pub struct ModelRegistry {
+    models: Vec<ModelManifest>,
+}
+
+impl ModelRegistry {
+    /// The models that ship with Photon.
+    pub fn builtin() -> Self {
+        let models =
+            serde_json::from_str(BUILTIN_REGISTRY).expect("built-in model registry must parse");
+        Self { models }
+    }
+
+    /// Built-in models plus every `<model_dir>/<name>/manifest.json`.
+    ///
+    /// An on-disk manifest replaces the built-in entry of the same name.
+    /// Manifests that fail to parse are skipped with a warning.
+    pub fn load(model_dir: &Path) -> Self {
+        let mut registry = Self::builtin();
+        let Ok(entries) = std::fs::read_dir(model_dir) else {
+            return registry;
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().join(MANIFEST_FILENAME))
+            .filter(|path| path.is_file())
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match ModelManifest::from_file(&path) {
+                Ok(manifest) => registry.insert(manifest),
+                Err(e) => tracing::warn!("Skipping model: {e}"),
+            }
+        }
+        registry
+    }
+
+    /// Add a model, replacing any existing entry with the same name.
+    fn insert(&mut self, manifest: ModelManifest) {
+        match self.models.iter_mut().find(|m| m.name == manifest.name) {
+            Some(existing) => *existing = manifest,
+            None => self.models.push(manifest),
+        }
+    }
+
+    /// Look up a model by name.
+    pub fn get(&self, name: &str) -> Option<&ModelManifest> {
+        self.models.iter().find(|m| m.name == name)
+    }
+
+    /// All known models, built-ins first.
+    pub fn models(&self) -> &[ModelManifest] {
+        &self.models
+    }
+
+    /// The model `name` names as its `high_quality` variant, if registered.
+    pub fn high_quality_variant(&self, name: &str) -> Option<&ModelManifest> {
+        let variant = self.get(name)?.high_quality.as_deref()?;
+        self.get(variant)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SO400M: &str = r#"{
+        "embedding_dim": 1152,
+        "image_size": 384,
+        "mean": [0.5, 0.5, 0.5],
+        "std": [0.5, 0.5, 0.5],
+        "text_max_length": 64,
+        "vision_output": "pooler_output",
+        "text_output": "pooler_output",
+        "logit_scale": 117.33,
+        "logit_bias": -12.93
+    }"#;
+
+    fn write_manifest(model_dir: &Path, name: &str, json: &str) {
+        let dir = model_dir.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(MANIFEST_FILENAME), json).unwrap();
+    }
+
+    #[test]
+    fn test_builtin_registry_parses() {
+        let registry = ModelRegistry::builtin();
+        let base = registry.get("siglip-base-patch16").unwrap();
+        assert_eq!(base.embedding_dim, 768);
+        assert_eq!(base.image_size, 224);
+        assert!(base.download.as_ref().unwrap().blake3.is_some());
+        assert_eq!(
+            registry.get("siglip-base-patch16-384").unwrap().image_size,
+            384
+        );
+        assert_eq!(
+            registry
+                .high_quality_variant("siglip-base-patch16")
+                .unwrap()
+                .name,
+            "siglip-base-patch16-384"
+        );
+        assert!(registry
+            .high_quality_variant("siglip-base-patch16-384")
+            .is_none());
+        for model in registry.models() {
+            model.check().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_disk_manifest_adds_model_named_after_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifest(dir.path(), "siglip-so400m-patch14-384", SO400M);
+
+        let registry = ModelRegistry::load(dir.path());
+        let so400m = registry.get("siglip-so400m-patch14-384").unwrap();
+        assert_eq!(so400m.embedding_dim, 1152);
+        assert_eq!(so400m.label, "siglip-so400m-patch14-384");
+        assert!(so400m.download.is_none());
+        assert!(registry.get("siglip-base-patch16").is_some());
+    }
+
+    #[test]
+    fn test_disk_manifest_overrides_builtin() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifest(
+            dir.path(),
+            "siglip-base-patch16",
+            &SO400M.replace("1152", "512"),
+        );
+
+        let manifest = ModelManifest::resolve(dir.path(), "siglip-base-patch16").unwrap();
+        assert_eq!(manifest.embedding_dim, 512);
+        assert_eq!(
+            ModelRegistry::load(dir.path())
+                .get("siglip-base-patch16")
+                .unwrap()
+                .embedding_dim,
+            512
+        );
+    }
+
+    #[test]
+    fn test_resolve_unknown_model_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = ModelManifest::resolve(dir.path(), "mystery-vit").unwrap_err();
+        assert!(err.to_string().contains("Unknown model 'mystery-vit'"));
+    }
+
+    #[test]
+    fn test_invalid_manifests_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifest(dir.path(), "zero-dim", &SO400M.replace("1152", "0"));
+        write_manifest(
+            dir.path(),
+            "misnamed",
+            &SO400M.replacen('{', r#"{ "name": "other","#, 1),
+        );
+
+        let err = ModelManifest::resolve(dir.path(), "zero-dim").unwrap_err();
+        assert!(err.to_string().contains("embedding_dim"));
+        let err = ModelManifest::resolve(dir.path(), "misnamed").unwrap_err();
+        assert!(err.to_string().contains("sits in 'misnamed'"));
+
+        let registry = ModelRegistry::load(dir.path());
+        assert!(registry.get("zero-dim").is_none());
+        assert_eq!(registry.models().len(), 2);
+    }
+
+    #[test]
+    fn test_text_encoder_location() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifest(dir.path(), "siglip-so400m-patch14-384", SO400M);
+        let base = ModelManifest::resolve(dir.path(), "siglip-base-patch16").unwrap();
+        let so400m = ModelManifest::resolve(dir.path(), "siglip-so400m-patch14-384").unwrap();
+
+        assert_eq!(base.text_encoder, TextEncoderSource::Shared);
+        assert_eq!(base.text_model_dir(dir.path()).unwrap(), dir.path());
+        assert_eq!(base.label_bank_filename(), "label_bank.bin");
+
+        // Without "text_encoder" a manifest needs its own
+        assert_eq!(so400m.text_encoder, TextEncoderSource::Own);
+        let err = so400m.text_model_dir(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("needs its own text_model.onnx"));
+
+        let own = dir.path().join("siglip-so400m-patch14-384");
+        std::fs::write(own.join(TEXT_MODEL_FILENAME), b"onnx").unwrap();
+        assert_eq!(so400m.text_model_dir(dir.path()).unwrap(), own);
+        assert_eq!(
+            so400m.label_bank_filename(),
+            "label_bank.siglip-so400m-patch14-384.bin"
+        );
+    }
+
+    #[test]
+    fn test_same_width_model_does_not_borrow_shared_encoder() {
+        // A 768-d OpenCLIP ViT-L/14 embeds into a different space than SigLIP base
+        let dir = tempfile::tempdir().unwrap();
+        write_manifest(
+            dir.path(),
+            "openclip-vit-l-14",
+            &SO400M.replace("1152", "768"),
+        );
+        let clip = ModelManifest::resolve(dir.path(), "openclip-vit-l-14").unwrap();
+        assert!(clip.text_model_dir(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_shared_encoder_needs_matching_width() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifest(
+            dir.path(),
+            "siglip-so400m-patch14-384",
+            &SO400M.replacen('{', r#"{ "text_encoder": "shared","#, 1),
+        );
+        let so400m = ModelManifest::resolve(dir.path(), "siglip-so400m-patch14-384").unwrap();
+        let err = so400m.text_model_dir(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("shared text encoder is 768-d"));
+    }
+}
@@ -1,7 +1,9 @@
 //! SigLIP embedding generation.
 //!
-//! This module handles converting images into 768-dimensional vector embeddings
-//! using a SigLIP visual encoder running locally via ONNX Runtime.
+//! This module handles converting images into vector embeddings using a
+//! SigLIP (or other CLIP-family) visual encoder running locally via ONNX
+//! Runtime. The model's [`ModelManifest`] supplies its input size,
+//! normalization and embedding width (768 for SigLIP base).
 //!
 //! # Usage
 //!
@@ -12,11 +14,16 @@
 //! let config = Config::default();
 //! let engine = EmbeddingEngine::load(&config.embedding, &config.model_dir())?;
 //! let embedding = engine.embed(&decoded_image)?;
-//! // embedding is a Vec<f32> with 768 elements
+//! // embedding is a Vec<f32> with engine.embedding_dim() elements
 //! ```
 
+pub(crate) mod batcher;
+pub(crate) mod manifest;
+pub(crate) mod pool;
 pub(crate) mod preprocess;
+pub(crate) mod runtime;
 pub(crate) mod siglip;
+pub(crate) mod text;
 
 use std::path::{Path, PathBuf};
 
@@ -25,7 +32,8 @@ use image::DynamicImage;
 use crate::config::EmbeddingConfig;
 use crate::error::PipelineError;
 
-use self::preprocess::preprocess;
+use self::manifest::ModelManifest;
+use self::preprocess::preprocess_normalized;
 use self::siglip::SigLipSession;
 
 /// The visual encoder ONNX model filename.
@@ -34,15 +42,17 @@ const VISUAL_MODEL_FILENAME: &str = "visual.onnx";
 /// Engine for generating image embeddings via SigLIP.
 pub struct EmbeddingEngine {
     session: SigLipSession,
-    image_size: u32,
+    manifest: ModelManifest,
 }
 
 impl EmbeddingEngine {
     /// Load the SigLIP visual encoder from the model directory.
     ///
-    /// Expects the ONNX model at `{model_dir}/{model_name}/visual.onnx`.
+    /// Expects the ONNX model at `{model_dir}/{model_name}/visual.onnx`, and
+    /// either a built-in manifest or `{model_dir}/{model_name}/manifest.json`.
     pub fn load(config: &EmbeddingConfig, model_dir: &Path) -> Result<Self, PipelineError> {
         let model_path = model_dir.join(&config.model).join(VISUAL_MODEL_FILENAME);
+        let manifest = ModelManifest::resolve(model_dir, &config.model)?;
 
         if !model_path.exists() {
             return Err(PipelineError::Embedding {
@@ -52,27 +62,46 @@ impl EmbeddingEngine {
         }
 
         tracing::info!("Loading SigLIP model from {:?}", model_path);
-        let session = SigLipSession::load(&model_path)?;
-        tracing::info!("SigLIP model loaded successfully");
+        let session = SigLipSession::load(&model_path, config, &manifest)?;
+        tracing::info!(
+            "SigLIP model loaded successfully ({}-d, {}px)",
+            manifest.embedding_dim,
+            manifest.image_size
+        );
+
+        Ok(Self { session, manifest })
+    }
+
+    /// Get the image input size for this model (224 or 384 for SigLIP base).
+    pub fn image_size(&self) -> u32 {
+        self.manifest.image_size
+    }
 
-        let image_size = config.image_size;
+    /// Width of the embeddings this model produces.
+    pub fn embedding_dim(&self) -> usize {
+        self.manifest.embedding_dim
+    }
 
-        Ok(Self {
-            session,
-            image_size,
-        })
+    /// The manifest this engine was loaded from.
+    pub fn manifest(&self) -> &ModelManifest {
+        &self.manifest
     }
 
-    /// Get the image input size for this model (224 or 384).
-    pub fn image_size(&self) -> u32 {
-        self.image_size
+    /// Resize and normalize an image into this model's input tensor.
+    pub fn preprocess(&self, image: &DynamicImage) -> ndarray::Array4<f32> {
+        preprocess_normalized(
+            image,
+            self.manifest.image_size,
+            self.manifest.mean,
+            self.manifest.std,
+        )
     }
 
     /// Generate an embedding vector for an image.
     ///
-    /// Returns an L2-normalized Vec<f32> (typically 768 dimensions).
+    /// Returns an L2-normalized Vec<f32> of `embedding_dim()` floats.
     pub fn embed(&self, image: &DynamicImage, path: &Path) -> Result<Vec<f32>, PipelineError> {
-        let tensor = preprocess(image, self.image_size);
+        let tensor = self.preprocess(image);
         self.session.embed(&tensor, path)
     }
 
@@ -108,14 +137,27 @@ impl EmbeddingEngine {
         &self,
         images: &[(&DynamicImage, &Path)],
     ) -> Result<Vec<Vec<f32>>, PipelineError> {
-        let tensors: Vec<ndarray::Array4<f32>> = images
-            .iter()
-            .map(|(img, _)| preprocess(img, self.image_size))
-            .collect();
+        let tensors: Vec<ndarray::Array4<f32>> =
+            images.iter().map(|(img, _)| self.preprocess(img)).collect();
         let paths: Vec<PathBuf> = images.iter().map(|(_, p)| p.to_path_buf()).collect();
         self.session.embed_batch(&tensors, &paths)
     }
 
+    /// Generate embeddings for each frame of an animation plus a pooled
+    /// embedding for the whole clip.
+    ///
+    /// Returns `(pooled, per_frame)`: the frames are embedded in a single
+    /// batch and the pooled vector is their mean, re-normalized to unit length.
+    pub fn embed_frames(
+        &self,
+        frames: &[DynamicImage],
+        path: &Path,
+    ) -> Result<(Vec<f32>, Vec<Vec<f32>>), PipelineError> {
+        let images: Vec<(&DynamicImage, &Path)> = frames.iter().map(|f| (f, path)).collect();
+        let per_frame = self.embed_batch(&images)?;
+        Ok((mean_pool(&per_frame), per_frame))
+    }
+
     /// Check whether the model files exist on disk.
     pub fn model_exists(config: &EmbeddingConfig, model_dir: &Path) -> bool {
         let model_path = model_dir.join(&config.model).join(VISUAL_MODEL_FILENAME);
@@ -128,11 +170,46 @@ impl EmbeddingEngine {
     }
 }
 
+/// Mean-pool a set of embeddings and L2-normalize the result.
+///
+/// Returns an empty vector for empty input.
+pub(crate) fn mean_pool(embeddings: &[Vec<f32>]) -> Vec<f32> {
+    let Some(first) = embeddings.first() else {
+        return vec![];
+    };
+    let mut pooled = vec![0.0f32; first.len()];
+    for embedding in embeddings {
+        for (acc, &x) in pooled.iter_mut().zip(embedding) {
+            *acc += x;
+        }
+    }
+    let norm: f32 = pooled.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for x in &mut pooled {
+            *x /= norm;
+        }
+    }
+    pooled
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::Config;
 
+    #[test]
+    fn test_mean_pool_renormalizes() {
+        let pooled = mean_pool(&[vec![1.0, 0.0], vec![0.0, 1.0]]);
+        let expected = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((pooled[0] - expected).abs() < 1e-6);
+        assert!((pooled[1] - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mean_pool_empty() {
+        assert!(mean_pool(&[]).is_empty());
+    }
+
     /// Try to load the engine for tests; returns None if model files are missing.
     fn try_load_engine() -> Option<EmbeddingEngine> {
         let config = Config::default();
@@ -161,7 +238,7 @@ mod tests {
 
         let img = DynamicImage::ImageRgb8(image::RgbImage::new(640, 480));
         let path = PathBuf::from("test.png");
-        let tensor = preprocess(&img, engine.image_size());
+        let tensor = engine.preprocess(&img);
 
         // Single embed
         let single = engine.embed_preprocessed(&tensor, &path).unwrap();
@@ -198,15 +275,19 @@ mod tests {
             image::Rgb([0, 0, 255]),
         ));
 
-        let t1 = preprocess(&img1, engine.image_size());
-        let t2 = preprocess(&img2, engine.image_size());
+        let t1 = engine.preprocess(&img1);
+        let t2 = engine.preprocess(&img2);
         let paths = vec![PathBuf::from("red.png"), PathBuf::from("blue.png")];
 
         let results = engine.embed_batch_preprocessed(&[t1, t2], &paths).unwrap();
 
         assert_eq!(results.len(), 2);
         for (i, emb) in results.iter().enumerate() {
-            assert_eq!(emb.len(), 768, "Embedding {i} should be 768-dim");
+            assert_eq!(
+                emb.len(),
+                engine.embedding_dim(),
+                "Embedding {i} should match the manifest"
+            );
             let norm: f32 = emb.iter().map(|x| x * x).sum::<f32>().sqrt();
             assert!(
                 (norm - 1.0).abs() < 1e-4,
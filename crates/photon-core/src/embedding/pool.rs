@@ -0,0 +1,198 @@
+//! A fixed pool of inference sessions.
+//!
+//! `Session::run` needs `&mut self`, so one session serializes every caller.
+//! The pool holds `embedding.pool_size` independent sessions, each behind its
+//! own `Mutex`, and hands one out per call:
+//!
+//! - **round_robin** — slots in turn, waiting on the chosen one if it is busy.
+//! - **least_busy** — the first idle slot (scanning from a rotating start),
+//!   otherwise the slot with the fewest callers holding or waiting on it.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, TryLockError};
+
+/// Accepted values for `embedding.pool_checkout`.
+pub const POOL_CHECKOUTS: &[&str] = &["least_busy", "round_robin"];
+
+/// How a slot is chosen for each checkout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Checkout {
+    RoundRobin,
+    LeastBusy,
+}
+
+impl Checkout {
+    /// Parse a validated `embedding.pool_checkout` value.
+    pub fn parse(name: &str) -> Self {
+        match name {
+            "round_robin" => Self::RoundRobin,
+            _ => Self::LeastBusy,
+        }
+    }
+}
+
+struct Slot<T> {
+    item: Mutex<T>,
+    /// Callers holding or waiting on this slot.
+    in_flight: AtomicUsize,
+}
+
+/// Pool of `T` (an ONNX session) with per-slot locking.
+pub(crate) struct SessionPool<T> {
+    slots: Vec<Slot<T>>,
+    next: AtomicUsize,
+    checkout: Checkout,
+}
+
+/// Exclusive access to one pooled item; returns the slot on drop.
+pub(crate) struct PoolGuard<'a, T> {
+    guard: MutexGuard<'a, T>,
+    in_flight: &'a AtomicUsize,
+}
+
+impl<T> SessionPool<T> {
+    /// Build a pool from already-loaded items. `items` must not be empty.
+    pub fn new(items: Vec<T>, checkout: Checkout) -> Self {
+        assert!(!items.is_empty(), "session pool needs at least one item");
+        Self {
+            slots: items
+                .into_iter()
+                .map(|item| Slot {
+                    item: Mutex::new(item),
+                    in_flight: AtomicUsize::new(0),
+                })
+                .collect(),
+            next: AtomicUsize::new(0),
+            checkout,
+        }
+    }
+
+    /// Check out an item, blocking until the chosen slot is free.
+    pub fn get(&self) -> Result<PoolGuard<'_, T>, String> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % self.slots.len();
+        let index = match self.checkout {
+            Checkout::RoundRobin => start,
+            Checkout::LeastBusy => {
+                for offset in 0..self.slots.len() {
+                    let index = (start + offset) % self.slots.len();
+                    let slot = &self.slots[index];
+                    match slot.item.try_lock() {
+                        Ok(guard) => {
+                            slot.in_flight.fetch_add(1, Ordering::AcqRel);
+                            return Ok(PoolGuard {
+                                guard,
+                                in_flight: &slot.in_flight,
+                            });
+                        }
+                        Err(TryLockError::WouldBlock) => {}
+                        Err(TryLockError::Poisoned(e)) => {
+                            return Err(format!("Session lock poisoned: {e}"))
+                        }
+                    }
+                }
+                (0..self.slots.len())
+                    .map(|offset| (start + offset) % self.slots.len())
+                    .min_by_key(|&index| self.slots[index].in_flight.load(Ordering::Acquire))
+                    .unwrap_or(start)
+            }
+        };
+        self.lock(index)
+    }
+
+    fn lock(&self, index: usize) -> Result<PoolGuard<'_, T>, String> {
+        let slot = &self.slots[index];
+        slot.in_flight.fetch_add(1, Ordering::AcqRel);
+        match slot.item.lock() {
+            Ok(guard) => Ok(PoolGuard {
+                guard,
+                in_flight: &slot.in_flight,
+            }),
+            Err(e) => {
+                slot.in_flight.fetch_sub(1, Ordering::AcqRel);
+                Err(format!("Session lock poisoned: {e}"))
+            }
+        }
+    }
+}
+
+impl<T> Deref for PoolGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for PoolGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for PoolGuard<'_, T> {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Barrier};
+
+    #[test]
+    fn test_round_robin_cycles_slots() {
+        let pool = SessionPool::new(vec![0, 1, 2], Checkout::RoundRobin);
+        let seen: Vec<i32> = (0..6).map(|_| *pool.get().unwrap()).collect();
+        assert_eq!(seen, vec![0, 1, 2, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_least_busy_skips_held_slots() {
+        let pool = SessionPool::new(vec![0, 1, 2], Checkout::LeastBusy);
+        let a = pool.get().unwrap();
+        let b = pool.get().unwrap();
+        let c = pool.get().unwrap();
+        let mut held = vec![*a, *b, *c];
+        held.sort_unstable();
+        assert_eq!(held, vec![0, 1, 2], "each checkout gets an idle slot");
+
+        drop(b);
+        let freed = *pool.get().unwrap();
+        assert_eq!(freed, 1, "the only idle slot is handed out");
+    }
+
+    #[test]
+    fn test_in_flight_released_on_drop() {
+        let pool = SessionPool::new(vec![()], Checkout::LeastBusy);
+        {
+            let _guard = pool.get().unwrap();
+            assert_eq!(pool.slots[0].in_flight.load(Ordering::Acquire), 1);
+        }
+        assert_eq!(pool.slots[0].in_flight.load(Ordering::Acquire), 0);
+    }
+
+    #[test]
+    fn test_concurrent_checkouts_use_every_slot() {
+        let pool = Arc::new(SessionPool::new(vec![0usize; 4], Checkout::LeastBusy));
+        let barrier = Arc::new(Barrier::new(4));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let pool = Arc::clone(&pool);
+                let barrier = Arc::clone(&barrier);
+                std::thread::spawn(move || {
+                    let mut guard = pool.get().unwrap();
+                    *guard += 1;
+                    // Hold the slot until all four threads have one
+                    barrier.wait();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let counts: Vec<usize> = (0..4).map(|i| *pool.lock(i).unwrap()).collect();
+        assert_eq!(counts, vec![1, 1, 1, 1]);
+    }
+}
@@ -1,10 +1,13 @@
-//! Image preprocessing for SigLIP embedding generation.
+//! Image preprocessing for embedding generation.
 //!
 //! SigLIP base-patch16-224 expects:
 //! - Input size: 224×224 pixels
 //! - Normalization: pixels scaled to [-1, 1] via (pixel/255 - 0.5) / 0.5
 //! - Channel order: RGB
 //! - Tensor layout: NCHW [batch, channels, height, width]
+//!
+//! Other models declare their own input size and per-channel mean/std in
+//! their manifest; see [`preprocess_normalized`].
 
 use image::DynamicImage;
 use ndarray::Array4;
@@ -13,16 +16,28 @@ use ndarray::Array4;
 const CHANNELS: usize = 3;
 
 /// SigLIP normalization mean (per-channel).
-const NORM_MEAN: f32 = 0.5;
+const NORM_MEAN: [f32; 3] = [0.5; 3];
 
 /// SigLIP normalization std (per-channel).
-const NORM_STD: f32 = 0.5;
+const NORM_STD: [f32; 3] = [0.5; 3];
 
 /// Preprocess an image for SigLIP inference.
 ///
 /// Resizes to `image_size × image_size`, converts to RGB, normalizes to [-1, 1],
 /// and returns an NCHW tensor suitable for ONNX Runtime.
 pub fn preprocess(image: &DynamicImage, image_size: u32) -> Array4<f32> {
+    preprocess_normalized(image, image_size, NORM_MEAN, NORM_STD)
+}
+
+/// Preprocess an image with explicit per-channel (RGB) normalization.
+///
+/// Each channel becomes `(pixel/255 - mean[c]) / std[c]`.
+pub fn preprocess_normalized(
+    image: &DynamicImage,
+    image_size: u32,
+    mean: [f32; 3],
+    std: [f32; 3],
+) -> Array4<f32> {
     let resized = image.resize_exact(
         image_size,
         image_size,
@@ -43,7 +58,7 @@ pub fn preprocess(image: &DynamicImage, image_size: u32) -> Array4<f32> {
         for (c, &val) in pixel.iter().enumerate() {
             // NCHW layout: offset = c * size * size + y * size + x
             let idx = c * size * size + y * size + x;
-            tensor_data[idx] = (val as f32 / 255.0 - NORM_MEAN) / NORM_STD;
+            tensor_data[idx] = (val as f32 / 255.0 - mean[c]) / std[c];
         }
     }
 
@@ -84,4 +99,23 @@ mod tests {
         let min_val = tensor.iter().cloned().fold(f32::INFINITY, f32::min);
         assert!((min_val - (-1.0)).abs() < 0.01);
     }
+
+    #[test]
+    fn test_preprocess_per_channel_normalization() {
+        // OpenAI CLIP statistics
+        let mean = [0.481_454_66, 0.457_827_5, 0.408_210_73];
+        let std = [0.268_629_54, 0.261_302_6, 0.275_777_1];
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 128])));
+        let tensor = preprocess_normalized(&img, 2, mean, std);
+
+        assert_eq!(tensor.shape(), &[1, 3, 2, 2]);
+        let expected = [
+            (1.0 - mean[0]) / std[0],
+            (0.0 - mean[1]) / std[1],
+            (128.0 / 255.0 - mean[2]) / std[2],
+        ];
+        for (c, want) in expected.iter().enumerate() {
+            assert!((tensor[[0, c, 1, 1]] - want).abs() < 1e-4, "channel {c}");
+        }
+    }
 }
@@ -0,0 +1,246 @@
+//! ONNX Runtime session construction from `[embedding.runtime]`.
+//!
+//! Both the vision and the text encoder build their sessions here, so thread
+//! counts, graph optimization and arena settings apply to every model.
+//!
+//! With `optimized_model_dir` set, the graph is optimized once and the result
+//! saved in that directory; later loads read it back with
+//! optimization turned off, skipping the (multi-second) optimization pass.
+//! Graphs optimized at `layout` or `all` are specific to the CPU (and ONNX
+//! Runtime build) that produced them, so the cache must not be shared
+//! between machines.
+
+use std::path::{Path, PathBuf};
+
+use ort::ep;
+use ort::session::builder::{GraphOptimizationLevel, SessionBuilder};
+use ort::session::Session;
+
+use crate::config::RuntimeConfig;
+
+/// Accepted values for `embedding.runtime.optimization_level`.
+pub const OPTIMIZATION_LEVELS: &[&str] = &["disable", "basic", "extended", "layout", "all"];
+
+/// Map a validated level name to the ONNX Runtime enum.
+fn optimization_level(name: &str) -> GraphOptimizationLevel {
+    match name {
+        "disable" => GraphOptimizationLevel::Disable,
+        "basic" => GraphOptimizationLevel::Level1,
+        "extended" => GraphOptimizationLevel::Level2,
+        "layout" => GraphOptimizationLevel::Level3,
+        _ => GraphOptimizationLevel::All,
+    }
+}
+
+/// Load an ONNX model with the configured runtime settings.
+///
+/// A cache entry that fails to load is deleted and rebuilt from the source
+/// model. Errors are returned as messages so each encoder can wrap them in
+/// its own `PipelineError` variant.
+pub(crate) fn load_session(model_path: &Path, config: &RuntimeConfig) -> Result<Session, String> {
+    let cached = cached_model_path(model_path, config);
+
+    if let Some(path) = cached.as_ref().filter(|path| is_fresh(path, model_path)) {
+        tracing::debug!("Loading optimized model from cache {:?}", path);
+        // Already optimized; running the passes again would only cost time
+        match configure(config, GraphOptimizationLevel::Disable)?.commit_from_file(path) {
+            Ok(session) => return Ok(session),
+            Err(e) => {
+                tracing::warn!("Discarding unreadable optimized model {:?}: {e}", path);
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    let mut builder = configure(config, optimization_level(&config.optimization_level))?;
+
+    // ONNX Runtime writes the optimized graph while loading; it goes to a
+    // temporary file that is only renamed into the cache once complete, so an
+    // interrupted run never leaves a truncated entry behind
+    let temp = cached.as_deref().map(temp_path);
+    if let Some(temp) = &temp {
+        if let Some(parent) = temp.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create optimized model dir {parent:?}: {e}"))?;
+        }
+        builder = builder
+            .with_optimized_model_path(temp)
+            .map_err(|e| format!("Failed to set optimized model path: {e}"))?;
+    }
+
+    let session = builder
+        .commit_from_file(model_path)
+        .map_err(|e| format!("Failed to load ONNX model: {e}"));
+
+    if let (Some(temp), Some(cached)) = (&temp, &cached) {
+        if session.is_ok() && temp.exists() {
+            if let Err(e) = std::fs::rename(temp, cached) {
+                tracing::warn!("Failed to cache optimized model at {:?}: {e}", cached);
+            }
+        }
+        // Leftovers of a failed load or rename
+        let _ = std::fs::remove_file(temp);
+    }
+
+    session
+}
+
+/// Temporary name the optimized graph is written under before it is moved
+/// to `cached`: same directory, so the rename is atomic.
+fn temp_path(cached: &Path) -> PathBuf {
+    let mut name = cached.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tmp", std::process::id()));
+    cached.with_file_name(name)
+}
+
+/// Session builder with every setting except the model source applied.
+fn configure(
+    config: &RuntimeConfig,
+    level: GraphOptimizationLevel,
+) -> Result<SessionBuilder, String> {
+    let mut builder = Session::builder()
+        .map_err(|e| format!("Failed to create ONNX session builder: {e}"))?
+        .with_optimization_level(level)
+        .map_err(|e| format!("Failed to set graph optimization level: {e}"))?
+        .with_memory_pattern(config.memory_pattern)
+        .map_err(|e| format!("Failed to set memory pattern: {e}"))?
+        .with_execution_providers([ep::CPU::default()
+            .with_arena_allocator(config.cpu_arena)
+            .build()])
+        .map_err(|e| format!("Failed to configure CPU execution provider: {e}"))?;
+
+    if config.intra_threads > 0 {
+        builder = builder
+            .with_intra_threads(config.intra_threads)
+            .map_err(|e| format!("Failed to set intra-op threads: {e}"))?;
+    }
+    if config.parallel_execution {
+        builder = builder
+            .with_parallel_execution(true)
+            .map_err(|e| format!("Failed to enable parallel execution: {e}"))?;
+        if config.inter_threads > 0 {
+            builder = builder
+                .with_inter_threads(config.inter_threads)
+                .map_err(|e| format!("Failed to set inter-op threads: {e}"))?;
+        }
+    }
+    Ok(builder)
+}
+
+/// Resident memory of this process in bytes, where the OS exposes it (Linux).
+pub(crate) fn resident_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// Where the optimized copy of `model_path` is cached, if caching is on.
+///
+/// The name carries the source's parent directory (the model variant), its
+/// file stem and the optimization level, so variants and levels never share
+/// an entry: `<dir>/<variant>-<stem>.<level>.onnx`.
+fn cached_model_path(model_path: &Path, config: &RuntimeConfig) -> Option<PathBuf> {
+    if config.optimized_model_dir.is_empty() {
+        return None;
+    }
+    let dir = PathBuf::from(shellexpand::tilde(&config.optimized_model_dir).into_owned());
+    let stem = model_path.file_stem()?.to_string_lossy();
+    let variant = model_path
+        .parent()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    Some(dir.join(format!(
+        "{variant}-{stem}.{}.onnx",
+        config.optimization_level
+    )))
+}
+
+/// A cache entry is usable when it exists and is newer than its source model,
+/// so a re-downloaded model invalidates it.
+fn is_fresh(cached: &Path, source: &Path) -> bool {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(cached), modified(source)) {
+        (Some(cached), Some(source)) => cached >= source,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runtime(dir: &str) -> RuntimeConfig {
+        RuntimeConfig {
+            optimized_model_dir: dir.to_string(),
+            ..RuntimeConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_optimization_levels_all_map() {
+        assert_eq!(
+            optimization_level("disable"),
+            GraphOptimizationLevel::Disable
+        );
+        assert_eq!(optimization_level("layout"), GraphOptimizationLevel::Level3);
+        assert_eq!(optimization_level("all"), GraphOptimizationLevel::All);
+    }
+
+    #[test]
+    fn test_cached_model_path_names() {
+        assert!(cached_model_path(Path::new("/m/siglip/visual.onnx"), &runtime("")).is_none());
+
+        let vision = cached_model_path(
+            Path::new("/m/siglip-base-patch16-384/visual.onnx"),
+            &runtime("/cache"),
+        );
+        assert_eq!(
+            vision,
+            Some(PathBuf::from(
+                "/cache/siglip-base-patch16-384-visual.all.onnx"
+            ))
+        );
+
+        let text = cached_model_path(Path::new("/m/text_model.onnx"), &runtime("/cache"));
+        assert_eq!(text, Some(PathBuf::from("/cache/m-text_model.all.onnx")));
+    }
+
+    #[test]
+    fn test_temp_path_sits_next_to_cache_entry() {
+        let cached = Path::new("/cache/siglip-visual.all.onnx");
+        let temp = temp_path(cached);
+        assert_eq!(temp.parent(), cached.parent());
+        assert_ne!(temp, cached);
+        assert!(temp.to_string_lossy().ends_with(".tmp"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_resident_memory_reported_on_linux() {
+        assert!(resident_memory_bytes().is_some_and(|bytes| bytes > 0));
+    }
+
+    #[test]
+    fn test_cache_freshness() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("visual.onnx");
+        let cached = dir.path().join("cached.onnx");
+
+        std::fs::write(&source, b"model").unwrap();
+        assert!(!is_fresh(&cached, &source), "missing cache entry");
+
+        std::fs::write(&cached, b"optimized").unwrap();
+        assert!(is_fresh(&cached, &source));
+
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&source)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(!is_fresh(&cached, &source), "source replaced after caching");
+    }
+}
@@ -1,41 +1,66 @@
 //! SigLIP ONNX model session management and inference.
 //!
-//! Loads a SigLIP visual encoder exported to ONNX format and runs inference
-//! to produce 768-dimensional image embedding vectors.
+//! Loads a SigLIP (or other CLIP-family) visual encoder exported to ONNX
+//! format and runs inference to produce image embedding vectors of the width
+//! declared in the model's manifest (768 for SigLIP base).
 
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
 
 use ndarray::Array4;
 use ort::session::Session;
 use ort::value::Value;
 
+use super::manifest::ModelManifest;
+use super::pool::{Checkout, SessionPool};
+use super::runtime::{load_session, resident_memory_bytes};
+use crate::config::{available_cpus, EmbeddingConfig};
 use crate::error::PipelineError;
 
-/// Wraps an ONNX Runtime session for SigLIP visual embedding.
+/// Wraps a pool of ONNX Runtime sessions for SigLIP visual embedding.
 ///
-/// Uses a `Mutex` because `Session::run` requires `&mut self`.
+/// `Session::run` requires `&mut self`, so each call checks out one session;
+/// with `embedding.pool_size` > 1 that many images are embedded at once.
 pub struct SigLipSession {
-    session: Mutex<Session>,
+    sessions: SessionPool<Session>,
     /// Name of the input tensor (detected from model metadata).
     input_name: String,
+    /// Output holding the cross-modal embedding, from the manifest.
+    output_name: String,
+    /// Embedding width declared by the manifest.
+    embedding_dim: usize,
 }
 
 impl SigLipSession {
-    /// Load a SigLIP visual encoder from an ONNX file.
-    pub fn load(model_path: &Path) -> Result<Self, PipelineError> {
-        let session = Session::builder()
-            .map_err(|e| PipelineError::Embedding {
-                path: model_path.to_path_buf(),
-                message: format!("Failed to create ONNX session builder: {e}"),
-            })?
-            .commit_from_file(model_path)
-            .map_err(|e| PipelineError::Embedding {
+    /// Load `config.pool_size` sessions of a SigLIP visual encoder from an ONNX file.
+    pub fn load(
+        model_path: &Path,
+        config: &EmbeddingConfig,
+        manifest: &ModelManifest,
+    ) -> Result<Self, PipelineError> {
+        let pool_size = config.effective_pool_size(1);
+        let mut runtime = config.runtime.clone();
+        if runtime.intra_threads == 0 && pool_size > 1 {
+            // Every session has its own intra-op pool; split the CPUs between
+            // them instead of each claiming all of them
+            runtime.intra_threads = (available_cpus() / pool_size).max(1);
+            tracing::debug!(
+                "{pool_size} sessions, {} intra-op threads each",
+                runtime.intra_threads
+            );
+        }
+
+        let resident_before = resident_memory_bytes();
+        let sessions = (0..pool_size)
+            .map(|_| load_session(model_path, &runtime))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|message| PipelineError::Embedding {
                 path: model_path.to_path_buf(),
-                message: format!("Failed to load ONNX model: {e}"),
+                message,
             })?;
+        report_memory(model_path, sessions.len(), resident_before);
 
         // Detect the input tensor name from model metadata.
+        let session = &sessions[0];
         let input_name = session
             .inputs()
             .first()
@@ -54,15 +79,18 @@ impl SigLipSession {
         );
 
         Ok(Self {
-            session: Mutex::new(session),
+            sessions: SessionPool::new(sessions, Checkout::parse(&config.pool_checkout)),
             input_name,
+            output_name: manifest.vision_output.clone(),
+            embedding_dim: manifest.embedding_dim,
         })
     }
 
     /// Run inference on a preprocessed image tensor and return the embedding.
     ///
     /// Input shape: \[1, 3, image_size, image_size\] (NCHW, normalized to \[-1, 1\]).
-    /// Output: L2-normalized embedding vector (768 floats from pooler_output).
+    /// Output: L2-normalized embedding vector (`embedding_dim` floats from the
+    /// manifest's vision output, `pooler_output` for SigLIP).
     pub fn embed(
         &self,
         preprocessed: &Array4<f32>,
@@ -80,25 +108,28 @@ impl SigLipSession {
 
         let inputs = ort::inputs![self.input_name.as_str() => input_value];
 
-        let mut session = self.session.lock().map_err(|e| PipelineError::Embedding {
-            path: Default::default(),
-            message: format!("Session lock poisoned: {e}"),
-        })?;
+        let mut session = self
+            .sessions
+            .get()
+            .map_err(|message| PipelineError::Embedding {
+                path: Default::default(),
+                message,
+            })?;
 
         let outputs = session.run(inputs).map_err(|e| PipelineError::Embedding {
             path: Default::default(),
             message: format!("ONNX inference failed: {e}"),
         })?;
 
-        // Extract pooler_output by name — the cross-modal embedding projection.
-        // This is the 2nd output; the 1st (last_hidden_state) is NOT aligned
-        // across modalities and should not be used for tagging.
+        // Extract the embedding output by name — the cross-modal projection.
+        // For SigLIP this is pooler_output, the 2nd output; the 1st
+        // (last_hidden_state) is NOT aligned across modalities.
         let pooler_output = outputs
             .iter()
-            .find(|(name, _)| *name == "pooler_output")
+            .find(|(name, _)| *name == self.output_name)
             .ok_or_else(|| PipelineError::Embedding {
                 path: path.to_path_buf(),
-                message: "Model did not produce pooler_output".to_string(),
+                message: format!("Model did not produce {}", self.output_name),
             })?;
 
         let (shape, data) =
@@ -107,10 +138,10 @@ impl SigLipSession {
                 .try_extract_tensor::<f32>()
                 .map_err(|e| PipelineError::Embedding {
                     path: path.to_path_buf(),
-                    message: format!("Failed to extract pooler_output tensor: {e}"),
+                    message: format!("Failed to extract {} tensor: {e}", self.output_name),
                 })?;
 
-        // pooler_output is [1, 768] — extract the single embedding vector.
+        // The output is [1, dim] — extract the single embedding vector.
         let mut raw = match shape.len() {
             1 => data.to_vec(),
             2 => {
@@ -120,10 +151,11 @@ impl SigLipSession {
             _ => {
                 return Err(PipelineError::Embedding {
                     path: path.to_path_buf(),
-                    message: format!("Unexpected pooler_output shape: {:?}", shape),
+                    message: format!("Unexpected {} shape: {:?}", self.output_name, shape),
                 });
             }
         };
+        self.check_dim(raw.len(), path)?;
 
         crate::math::l2_normalize_in_place(&mut raw);
         Ok(raw)
@@ -133,7 +165,7 @@ impl SigLipSession {
     ///
     /// Each tensor must have shape \[1, 3, image_size, image_size\] (NCHW, normalized to \[-1, 1\]).
     /// They are stacked into a single \[N, 3, image_size, image_size\] tensor for one ONNX call.
-    /// Returns N L2-normalized embedding vectors (768 floats each).
+    /// Returns N L2-normalized embedding vectors (`embedding_dim` floats each).
     pub fn embed_batch(
         &self,
         tensors: &[Array4<f32>],
@@ -180,23 +212,26 @@ impl SigLipSession {
 
         let inputs = ort::inputs![self.input_name.as_str() => input_value];
 
-        let mut session = self.session.lock().map_err(|e| PipelineError::Embedding {
-            path: Default::default(),
-            message: format!("Session lock poisoned: {e}"),
-        })?;
+        let mut session = self
+            .sessions
+            .get()
+            .map_err(|message| PipelineError::Embedding {
+                path: Default::default(),
+                message,
+            })?;
 
         let outputs = session.run(inputs).map_err(|e| PipelineError::Embedding {
             path: Default::default(),
             message: format!("ONNX batch inference failed: {e}"),
         })?;
 
-        // Extract pooler_output [N, 768].
+        // Extract the embedding output [N, dim].
         let pooler_output = outputs
             .iter()
-            .find(|(name, _)| *name == "pooler_output")
+            .find(|(name, _)| *name == self.output_name)
             .ok_or_else(|| PipelineError::Embedding {
                 path: paths.first().cloned().unwrap_or_default(),
-                message: "Model did not produce pooler_output".to_string(),
+                message: format!("Model did not produce {}", self.output_name),
             })?;
 
         let (shape, data) =
@@ -205,7 +240,7 @@ impl SigLipSession {
                 .try_extract_tensor::<f32>()
                 .map_err(|e| PipelineError::Embedding {
                     path: paths.first().cloned().unwrap_or_default(),
-                    message: format!("Failed to extract batch pooler_output tensor: {e}"),
+                    message: format!("Failed to extract batch {} tensor: {e}", self.output_name),
                 })?;
 
         // Determine embedding dimension from output shape.
@@ -215,10 +250,11 @@ impl SigLipSession {
             _ => {
                 return Err(PipelineError::Embedding {
                     path: paths.first().cloned().unwrap_or_default(),
-                    message: format!("Unexpected batch pooler_output shape: {:?}", shape),
+                    message: format!("Unexpected batch {} shape: {:?}", self.output_name, shape),
                 });
             }
         };
+        self.check_dim(embedding_dim, paths.first().map_or(Path::new(""), |p| p))?;
 
         // Split into per-image embeddings and L2-normalize each.
         let embeddings: Vec<Vec<f32>> = data
@@ -229,6 +265,43 @@ impl SigLipSession {
 
         Ok(embeddings)
     }
+
+    /// Fail clearly when the model's output width disagrees with its manifest.
+    fn check_dim(&self, dim: usize, path: &Path) -> Result<(), PipelineError> {
+        if dim == self.embedding_dim {
+            return Ok(());
+        }
+        Err(PipelineError::Embedding {
+            path: path.to_path_buf(),
+            message: format!(
+                "Model produced {dim}-d embeddings but its manifest declares {}",
+                self.embedding_dim
+            ),
+        })
+    }
+}
+
+/// Log how much memory the session pool took, so users can size `pool_size`.
+///
+/// Uses the process's resident set where the OS reports it; elsewhere the
+/// model file size is the best available estimate per session.
+fn report_memory(model_path: &Path, count: usize, resident_before: Option<u64>) {
+    const MB: f64 = 1024.0 * 1024.0;
+    match resident_before.zip(resident_memory_bytes()) {
+        Some((before, after)) => {
+            let total = after.saturating_sub(before) as f64 / MB;
+            tracing::info!(
+                "Loaded {count} SigLIP session(s): +{total:.0} MB resident (~{:.0} MB each)",
+                total / count as f64
+            );
+        }
+        None => {
+            let model = std::fs::metadata(model_path).map_or(0, |m| m.len()) as f64 / MB;
+            tracing::info!(
+                "Loaded {count} SigLIP session(s): model is {model:.0} MB, expect at least that much memory per session"
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,113 @@
+//! Text query embeddings in the same space as image embeddings.
+//!
+//! Wraps the text encoder used for the tagging vocabulary so search backends
+//! can embed queries and compare them (by dot product) against the image
+//! embeddings Photon emits. Queries are encoded as given; the vocabulary's
+//! "a photo of a {term}" template is not applied.
+
+use std::path::Path;
+
+use crate::config::EmbeddingConfig;
+use crate::error::PipelineError;
+use crate::tagging::text_encoder::SigLipTextEncoder;
+
+use super::manifest::ModelManifest;
+
+/// Queries per ONNX call.
+const BATCH_SIZE: usize = 64;
+
+/// Encodes text into L2-normalized vectors aligned with an image model.
+pub struct TextEmbedder {
+    encoder: SigLipTextEncoder,
+}
+
+impl TextEmbedder {
+    /// Load the text encoder paired with `config.model`: the model's own
+    /// `text_model.onnx`, or the shared one if its manifest says so.
+    pub fn load(config: &EmbeddingConfig, model_dir: &Path) -> Result<Self, PipelineError> {
+        let manifest = ModelManifest::resolve(model_dir, &config.model)?;
+        let text_dir = manifest.text_model_dir(model_dir)?;
+        let encoder = SigLipTextEncoder::new(&text_dir, &manifest, &config.runtime)?;
+        tracing::debug!(
+            "Loaded text encoder for {} from {:?}",
+            manifest.name,
+            text_dir
+        );
+        Ok(Self { encoder })
+    }
+
+    /// Check whether the text encoder and tokenizer for `config.model` exist.
+    pub fn model_exists(config: &EmbeddingConfig, model_dir: &Path) -> bool {
+        ModelManifest::resolve(model_dir, &config.model)
+            .and_then(|manifest| manifest.text_model_dir(model_dir))
+            .is_ok_and(|text_dir| SigLipTextEncoder::model_exists(&text_dir))
+    }
+
+    /// Id of the image model these vectors are comparable with
+    /// (e.g. "siglip-base-patch16"). Store it next to an index to catch
+    /// queries embedded for a different model.
+    pub fn model(&self) -> &str {
+        &self.encoder.manifest().name
+    }
+
+    /// Width of the vectors `encode` returns.
+    pub fn embedding_dim(&self) -> usize {
+        self.encoder.manifest().embedding_dim
+    }
+
+    /// Embed each text, in order. Large inputs are split into batches.
+    pub fn encode(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, PipelineError> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for chunk in texts.chunks(BATCH_SIZE) {
+            embeddings.extend(self.encoder.encode_batch(chunk)?);
+        }
+        Ok(embeddings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_missing_text_encoder() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = EmbeddingConfig::default();
+        assert!(!TextEmbedder::model_exists(&config, dir.path()));
+
+        let err = TextEmbedder::load(&config, dir.path()).err().unwrap();
+        assert!(err.to_string().contains("Text encoder not found"));
+    }
+
+    #[test]
+    fn test_unknown_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = EmbeddingConfig {
+            model: "mystery-vit".to_string(),
+            ..EmbeddingConfig::default()
+        };
+        assert!(!TextEmbedder::model_exists(&config, dir.path()));
+        assert!(TextEmbedder::load(&config, dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_encode_matches_model_dim() {
+        let config = Config::default();
+        if !TextEmbedder::model_exists(&config.embedding, &config.model_dir()) {
+            eprintln!("Skipping: text encoder not found");
+            return;
+        }
+        let embedder = TextEmbedder::load(&config.embedding, &config.model_dir()).unwrap();
+        let texts = vec!["red car at night".to_string(), "a dog".to_string()];
+        let embeddings = embedder.encode(&texts).unwrap();
+
+        assert_eq!(embeddings.len(), 2);
+        for embedding in &embeddings {
+            assert_eq!(embedding.len(), embedder.embedding_dim());
+            let norm: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+            assert!((norm - 1.0).abs() < 1e-4);
+        }
+        assert_eq!(embedder.model(), "siglip-base-patch16");
+    }
+}
@@ -93,12 +93,15 @@ pub enum PipelineError {
     },
 
     /// Image dimensions exceed limit
-    #[error("Image too large: {path} ({width}x{height} > {max_dim})")]
+    #[error(
+        "Image too large: {path} ({width}x{height}, limits {max_dim}px per side, {max_megapixels} MP)"
+    )]
     ImageTooLarge {
         path: PathBuf,
         width: u32,
         height: u32,
         max_dim: u32,
+        max_megapixels: u32,
     },
 
     /// Unsupported image format
@@ -135,10 +138,10 @@ impl PipelineError {
                 Some("Increase `limits.max_file_size_mb` in config, or resize the image.")
             }
             PipelineError::ImageTooLarge { .. } => {
-                Some("Increase `limits.max_image_dimension` in config, or resize the image.")
+                Some("Increase `limits.max_image_dimension` or `limits.max_megapixels` in config, or resize the image.")
             }
             PipelineError::UnsupportedFormat { .. } => {
-                Some("Supported formats: JPEG, PNG, WebP, GIF, TIFF, BMP, AVIF.")
+                Some("Supported formats: JPEG, PNG, WebP, GIF, TIFF, BMP, AVIF, RAW (CR2, NEF, ARW, DNG).")
             }
             PipelineError::FileNotFound(_) => Some("Check the file path and try again."),
             _ => None,
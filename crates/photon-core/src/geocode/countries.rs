@@ -0,0 +1,186 @@
+//! Country polygons from a GeoJSON `FeatureCollection`, with point-in-polygon
+//! lookup.
+//!
+//! Accepts the property names of Natural Earth (`ADMIN`, `ISO_A2`) and of
+//! the datasets/geo-countries export (`name`, `ISO3166-1-Alpha-2`).
+
+use serde_json::Value;
+
+/// A closed ring of `(longitude, latitude)` vertices.
+type Ring = Vec<(f64, f64)>;
+
+/// One country: its name, ISO code and (multi)polygon outline.
+pub(crate) struct CountryShape {
+    pub name: String,
+    /// ISO 3166-1 alpha-2 code, if the feature has a valid one
+    pub code: Option<String>,
+    /// Polygons, each an outer ring followed by its holes
+    polygons: Vec<Vec<Ring>>,
+    /// Bounding box: min lon, min lat, max lon, max lat
+    bbox: [f64; 4],
+}
+
+const NAME_KEYS: &[&str] = &["ADMIN", "NAME", "name"];
+const CODE_KEYS: &[&str] = &["ISO_A2", "ISO_A2_EH", "ISO3166-1-Alpha-2", "iso_a2"];
+
+impl CountryShape {
+    /// Whether the point lies inside the country (holes excluded).
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        let [min_lon, min_lat, max_lon, max_lat] = self.bbox;
+        if longitude < min_lon || longitude > max_lon || latitude < min_lat || latitude > max_lat {
+            return false;
+        }
+        self.polygons.iter().any(|rings| {
+            let mut inside = false;
+            for ring in rings {
+                if ring_contains(ring, longitude, latitude) {
+                    inside = !inside;
+                }
+            }
+            inside
+        })
+    }
+}
+
+/// Parse the country features of a GeoJSON document.
+///
+/// Features without a name or a (Multi)Polygon geometry are skipped.
+pub(crate) fn parse_geojson(json: &str) -> Result<Vec<CountryShape>, String> {
+    let doc: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let features = doc
+        .get("features")
+        .and_then(Value::as_array)
+        .ok_or("expected a FeatureCollection")?;
+
+    Ok(features.iter().filter_map(parse_feature).collect())
+}
+
+fn parse_feature(feature: &Value) -> Option<CountryShape> {
+    let properties = feature.get("properties")?;
+    let property = |keys: &[&str]| {
+        keys.iter()
+            .filter_map(|key| properties.get(*key).and_then(Value::as_str))
+            .find(|v| !v.is_empty() && *v != "-99")
+            .map(str::to_string)
+    };
+    let name = property(NAME_KEYS)?;
+    let code = property(CODE_KEYS).filter(|c| c.len() == 2);
+
+    let geometry = feature.get("geometry")?;
+    let coordinates = geometry.get("coordinates")?;
+    let polygons: Vec<Vec<Ring>> = match geometry.get("type")?.as_str()? {
+        "Polygon" => vec![parse_polygon(coordinates)?],
+        "MultiPolygon" => coordinates
+            .as_array()?
+            .iter()
+            .filter_map(parse_polygon)
+            .collect(),
+        _ => return None,
+    };
+
+    let mut bbox = [f64::MAX, f64::MAX, f64::MIN, f64::MIN];
+    for &(lon, lat) in polygons.iter().flatten().flatten() {
+        bbox = [
+            bbox[0].min(lon),
+            bbox[1].min(lat),
+            bbox[2].max(lon),
+            bbox[3].max(lat),
+        ];
+    }
+    if bbox[0] > bbox[2] {
+        return None;
+    }
+
+    Some(CountryShape {
+        name,
+        code,
+        polygons,
+        bbox,
+    })
+}
+
+fn parse_polygon(value: &Value) -> Option<Vec<Ring>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|ring| {
+            ring.as_array()?
+                .iter()
+                .map(|pos| Some((pos.get(0)?.as_f64()?, pos.get(1)?.as_f64()?)))
+                .collect::<Option<Ring>>()
+        })
+        .collect()
+}
+
+/// Even-odd ray casting test for a single ring.
+fn ring_contains(ring: &[(f64, f64)], x: f64, y: f64) -> bool {
+    let mut inside = false;
+    let mut j = ring.len().wrapping_sub(1);
+    for i in 0..ring.len() {
+        let (xi, yi) = ring[i];
+        let (xj, yj) = ring[j];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Two boxy "countries": Portugal (with a hole) and Spain (as a
+    /// MultiPolygon), split at longitude -7.
+    pub(crate) const GEOJSON: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {"type": "Feature",
+     "properties": {"ADMIN": "Portugal", "ISO_A2": "PT"},
+     "geometry": {"type": "Polygon", "coordinates": [
+       [[-9.5, 37.0], [-7.0, 37.0], [-7.0, 42.0], [-9.5, 42.0], [-9.5, 37.0]],
+       [[-8.0, 39.5], [-7.5, 39.5], [-7.5, 40.0], [-8.0, 40.0], [-8.0, 39.5]]
+     ]}},
+    {"type": "Feature",
+     "properties": {"name": "Spain", "ISO3166-1-Alpha-2": "ES"},
+     "geometry": {"type": "MultiPolygon", "coordinates": [
+       [[[-7.0, 36.0], [3.0, 36.0], [3.0, 43.5], [-7.0, 43.5], [-7.0, 36.0]]]
+     ]}},
+    {"type": "Feature",
+     "properties": {"ADMIN": "No Geometry", "ISO_A2": "-99"},
+     "geometry": null}
+  ]
+}"#;
+
+    #[test]
+    fn test_parse_geojson() {
+        let shapes = parse_geojson(GEOJSON).unwrap();
+        assert_eq!(shapes.len(), 2);
+        assert_eq!(shapes[0].name, "Portugal");
+        assert_eq!(shapes[0].code.as_deref(), Some("PT"));
+        assert_eq!(shapes[1].name, "Spain");
+        assert_eq!(shapes[1].code.as_deref(), Some("ES"));
+    }
+
+    #[test]
+    fn test_contains() {
+        let shapes = parse_geojson(GEOJSON).unwrap();
+        let (portugal, spain) = (&shapes[0], &shapes[1]);
+
+        assert!(portugal.contains(38.7, -9.1));
+        assert!(!portugal.contains(38.9, -6.9));
+        assert!(spain.contains(38.9, -6.9));
+        // Inside Portugal's hole
+        assert!(!portugal.contains(39.75, -7.75));
+        // Atlantic
+        assert!(!portugal.contains(38.7, -12.0));
+        assert!(!spain.contains(38.7, -12.0));
+    }
+
+    #[test]
+    fn test_parse_geojson_rejects_non_collection() {
+        assert!(parse_geojson("{\"type\": \"Feature\"}").is_err());
+        assert!(parse_geojson("not json").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+//! Parsers for the GeoNames dump files (tab-separated, UTF-8).
+//!
+//! - `cities1000.txt`: one populated place per line (19 columns)
+//! - `admin1CodesASCII.txt`: `CC.code` → first-level division name
+//! - `countryInfo.txt`: ISO code → country name, `#` comment lines
+
+use std::collections::HashMap;
+use std::io::BufRead;
+
+/// A populated place from the cities dump.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct City {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// ISO 3166-1 alpha-2 code
+    pub country_code: String,
+    /// GeoNames admin1 code, unique within the country (e.g. "CA", "08")
+    pub admin1_code: String,
+}
+
+// Column positions in the cities dump
+const NAME: usize = 1;
+const LATITUDE: usize = 4;
+const LONGITUDE: usize = 5;
+const COUNTRY_CODE: usize = 8;
+const ADMIN1_CODE: usize = 10;
+
+/// Parse a GeoNames cities dump, skipping malformed lines.
+pub(crate) fn parse_cities(reader: impl BufRead) -> std::io::Result<Vec<City>> {
+    let mut cities = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() <= ADMIN1_CODE {
+            continue;
+        }
+        let (Ok(latitude), Ok(longitude)) = (
+            fields[LATITUDE].parse::<f64>(),
+            fields[LONGITUDE].parse::<f64>(),
+        ) else {
+            continue;
+        };
+        cities.push(City {
+            name: fields[NAME].to_string(),
+            latitude,
+            longitude,
+            country_code: fields[COUNTRY_CODE].to_string(),
+            admin1_code: fields[ADMIN1_CODE].to_string(),
+        });
+    }
+    Ok(cities)
+}
+
+/// Parse `admin1CodesASCII.txt` into a `"CC.code"` → name map.
+pub(crate) fn parse_admin1(reader: impl BufRead) -> std::io::Result<HashMap<String, String>> {
+    parse_pairs(reader, 0, 1)
+}
+
+/// Parse `countryInfo.txt` into an ISO alpha-2 code → country name map.
+pub(crate) fn parse_country_info(reader: impl BufRead) -> std::io::Result<HashMap<String, String>> {
+    parse_pairs(reader, 0, 4)
+}
+
+/// Map one column to another, skipping comments and short lines.
+fn parse_pairs(
+    reader: impl BufRead,
+    key: usize,
+    value: usize,
+) -> std::io::Result<HashMap<String, String>> {
+    let mut map = HashMap::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').collect();
+        if let (Some(k), Some(v)) = (fields.get(key), fields.get(value)) {
+            if !k.is_empty() && !v.is_empty() {
+                map.insert(k.to_string(), v.to_string());
+            }
+        }
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Lisbon, Porto and Badajoz (just across the Spanish border), in dump format.
+    pub(crate) const CITIES: &str = "\
+2267057\tLisbon\tLisbon\tLisboa\t38.71667\t-9.13333\tP\tPPLC\tPT\t\t14\t1106\t\t\t517802\t\t45\tEurope/Lisbon\t2022-01-01
+2735943\tPorto\tPorto\t\t41.14961\t-8.61099\tP\tPPLA\tPT\t\t17\t1312\t\t\t249633\t\t93\tEurope/Lisbon\t2022-01-01
+2521420\tBadajoz\tBadajoz\t\t38.87789\t-6.97061\tP\tPPLA2\tES\t\t57\tBA\t\t\t148334\t\t186\tEurope/Madrid\t2022-01-01
+";
+
+    pub(crate) const ADMIN1: &str = "\
+PT.14\tLisbon\tLisbon\t2267056
+PT.17\tPorto\tPorto\t2735941
+ES.57\tExtremadura\tExtremadura\t2593112
+";
+
+    pub(crate) const COUNTRY_INFO: &str = "\
+#ISO\tISO3\tISO-Numeric\tfips\tCountry\tCapital
+PT\tPRT\t620\tPO\tPortugal\tLisbon
+ES\tESP\t724\tSP\tSpain\tMadrid
+";
+
+    #[test]
+    fn test_parse_cities() {
+        let cities = parse_cities(CITIES.as_bytes()).unwrap();
+        assert_eq!(cities.len(), 3);
+        assert_eq!(cities[0].name, "Lisbon");
+        assert_eq!(cities[0].country_code, "PT");
+        assert_eq!(cities[0].admin1_code, "14");
+        assert!((cities[1].latitude - 41.14961).abs() < 1e-9);
+        assert!((cities[2].longitude + 6.97061).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_cities_skips_malformed_lines() {
+        let dump = "1\tNowhere\t\t\tnot-a-number\t0\tP\tPPL\tXX\t\t01\n2\tShort\n";
+        assert!(parse_cities(dump.as_bytes()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_lookup_tables() {
+        let admin1 = parse_admin1(ADMIN1.as_bytes()).unwrap();
+        assert_eq!(admin1["ES.57"], "Extremadura");
+
+        let countries = parse_country_info(COUNTRY_INFO.as_bytes()).unwrap();
+        assert_eq!(countries.len(), 2);
+        assert_eq!(countries["PT"], "Portugal");
+    }
+}
@@ -0,0 +1,164 @@
+//! Static k-d tree for nearest-neighbor search on the unit sphere.
+//!
+//! Points are stored as 3D unit vectors, so straight-line (chord) distance
+//! orders neighbors exactly like great-circle distance and the search needs
+//! no special handling at the poles or the antimeridian.
+
+/// Mean Earth radius in kilometers (IUGG).
+const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// Balanced k-d tree stored implicitly in one array: the node for a slice is
+/// its middle element, with the left and right halves as subtrees.
+pub(crate) struct KdTree {
+    nodes: Vec<([f64; 3], usize)>,
+}
+
+impl KdTree {
+    /// Build a tree over `(latitude, longitude)` points, identified by index.
+    pub fn build(points: impl IntoIterator<Item = (f64, f64)>) -> Self {
+        let mut nodes: Vec<([f64; 3], usize)> = points
+            .into_iter()
+            .enumerate()
+            .map(|(i, (lat, lon))| (unit_vector(lat, lon), i))
+            .collect();
+        Self::arrange(&mut nodes, 0);
+        Self { nodes }
+    }
+
+    /// Index and distance in kilometers of the nearest point that `accept`s.
+    pub fn nearest(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        accept: impl Fn(usize) -> bool,
+    ) -> Option<(usize, f64)> {
+        let target = unit_vector(latitude, longitude);
+        let mut best = None;
+        Self::search(&self.nodes, 0, &target, &accept, &mut best);
+        best.map(|(index, chord_sq)| (index, chord_to_km(chord_sq.sqrt())))
+    }
+
+    fn arrange(nodes: &mut [([f64; 3], usize)], depth: usize) {
+        if nodes.len() <= 1 {
+            return;
+        }
+        let axis = depth % 3;
+        let mid = nodes.len() / 2;
+        nodes.select_nth_unstable_by(mid, |a, b| a.0[axis].total_cmp(&b.0[axis]));
+        let (left, right) = nodes.split_at_mut(mid);
+        Self::arrange(left, depth + 1);
+        Self::arrange(&mut right[1..], depth + 1);
+    }
+
+    fn search(
+        nodes: &[([f64; 3], usize)],
+        depth: usize,
+        target: &[f64; 3],
+        accept: &impl Fn(usize) -> bool,
+        best: &mut Option<(usize, f64)>,
+    ) {
+        if nodes.is_empty() {
+            return;
+        }
+        let mid = nodes.len() / 2;
+        let (point, index) = nodes[mid];
+
+        let dist_sq: f64 = (0..3).map(|i| (point[i] - target[i]).powi(2)).sum();
+        if best.is_none_or(|(_, best_sq)| dist_sq < best_sq) && accept(index) {
+            *best = Some((index, dist_sq));
+        }
+
+        let axis = depth % 3;
+        let delta = target[axis] - point[axis];
+        let (near, far) = if delta < 0.0 {
+            (&nodes[..mid], &nodes[mid + 1..])
+        } else {
+            (&nodes[mid + 1..], &nodes[..mid])
+        };
+        Self::search(near, depth + 1, target, accept, best);
+        // The far side can only hold a closer point if the splitting plane is
+        // nearer than the best match so far
+        if best.is_none_or(|(_, best_sq)| delta * delta < best_sq) {
+            Self::search(far, depth + 1, target, accept, best);
+        }
+    }
+}
+
+fn unit_vector(latitude: f64, longitude: f64) -> [f64; 3] {
+    let (lat, lon) = (latitude.to_radians(), longitude.to_radians());
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+fn chord_to_km(chord: f64) -> f64 {
+    2.0 * (chord / 2.0).min(1.0).asin() * EARTH_RADIUS_KM
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Great-circle distance in kilometers between two positions.
+    fn distance_km(a: (f64, f64), b: (f64, f64)) -> f64 {
+        let (p, q) = (unit_vector(a.0, a.1), unit_vector(b.0, b.1));
+        let chord = (0..3).map(|i| (p[i] - q[i]).powi(2)).sum::<f64>().sqrt();
+        chord_to_km(chord)
+    }
+
+    fn brute_force(points: &[(f64, f64)], target: (f64, f64)) -> usize {
+        (0..points.len())
+            .min_by(|&a, &b| {
+                distance_km(points[a], target).total_cmp(&distance_km(points[b], target))
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_distance_km() {
+        // Lisbon → Porto is ~274 km
+        let d = distance_km((38.71667, -9.13333), (41.14961, -8.61099));
+        assert!((d - 274.0).abs() < 2.0, "got {d}");
+        assert!(distance_km((10.0, 20.0), (10.0, 20.0)) < 1e-9);
+    }
+
+    #[test]
+    fn test_nearest_matches_brute_force() {
+        // Deterministic pseudo-random grid of points covering the globe
+        let points: Vec<(f64, f64)> = (0..500)
+            .map(|i| {
+                let lat = ((i * 37) % 179) as f64 - 89.0;
+                let lon = ((i * 101) % 359) as f64 - 179.0;
+                (lat, lon)
+            })
+            .collect();
+        let tree = KdTree::build(points.iter().copied());
+
+        for target in [
+            (0.0, 0.0),
+            (51.5, -0.1),
+            (-33.9, 151.2),
+            (89.9, 10.0),
+            (5.0, 179.9),
+        ] {
+            let (index, km) = tree.nearest(target.0, target.1, |_| true).unwrap();
+            assert_eq!(index, brute_force(&points, target), "target {target:?}");
+            assert!((km - distance_km(points[index], target)).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_nearest_crosses_antimeridian() {
+        let tree = KdTree::build([(0.0, 179.5), (0.0, 170.0)]);
+        let (index, km) = tree.nearest(0.0, -179.5, |_| true).unwrap();
+        assert_eq!(index, 0);
+        assert!(km < 112.0);
+    }
+
+    #[test]
+    fn test_nearest_with_filter() {
+        let tree = KdTree::build([(0.0, 0.0), (0.0, 1.0), (0.0, 2.0)]);
+        let (index, _) = tree.nearest(0.0, 0.1, |i| i != 0).unwrap();
+        assert_eq!(index, 1);
+        assert!(tree.nearest(0.0, 0.1, |_| false).is_none());
+        assert!(KdTree::build([]).nearest(0.0, 0.0, |_| true).is_none());
+    }
+}
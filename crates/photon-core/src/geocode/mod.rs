@@ -0,0 +1,293 @@
+//! Offline reverse geocoding of GPS coordinates.
+//!
+//! Resolves a position to country, first-level division and nearest city
+//! using a locally installed GeoNames dump and country polygons — no network
+//! access. Cities are indexed in a k-d tree; the country comes from the
+//! polygon containing the point, falling back to the nearest city's country
+//! (e.g. just off the coast, where simplified outlines end).
+
+pub(crate) mod countries;
+pub(crate) mod geonames;
+pub(crate) mod kdtree;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::config::GeocodingConfig;
+use crate::error::{PipelineError, Result};
+use crate::types::Location;
+
+use countries::CountryShape;
+use geonames::City;
+use kdtree::KdTree;
+
+/// Reverse geocoder over an in-memory GeoNames index.
+pub struct Geocoder {
+    cities: Vec<City>,
+    index: KdTree,
+    /// `"CC.code"` → admin1 name
+    admin1: HashMap<String, String>,
+    /// ISO alpha-2 code → country name
+    country_names: HashMap<String, String>,
+    shapes: Vec<CountryShape>,
+    max_distance_km: f64,
+}
+
+impl Geocoder {
+    /// GeoNames populated places with population ≥ 1000, as extracted text.
+    pub const CITIES_FILE: &'static str = "cities1000.txt";
+    /// The same dump as downloaded; read directly when not extracted.
+    pub const CITIES_ARCHIVE: &'static str = "cities1000.zip";
+    /// GeoNames first-level administrative division names.
+    pub const ADMIN1_FILE: &'static str = "admin1CodesASCII.txt";
+    /// GeoNames country names and codes.
+    pub const COUNTRY_INFO_FILE: &'static str = "countryInfo.txt";
+    /// Country outlines as a GeoJSON `FeatureCollection`.
+    pub const COUNTRIES_FILE: &'static str = "countries.geojson";
+
+    /// Check whether the dataset files are present in `dir`.
+    pub fn dataset_exists(dir: &Path) -> bool {
+        (dir.join(Self::CITIES_FILE).exists() || dir.join(Self::CITIES_ARCHIVE).exists())
+            && dir.join(Self::ADMIN1_FILE).exists()
+            && dir.join(Self::COUNTRY_INFO_FILE).exists()
+            && dir.join(Self::COUNTRIES_FILE).exists()
+    }
+
+    /// Load the dataset from `dir` and build the spatial index.
+    pub fn load(config: &GeocodingConfig, dir: &Path) -> Result<Self> {
+        let cities = read_cities(dir)?;
+        let admin1 = geonames::parse_admin1(open(&dir.join(Self::ADMIN1_FILE))?)
+            .map_err(|e| dataset_error(&dir.join(Self::ADMIN1_FILE), e))?;
+        let country_names = geonames::parse_country_info(open(&dir.join(Self::COUNTRY_INFO_FILE))?)
+            .map_err(|e| dataset_error(&dir.join(Self::COUNTRY_INFO_FILE), e))?;
+
+        let countries_path = dir.join(Self::COUNTRIES_FILE);
+        let json = std::fs::read_to_string(&countries_path)
+            .map_err(|e| dataset_error(&countries_path, e))?;
+        let shapes =
+            countries::parse_geojson(&json).map_err(|e| dataset_error(&countries_path, e))?;
+
+        tracing::debug!(
+            "Geocoder loaded: {} cities, {} country outlines",
+            cities.len(),
+            shapes.len()
+        );
+        Ok(Self::new(
+            cities,
+            admin1,
+            country_names,
+            shapes,
+            config.max_distance_km,
+        ))
+    }
+
+    fn new(
+        cities: Vec<City>,
+        admin1: HashMap<String, String>,
+        country_names: HashMap<String, String>,
+        shapes: Vec<CountryShape>,
+        max_distance_km: f64,
+    ) -> Self {
+        let index = KdTree::build(cities.iter().map(|c| (c.latitude, c.longitude)));
+        Self {
+            cities,
+            index,
+            admin1,
+            country_names,
+            shapes,
+            max_distance_km,
+        }
+    }
+
+    /// Number of indexed cities.
+    pub fn city_count(&self) -> usize {
+        self.cities.len()
+    }
+
+    /// Resolve a position to a place.
+    ///
+    /// `city`, `admin1` and `distance_km` are set when a city lies within
+    /// `max_distance_km` (in the same country, when the point falls inside a
+    /// country polygon). Returns `None` for invalid coordinates or when
+    /// neither a country nor a nearby city is found (open sea).
+    pub fn lookup(&self, latitude: f64, longitude: f64) -> Option<Location> {
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return None;
+        }
+
+        let shape = self.shapes.iter().find(|s| s.contains(latitude, longitude));
+        let shape_code = shape.and_then(|s| s.code.as_deref());
+
+        let nearest = self.index.nearest(latitude, longitude, |i| {
+            shape_code.is_none_or(|code| self.cities[i].country_code == code)
+        });
+        let city = nearest
+            .filter(|&(_, km)| km <= self.max_distance_km)
+            .map(|(i, km)| (&self.cities[i], km));
+
+        let country_code = shape_code
+            .map(str::to_string)
+            .or_else(|| city.map(|(c, _)| c.country_code.clone()));
+        let country = country_code
+            .as_ref()
+            .and_then(|code| self.country_names.get(code).cloned())
+            .or_else(|| shape.map(|s| s.name.clone()));
+
+        if country.is_none() && city.is_none() {
+            return None;
+        }
+
+        Some(Location {
+            country,
+            country_code,
+            admin1: city.and_then(|(c, _)| {
+                self.admin1
+                    .get(&format!("{}.{}", c.country_code, c.admin1_code))
+                    .cloned()
+            }),
+            city: city.map(|(c, _)| c.name.clone()),
+            distance_km: city.map(|(_, km)| (km * 100.0).round() / 100.0),
+        })
+    }
+}
+
+/// Read the cities dump, preferring the extracted text over the zip.
+fn read_cities(dir: &Path) -> Result<Vec<City>> {
+    let text_path = dir.join(Geocoder::CITIES_FILE);
+    if text_path.exists() {
+        return geonames::parse_cities(open(&text_path)?)
+            .map_err(|e| dataset_error(&text_path, e).into());
+    }
+
+    let zip_path = dir.join(Geocoder::CITIES_ARCHIVE);
+    let file = File::open(&zip_path).map_err(|e| dataset_error(&zip_path, e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| dataset_error(&zip_path, e))?;
+    let entry = archive
+        .by_name(Geocoder::CITIES_FILE)
+        .map_err(|e| dataset_error(&zip_path, e))?;
+    geonames::parse_cities(BufReader::new(entry)).map_err(|e| dataset_error(&zip_path, e).into())
+}
+
+fn open(path: &Path) -> Result<BufReader<File>> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| dataset_error(path, e).into())
+}
+
+fn dataset_error(path: &Path, error: impl std::fmt::Display) -> PipelineError {
+    PipelineError::Model {
+        message: format!("Failed to read geocoding dataset {:?}: {error}", path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_dataset(dir: &Path, zipped: bool) {
+        if zipped {
+            let file = File::create(dir.join(Geocoder::CITIES_ARCHIVE)).unwrap();
+            let mut zip = zip::ZipWriter::new(file);
+            zip.start_file(
+                Geocoder::CITIES_FILE,
+                zip::write::SimpleFileOptions::default(),
+            )
+            .unwrap();
+            zip.write_all(geonames::tests::CITIES.as_bytes()).unwrap();
+            zip.finish().unwrap();
+        } else {
+            std::fs::write(dir.join(Geocoder::CITIES_FILE), geonames::tests::CITIES).unwrap();
+        }
+        std::fs::write(dir.join(Geocoder::ADMIN1_FILE), geonames::tests::ADMIN1).unwrap();
+        std::fs::write(
+            dir.join(Geocoder::COUNTRY_INFO_FILE),
+            geonames::tests::COUNTRY_INFO,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join(Geocoder::COUNTRIES_FILE),
+            countries::tests::GEOJSON,
+        )
+        .unwrap();
+    }
+
+    fn geocoder() -> Geocoder {
+        let dir = tempfile::tempdir().unwrap();
+        write_dataset(dir.path(), false);
+        Geocoder::load(&GeocodingConfig::default(), dir.path()).unwrap()
+    }
+
+    #[test]
+    fn test_dataset_exists() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!Geocoder::dataset_exists(dir.path()));
+        write_dataset(dir.path(), true);
+        assert!(Geocoder::dataset_exists(dir.path()));
+    }
+
+    #[test]
+    fn test_load_from_zipped_cities() {
+        let dir = tempfile::tempdir().unwrap();
+        write_dataset(dir.path(), true);
+        let geocoder = Geocoder::load(&GeocodingConfig::default(), dir.path()).unwrap();
+        assert_eq!(geocoder.city_count(), 3);
+    }
+
+    #[test]
+    fn test_load_missing_dataset_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(Geocoder::load(&GeocodingConfig::default(), dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_lookup_city() {
+        let location = geocoder().lookup(38.72, -9.14).unwrap();
+        assert_eq!(location.country.as_deref(), Some("Portugal"));
+        assert_eq!(location.country_code.as_deref(), Some("PT"));
+        assert_eq!(location.admin1.as_deref(), Some("Lisbon"));
+        assert_eq!(location.city.as_deref(), Some("Lisbon"));
+        assert!(location.distance_km.unwrap() < 1.0);
+    }
+
+    #[test]
+    fn test_lookup_prefers_city_in_containing_country() {
+        // Just west of the border: Badajoz (ES) is nearer, but the point is in
+        // Portugal, so the nearest Portuguese city wins (if close enough)
+        let mut geocoder = geocoder();
+        geocoder.max_distance_km = 500.0;
+        let location = geocoder.lookup(38.88, -7.05).unwrap();
+        assert_eq!(location.country_code.as_deref(), Some("PT"));
+        assert_eq!(location.city.as_deref(), Some("Lisbon"));
+        assert!(location.distance_km.unwrap() > 150.0);
+    }
+
+    #[test]
+    fn test_lookup_country_only_when_city_too_far() {
+        let location = geocoder().lookup(38.88, -7.05).unwrap();
+        assert_eq!(location.country.as_deref(), Some("Portugal"));
+        assert!(location.city.is_none());
+        assert!(location.admin1.is_none());
+        assert!(location.distance_km.is_none());
+    }
+
+    #[test]
+    fn test_lookup_offshore_falls_back_to_city_country() {
+        // ~40 km off Lisbon, outside every polygon
+        let location = geocoder().lookup(38.72, -9.6).unwrap();
+        assert_eq!(location.country.as_deref(), Some("Portugal"));
+        assert_eq!(location.country_code.as_deref(), Some("PT"));
+        assert_eq!(location.city.as_deref(), Some("Lisbon"));
+        assert!(location.distance_km.unwrap() > 30.0);
+    }
+
+    #[test]
+    fn test_lookup_open_sea_and_invalid() {
+        let geocoder = geocoder();
+        assert!(geocoder.lookup(30.0, -40.0).is_none());
+        assert!(geocoder.lookup(95.0, 0.0).is_none());
+        assert!(geocoder.lookup(0.0, f64::NAN).is_none());
+    }
+}
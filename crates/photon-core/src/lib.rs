@@ -20,7 +20,7 @@
 //! #[tokio::main]
 //! async fn main() -> photon_core::Result<()> {
 //!     let config = Config::load()?;
-//!     let mut processor = ImageProcessor::new(&config);
+//!     let mut processor = ImageProcessor::new(&config)?;
 //!     processor.load_embedding(&config)?;
 //!     processor.load_tagging(&config)?;
 //!
@@ -37,8 +37,10 @@ extern crate blas_src;
 
 // Module declarations — public modules have re-exported consumer types
 pub mod config;
+pub(crate) mod dedup;
 pub(crate) mod embedding;
 pub mod error;
+pub(crate) mod geocode;
 pub(crate) mod llm;
 pub(crate) mod math;
 pub(crate) mod output;
@@ -48,16 +50,27 @@ pub mod types;
 
 // Re-exports for convenient access
 pub use config::Config;
+pub use dedup::{find_duplicates, DedupOptions};
+pub use embedding::manifest::{
+    ModelManifest, ModelRegistry, ModelSource, TextEncoderSource, MANIFEST_FILENAME,
+};
 pub use embedding::preprocess::preprocess as preprocess_image;
+pub use embedding::text::TextEmbedder;
 pub use embedding::EmbeddingEngine;
 pub use error::{ConfigError, PhotonError, PipelineError, PipelineResult, Result};
+pub use geocode::Geocoder;
 pub use llm::{EnrichOptions, EnrichResult, Enricher, LlmProviderFactory};
 pub use output::{OutputFormat, OutputWriter};
 pub use pipeline::{
-    DiscoveredFile, FileDiscovery, Hasher, ImageDecoder, ImageProcessor, MetadataExtractor,
-    ProcessOptions, ThumbnailGenerator,
+    ArchiveEntry, ArchiveKind, ArchiveReader, DiscoveredFile, DiscoveryFilters, FileDiscovery,
+    FilterCounts, Hasher, ImageDecoder, ImageProcessor, MetadataExtractor, PaletteExtractor,
+    PlaceholderGenerator, ProcessOptions, QualityAnalyzer, Redactor, ThumbnailGenerator,
+};
+pub use types::{
+    DuplicateCluster, DuplicateKind, DuplicateMember, EnrichmentPatch, ExifData, Location,
+    OutputRecord, PaletteColor, ProcessedImage, ProcessingStats, QualityMetrics, Rendition, Tag,
+    XmpData,
 };
-pub use types::{EnrichmentPatch, ExifData, OutputRecord, ProcessedImage, ProcessingStats, Tag};
 
 /// Library version.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
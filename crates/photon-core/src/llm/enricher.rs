@@ -6,6 +6,7 @@
 
 use super::provider::{ImageInput, LlmProvider, LlmRequest};
 use super::retry;
+use crate::pipeline::archive;
 use crate::types::{EnrichmentPatch, ProcessedImage};
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -124,12 +125,21 @@ async fn enrich_single(
     image: &ProcessedImage,
     options: &EnrichOptions,
 ) -> EnrichResult {
+    // In-memory images have no file to send. Privacy mode moves the real
+    // path to `source_path` when it hashes `file_path`.
+    let Some(file_path) = image.source_path.as_deref().or(image.file_path.as_deref()) else {
+        return EnrichResult::Failure(
+            PathBuf::from(&image.file_name),
+            "No source file to read (image was processed from memory)".to_string(),
+        );
+    };
+
     // Guard: check file size before reading into memory
     let max_bytes = options.max_file_size_mb * 1024 * 1024;
-    match tokio::fs::metadata(&image.file_path).await {
+    match tokio::fs::metadata(file_path).await {
         Ok(m) if m.len() > max_bytes => {
             return EnrichResult::Failure(
-                image.file_path.clone(),
+                file_path.to_path_buf(),
                 format!(
                     "Image too large for enrichment: {} MB (limit: {} MB)",
                     m.len() / (1024 * 1024),
@@ -138,20 +148,28 @@ async fn enrich_single(
             );
         }
         Ok(_) => {} // within limit
+        Err(_) if archive::is_locator(file_path) => {
+            return EnrichResult::Failure(
+                file_path.to_path_buf(),
+                "Archive entries are not enriched (no file on disk to send); \
+                 extract the archive to describe them"
+                    .to_string(),
+            );
+        }
         Err(e) => {
             return EnrichResult::Failure(
-                image.file_path.clone(),
+                file_path.to_path_buf(),
                 format!("Failed to stat image: {e}"),
             );
         }
     }
 
     // Read image from disk and encode as base64
-    let image_bytes = match tokio::fs::read(&image.file_path).await {
+    let image_bytes = match tokio::fs::read(file_path).await {
         Ok(bytes) => bytes,
         Err(e) => {
             return EnrichResult::Failure(
-                image.file_path.clone(),
+                file_path.to_path_buf(),
                 format!("Failed to read image: {e}"),
             );
         }
@@ -168,7 +186,7 @@ async fn enrich_single(
             tracing::debug!(
                 "Retry {attempt}/{} for {:?} after {delay:?}",
                 options.retry_attempts,
-                image.file_path
+                file_path
             );
             tokio::time::sleep(delay).await;
         }
@@ -201,7 +219,7 @@ async fn enrich_single(
         }
     }
 
-    EnrichResult::Failure(image.file_path.clone(), last_error)
+    EnrichResult::Failure(file_path.to_path_buf(), last_error)
 }
 
 #[cfg(test)]
@@ -339,19 +357,14 @@ mod tests {
             .join("../../tests/fixtures/images")
             .join(name);
         ProcessedImage {
-            file_path: path,
+            file_path: Some(path),
             file_name: name.to_string(),
             content_hash: format!("hash_{name}"),
             width: 100,
             height: 100,
             format: "jpeg".to_string(),
             file_size: 1000,
-            embedding: vec![],
-            exif: None,
-            tags: vec![],
-            description: None,
-            thumbnail: None,
-            perceptual_hash: None,
+            ..ProcessedImage::default()
         }
     }
 
@@ -483,7 +496,7 @@ mod tests {
             fixture_image("beach.jpg"),
             {
                 let mut img = fixture_image("nonexistent.jpg");
-                img.file_path = PathBuf::from("/tmp/definitely_does_not_exist.jpg");
+                img.file_path = Some(PathBuf::from("/tmp/definitely_does_not_exist.jpg"));
                 img
             },
             fixture_image("car.jpg"),
@@ -511,7 +524,7 @@ mod tests {
         let provider = MockProvider::success("should not reach");
         let call_count = provider.call_count_handle();
         let mut image = fixture_image("ghost.jpg");
-        image.file_path = PathBuf::from("/nonexistent/path/ghost.jpg");
+        image.file_path = Some(PathBuf::from("/nonexistent/path/ghost.jpg"));
         let images = vec![image];
         let (results, (succeeded, failed)) = run_enricher(provider, &images, fast_options()).await;
 
@@ -528,6 +541,51 @@ mod tests {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_enricher_in_memory_image_fails() {
+        let provider = MockProvider::success("should not reach");
+        let call_count = provider.call_count_handle();
+        let mut image = fixture_image("upload.jpg");
+        image.file_path = None;
+        let images = vec![image];
+        let (results, (succeeded, failed)) = run_enricher(provider, &images, fast_options()).await;
+
+        assert_eq!(succeeded, 0);
+        assert_eq!(failed, 1);
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+        match &results[0] {
+            EnrichResult::Failure(path, msg) => {
+                assert_eq!(path, &PathBuf::from("upload.jpg"));
+                assert!(msg.contains("processed from memory"), "Got: {msg}");
+            }
+            EnrichResult::Success(_) => panic!("Expected in-memory failure"),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_enricher_archive_entry_fails_clearly() {
+        let provider = MockProvider::success("should not reach");
+        let call_count = provider.call_count_handle();
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("set.tar");
+        std::fs::write(&archive, b"").unwrap();
+        let mut image = fixture_image("a.jpg");
+        image.file_path = Some(archive::locator(&archive, "a.jpg"));
+
+        let (results, (_, failed)) = run_enricher(provider, &[image], fast_options()).await;
+        assert_eq!(failed, 1);
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+        match &results[0] {
+            EnrichResult::Failure(_, msg) => {
+                assert!(
+                    msg.contains("Archive entries are not enriched"),
+                    "Got: {msg}"
+                );
+            }
+            EnrichResult::Success(_) => panic!("Expected archive-entry failure"),
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
     async fn test_enricher_semaphore_bounds_concurrency() {
         // Track concurrent in-flight calls to verify semaphore enforcement.
@@ -619,7 +677,7 @@ mod tests {
         std::fs::write(&big_file, vec![0u8; 1024 * 1024 + 1]).unwrap();
 
         let mut image = fixture_image("huge.jpg");
-        image.file_path = big_file;
+        image.file_path = Some(big_file);
 
         let options = EnrichOptions {
             max_file_size_mb: 1,
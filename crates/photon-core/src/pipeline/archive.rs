@@ -0,0 +1,430 @@
+//! Zip and tar(.gz) archives as virtual directories.
+//!
+//! Discovery lists the supported entries of an archive without extracting
+//! anything; [`ArchiveReader`] later reads each entry into memory for the
+//! in-memory processing path. Entries are addressed by locators of the form
+//! `photos.zip!/2019/beach.jpg`.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use flate2::read::MultiGzDecoder;
+use zip::ZipArchive;
+
+use crate::error::PipelineError;
+
+/// Separator between the archive path and the entry name in a locator.
+pub const LOCATOR_SEPARATOR: &str = "!/";
+
+/// Supported archive container types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// Detect the archive type from the file name (`.zip`, `.tar`, `.tar.gz`, `.tgz`).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// A file stored inside an archive.
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    /// Path to the archive on disk
+    pub archive: PathBuf,
+    /// Archive container type
+    pub kind: ArchiveKind,
+    /// Entry name inside the archive
+    pub name: String,
+    /// Position in the archive (zip central directory index, tar entry ordinal)
+    pub index: usize,
+    /// Tar: offset of the entry data in the uncompressed stream. Zip: unused.
+    pub offset: u64,
+    /// Uncompressed size in bytes, as declared by the archive
+    pub size: u64,
+}
+
+impl ArchiveEntry {
+    /// The `archive!/entry` locator used as this entry's path.
+    pub fn locator(&self) -> PathBuf {
+        locator(&self.archive, &self.name)
+    }
+}
+
+/// Build an `archive!/entry` locator.
+pub fn locator(archive: &Path, name: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{}{}{}",
+        archive.display(),
+        LOCATOR_SEPARATOR,
+        name.trim_start_matches('/')
+    ))
+}
+
+/// Whether `path` is an `archive!/entry` locator rather than a file on disk:
+/// it has no file of its own, and the part before some `!/` is an archive.
+pub(crate) fn is_locator(path: &Path) -> bool {
+    let Some(text) = path.to_str() else {
+        return false;
+    };
+    !path.exists()
+        && text.match_indices(LOCATOR_SEPARATOR).any(|(at, _)| {
+            let archive = Path::new(&text[..at]);
+            ArchiveKind::from_path(archive).is_some() && archive.is_file()
+        })
+}
+
+/// List the regular-file entries of an archive accepted by `keep`.
+///
+/// Only reads the zip central directory or the tar headers; no entry data is
+/// held in memory. Entries are returned in archive order.
+pub fn list_entries(
+    archive: &Path,
+    kind: ArchiveKind,
+    keep: impl Fn(&Path) -> bool,
+) -> io::Result<Vec<ArchiveEntry>> {
+    let file = BufReader::new(File::open(archive)?);
+    let mut entries = Vec::new();
+
+    match kind {
+        ArchiveKind::Zip => {
+            let mut zip = ZipArchive::new(file).map_err(io::Error::other)?;
+            for index in 0..zip.len() {
+                let entry = zip.by_index_raw(index).map_err(io::Error::other)?;
+                if !entry.is_file() || !keep(Path::new(entry.name())) {
+                    continue;
+                }
+                entries.push(ArchiveEntry {
+                    archive: archive.to_path_buf(),
+                    kind,
+                    name: entry.name().to_string(),
+                    index,
+                    offset: 0,
+                    size: entry.size(),
+                });
+            }
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let mut tar = tar::Archive::new(open_tar_stream(file, kind));
+            for (index, entry) in tar.entries()?.enumerate() {
+                let entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let path = entry.path()?;
+                if !keep(&path) {
+                    continue;
+                }
+                entries.push(ArchiveEntry {
+                    archive: archive.to_path_buf(),
+                    kind,
+                    name: path.to_string_lossy().into_owned(),
+                    index,
+                    offset: entry.raw_file_position(),
+                    size: entry.size(),
+                });
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Reads archive entries into memory, enforcing a per-entry size limit.
+///
+/// Keeps the most recently used archive open. Tar archives have no index, so
+/// reading entries in archive order (as discovery lists them) streams through
+/// the archive once; reading backwards reopens it.
+pub struct ArchiveReader {
+    max_bytes: u64,
+    max_mb: u64,
+    open: Option<OpenArchive>,
+}
+
+enum OpenArchive {
+    Zip {
+        path: PathBuf,
+        zip: ZipArchive<BufReader<File>>,
+    },
+    Tar {
+        path: PathBuf,
+        stream: Box<dyn Read + Send>,
+        position: u64,
+    },
+}
+
+impl ArchiveReader {
+    /// Create a reader that rejects entries larger than `max_file_size_mb`.
+    pub fn new(max_file_size_mb: u64) -> Self {
+        Self {
+            max_bytes: max_file_size_mb * 1024 * 1024,
+            max_mb: max_file_size_mb,
+            open: None,
+        }
+    }
+
+    /// Read one entry fully into memory.
+    ///
+    /// The declared size is checked before reading, and reads are capped at
+    /// the limit so an entry that lies about its size cannot exhaust memory.
+    pub fn read(&mut self, entry: &ArchiveEntry) -> Result<Vec<u8>, PipelineError> {
+        let locator = entry.locator();
+        if entry.size > self.max_bytes {
+            return Err(self.too_large(&locator, entry.size));
+        }
+
+        let read_error = |e: io::Error| PipelineError::Decode {
+            path: locator.clone(),
+            message: format!("Cannot read archive entry: {e}"),
+        };
+
+        let bytes = match entry.kind {
+            ArchiveKind::Zip => self.read_zip(entry).map_err(read_error)?,
+            ArchiveKind::Tar | ArchiveKind::TarGz => self.read_tar(entry).map_err(read_error)?,
+        };
+        if bytes.len() as u64 > self.max_bytes {
+            return Err(self.too_large(&locator, bytes.len() as u64));
+        }
+        Ok(bytes)
+    }
+
+    fn read_zip(&mut self, entry: &ArchiveEntry) -> io::Result<Vec<u8>> {
+        let reuse =
+            matches!(&self.open, Some(OpenArchive::Zip { path, .. }) if *path == entry.archive);
+        if !reuse {
+            let file = BufReader::new(File::open(&entry.archive)?);
+            self.open = Some(OpenArchive::Zip {
+                path: entry.archive.clone(),
+                zip: ZipArchive::new(file).map_err(io::Error::other)?,
+            });
+        }
+        let Some(OpenArchive::Zip { zip, .. }) = &mut self.open else {
+            unreachable!("zip archive opened above");
+        };
+
+        let file = zip.by_index(entry.index).map_err(io::Error::other)?;
+        let mut bytes = Vec::with_capacity(entry.size as usize);
+        file.take(self.max_bytes + 1).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn read_tar(&mut self, entry: &ArchiveEntry) -> io::Result<Vec<u8>> {
+        let reuse = matches!(
+            &self.open,
+            Some(OpenArchive::Tar { path, position, .. })
+                if *path == entry.archive && *position <= entry.offset
+        );
+        if !reuse {
+            let file = BufReader::new(File::open(&entry.archive)?);
+            self.open = Some(OpenArchive::Tar {
+                path: entry.archive.clone(),
+                stream: open_tar_stream(file, entry.kind),
+                position: 0,
+            });
+        }
+        let Some(OpenArchive::Tar {
+            stream, position, ..
+        }) = &mut self.open
+        else {
+            unreachable!("tar archive opened above");
+        };
+
+        // Skip forward to the entry data
+        let skip = entry.offset - *position;
+        let skipped = io::copy(&mut stream.as_mut().take(skip), &mut io::sink())?;
+        *position += skipped;
+        if skipped < skip {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "archive ended before entry data",
+            ));
+        }
+
+        let mut bytes = Vec::with_capacity(entry.size as usize);
+        stream.as_mut().take(entry.size).read_to_end(&mut bytes)?;
+        *position += bytes.len() as u64;
+        if (bytes.len() as u64) < entry.size {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "archive ended inside entry data",
+            ));
+        }
+        Ok(bytes)
+    }
+
+    fn too_large(&self, locator: &Path, size: u64) -> PipelineError {
+        PipelineError::FileTooLarge {
+            path: locator.to_path_buf(),
+            size_mb: size / (1024 * 1024),
+            max_mb: self.max_mb,
+        }
+    }
+}
+
+/// Wrap a tar file in a gzip decoder if needed.
+fn open_tar_stream(file: BufReader<File>, kind: ArchiveKind) -> Box<dyn Read + Send> {
+    match kind {
+        ArchiveKind::TarGz => Box::new(MultiGzDecoder::new(file)),
+        _ => Box::new(file),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn is_jpeg(path: &Path) -> bool {
+        path.extension().is_some_and(|e| e == "jpg")
+    }
+
+    fn write_zip(path: &Path, files: &[(&str, &[u8])]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, data) in files {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn write_tar_gz(path: &Path, files: &[(&str, &[u8])]) {
+        let gz =
+            flate2::write::GzEncoder::new(File::create(path).unwrap(), flate2::Compression::fast());
+        let mut tar = tar::Builder::new(gz);
+        for (name, data) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, *data).unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_kind_from_path() {
+        assert_eq!(
+            ArchiveKind::from_path(Path::new("a.ZIP")),
+            Some(ArchiveKind::Zip)
+        );
+        assert_eq!(
+            ArchiveKind::from_path(Path::new("a.tar")),
+            Some(ArchiveKind::Tar)
+        );
+        assert_eq!(
+            ArchiveKind::from_path(Path::new("a.tar.gz")),
+            Some(ArchiveKind::TarGz)
+        );
+        assert_eq!(
+            ArchiveKind::from_path(Path::new("a.tgz")),
+            Some(ArchiveKind::TarGz)
+        );
+        assert_eq!(ArchiveKind::from_path(Path::new("a.gz")), None);
+        assert_eq!(ArchiveKind::from_path(Path::new("a.jpg")), None);
+    }
+
+    #[test]
+    fn test_locator() {
+        assert_eq!(
+            locator(Path::new("/sets/a.zip"), "2019/beach.jpg"),
+            PathBuf::from("/sets/a.zip!/2019/beach.jpg")
+        );
+    }
+
+    #[test]
+    fn test_is_locator() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("set.zip");
+        write_zip(&archive, &[("a.jpg", b"jpeg")]);
+
+        assert!(is_locator(&locator(&archive, "a.jpg")));
+        assert!(!is_locator(&archive));
+        assert!(!is_locator(&locator(&dir.path().join("gone.zip"), "a.jpg")));
+    }
+
+    #[test]
+    fn test_zip_list_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("set.zip");
+        write_zip(
+            &path,
+            &[
+                ("a.jpg", b"first"),
+                ("notes.txt", b"skip"),
+                ("sub/b.jpg", b"second"),
+            ],
+        );
+
+        let entries = list_entries(&path, ArchiveKind::Zip, is_jpeg).unwrap();
+        let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["a.jpg", "sub/b.jpg"]);
+        assert_eq!(entries[1].size, 6);
+
+        let mut reader = ArchiveReader::new(1);
+        assert_eq!(reader.read(&entries[1]).unwrap(), b"second");
+        assert_eq!(reader.read(&entries[0]).unwrap(), b"first");
+    }
+
+    #[test]
+    fn test_tar_gz_read_in_any_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("set.tar.gz");
+        write_tar_gz(
+            &path,
+            &[("a.jpg", b"first"), ("b.txt", b"skip"), ("c.jpg", b"third")],
+        );
+
+        let entries = list_entries(&path, ArchiveKind::TarGz, is_jpeg).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let mut reader = ArchiveReader::new(1);
+        assert_eq!(reader.read(&entries[0]).unwrap(), b"first");
+        assert_eq!(reader.read(&entries[1]).unwrap(), b"third");
+        // Going backwards reopens the stream
+        assert_eq!(reader.read(&entries[0]).unwrap(), b"first");
+    }
+
+    #[test]
+    fn test_entry_over_limit_is_rejected_before_reading() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bomb.zip");
+        write_zip(&path, &[("big.jpg", &vec![0u8; 2 * 1024 * 1024])]);
+
+        let entries = list_entries(&path, ArchiveKind::Zip, is_jpeg).unwrap();
+        let err = ArchiveReader::new(1).read(&entries[0]).unwrap_err();
+        match err {
+            PipelineError::FileTooLarge { path, max_mb, .. } => {
+                assert!(path.to_string_lossy().ends_with("bomb.zip!/big.jpg"));
+                assert_eq!(max_mb, 1);
+            }
+            other => panic!("expected FileTooLarge, got: {other}"),
+        }
+    }
+
+    #[test]
+    fn test_understated_size_is_capped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("liar.zip");
+        write_zip(&path, &[("big.jpg", &vec![0u8; 2 * 1024 * 1024])]);
+
+        let mut entries = list_entries(&path, ArchiveKind::Zip, is_jpeg).unwrap();
+        entries[0].size = 10;
+        let err = ArchiveReader::new(1).read(&entries[0]).unwrap_err();
+        assert!(matches!(err, PipelineError::FileTooLarge { .. }));
+    }
+}
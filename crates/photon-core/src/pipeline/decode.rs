@@ -1,16 +1,29 @@
 //! Image decoding with format detection, validation, and timeout support.
 
-use image::{DynamicImage, GenericImageView, ImageFormat};
+use image::codecs::gif::GifDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::metadata::Orientation;
+use image::{
+    AnimationDecoder, DynamicImage, Frames, GenericImageView, ImageDecoder as _, ImageFormat,
+    ImageReader, Limits,
+};
+use std::io::Cursor;
 use std::path::Path;
 use std::time::Duration;
 use tokio::time::timeout;
 
 use crate::config::LimitsConfig;
 use crate::error::PipelineError;
+use crate::types::XmpData;
+
+use super::metadata::MetadataExtractor;
+use super::raw::{self, RawFormat};
 
 /// Image decoder with configurable limits and timeout.
 pub struct ImageDecoder {
     limits: LimitsConfig,
+    /// Frames to sample from animated GIF/WebP (1 = first frame only).
+    animation_frames: u32,
 }
 
 /// Result of decoding an image.
@@ -25,12 +38,74 @@ pub struct DecodedImage {
     pub height: u32,
     /// Original file size in bytes
     pub file_size: u64,
+    /// Camera RAW container the image was extracted from, if any.
+    /// When set, `image` holds the embedded JPEG preview.
+    pub raw_format: Option<RawFormat>,
+    /// Evenly spaced frames sampled from an animation (empty for still images).
+    /// `image` holds the first frame.
+    pub frames: Vec<DynamicImage>,
+    /// Total number of frames, for animations.
+    pub frame_count: Option<u32>,
+    /// Total playback duration of one loop in milliseconds, for animations.
+    pub duration_ms: Option<u64>,
+    /// Descriptive metadata from the file's XMP packet / IPTC block.
+    pub xmp: Option<XmpData>,
+}
+
+/// Frames sampled from an animated image.
+struct SampledAnimation {
+    frames: Vec<DynamicImage>,
+    frame_count: u32,
+    duration_ms: u64,
+}
+
+impl DecodedImage {
+    /// Format name for output: the RAW container type if present,
+    /// otherwise the detected image format.
+    pub fn format_name(&self) -> String {
+        match self.raw_format {
+            Some(raw) => raw.as_str().to_string(),
+            None => format_to_string(self.format),
+        }
+    }
+
+    /// Rotate/flip the pixels according to an EXIF orientation value (1-8).
+    ///
+    /// Updates `width`/`height` to the displayed dimensions. Values outside
+    /// 1-8 are ignored.
+    pub fn apply_orientation(&mut self, exif_orientation: u32) {
+        let Some(orientation) = u8::try_from(exif_orientation)
+            .ok()
+            .and_then(Orientation::from_exif)
+        else {
+            return;
+        };
+        if orientation == Orientation::NoTransforms {
+            return;
+        }
+        self.image.apply_orientation(orientation);
+        for frame in &mut self.frames {
+            frame.apply_orientation(orientation);
+        }
+        (self.width, self.height) = self.image.dimensions();
+    }
 }
 
 impl ImageDecoder {
     /// Create a new decoder with the given limits.
     pub fn new(limits: LimitsConfig) -> Self {
-        Self { limits }
+        Self {
+            limits,
+            animation_frames: 1,
+        }
+    }
+
+    /// Sample up to `frames` evenly spaced frames from animated GIF/WebP files.
+    ///
+    /// With 1 (the default) only the first frame is decoded.
+    pub fn with_animation_frames(mut self, frames: u32) -> Self {
+        self.animation_frames = frames.max(1);
+        self
     }
 
     /// Decode an image from an in-memory byte buffer with validation and timeout.
@@ -44,25 +119,24 @@ impl ImageDecoder {
     ) -> Result<DecodedImage, PipelineError> {
         let file_size = bytes.len() as u64;
         let path_owned = path.to_path_buf();
+        let limits = self.limits.clone();
+        let animation_frames = self.animation_frames;
         let timeout_duration = Duration::from_millis(self.limits.decode_timeout_ms);
 
         let decode_result = timeout(timeout_duration, async {
-            tokio::task::spawn_blocking(move || Self::decode_bytes_sync(bytes, &path_owned)).await
+            tokio::task::spawn_blocking(move || {
+                // Scanning the whole buffer for the XMP packet is not free on
+                // large files, so it runs here rather than on the async worker
+                let xmp = MetadataExtractor::extract_xmp_from_bytes(&bytes);
+                Self::decode_bytes_sync(bytes, &path_owned, &limits, animation_frames)
+                    .map(|decoded| DecodedImage { xmp, ..decoded })
+            })
+            .await
         })
         .await;
 
         match decode_result {
             Ok(Ok(Ok(mut decoded))) => {
-                if decoded.width > self.limits.max_image_dimension
-                    || decoded.height > self.limits.max_image_dimension
-                {
-                    return Err(PipelineError::ImageTooLarge {
-                        path: path.to_path_buf(),
-                        width: decoded.width,
-                        height: decoded.height,
-                        max_dim: self.limits.max_image_dimension,
-                    });
-                }
                 decoded.file_size = file_size;
                 Ok(decoded)
             }
@@ -80,12 +154,54 @@ impl ImageDecoder {
     }
 
     /// Synchronous decode from bytes (runs in spawn_blocking).
-    fn decode_bytes_sync(bytes: Vec<u8>, path: &Path) -> Result<DecodedImage, PipelineError> {
-        use std::io::Cursor;
-
+    ///
+    /// Dimensions are read from the header and checked against `limits`
+    /// before any pixel data is decoded.
+    fn decode_bytes_sync(
+        bytes: Vec<u8>,
+        path: &Path,
+        limits: &LimitsConfig,
+        animation_frames: u32,
+    ) -> Result<DecodedImage, PipelineError> {
         let file_size = bytes.len() as u64;
-        let cursor = Cursor::new(bytes);
-        let reader = image::ImageReader::new(cursor)
+
+        // Camera RAW: decode the embedded JPEG preview instead of the container
+        if let Some(raw_format) = raw::detect(&bytes, path) {
+            if let Some(preview) = raw::extract_preview(&bytes) {
+                return Self::decode_raw_preview(preview, raw_format, path, file_size, limits);
+            }
+            // No preview: it may still be a TIFF the regular decoder can read
+            tracing::debug!(
+                "No embedded preview in {:?}, decoding as a regular image",
+                path
+            );
+            return Self::decode_image(&bytes, path, limits, animation_frames, file_size).map_err(
+                |e| match e {
+                    PipelineError::Decode { path, message } => PipelineError::Decode {
+                        path,
+                        message: format!(
+                            "No embedded JPEG preview found in {} file ({message})",
+                            raw_format.as_str().to_uppercase()
+                        ),
+                    },
+                    other => other,
+                },
+            );
+        }
+
+        Self::decode_image(&bytes, path, limits, animation_frames, file_size)
+    }
+
+    /// Decode a buffer with the `image` crate, sampling animation frames when
+    /// `animation_frames` > 1.
+    fn decode_image(
+        bytes: &[u8],
+        path: &Path,
+        limits: &LimitsConfig,
+        animation_frames: u32,
+        file_size: u64,
+    ) -> Result<DecodedImage, PipelineError> {
+        let reader = ImageReader::new(Cursor::new(bytes))
             .with_guessed_format()
             .map_err(|e| PipelineError::Decode {
                 path: path.to_path_buf(),
@@ -102,11 +218,30 @@ impl ImageDecoder {
                     .to_string(),
             })?,
         };
-        let image = reader.decode().map_err(|e| PipelineError::Decode {
-            path: path.to_path_buf(),
-            message: e.to_string(),
-        })?;
 
+        if animation_frames > 1 && matches!(format, ImageFormat::Gif | ImageFormat::WebP) {
+            Self::check_header(bytes, format, path, limits)?;
+            if let Some(animation) =
+                Self::decode_animation(bytes, format, path, limits, animation_frames)?
+            {
+                let image = animation.frames[0].clone();
+                let (width, height) = image.dimensions();
+                return Ok(DecodedImage {
+                    image,
+                    format,
+                    width,
+                    height,
+                    file_size,
+                    raw_format: None,
+                    frames: animation.frames,
+                    frame_count: Some(animation.frame_count),
+                    duration_ms: Some(animation.duration_ms),
+                    xmp: None,
+                });
+            }
+        }
+
+        let image = Self::decode_checked(bytes, format, path, limits)?;
         let (width, height) = image.dimensions();
         Ok(DecodedImage {
             image,
@@ -114,8 +249,240 @@ impl ImageDecoder {
             width,
             height,
             file_size,
+            raw_format: None,
+            frames: Vec::new(),
+            frame_count: None,
+            duration_ms: None,
+            xmp: None,
         })
     }
+
+    /// Sample `max_frames` evenly spaced frames from an animated GIF or WebP.
+    ///
+    /// Decodes the animation once. The frame count isn't known up front, so
+    /// every `stride`-th frame is kept and the stride doubles (dropping every
+    /// other kept frame) whenever `2 * max_frames` are held; the final picks
+    /// are spread evenly over what is left. Returns `None` for single-frame
+    /// images.
+    fn decode_animation(
+        bytes: &[u8],
+        format: ImageFormat,
+        path: &Path,
+        limits: &LimitsConfig,
+        max_frames: u32,
+    ) -> Result<Option<SampledAnimation>, PipelineError> {
+        let decode_error = |e: image::ImageError| PipelineError::Decode {
+            path: path.to_path_buf(),
+            message: format!("Failed to decode animation frame: {e}"),
+        };
+
+        let Some(frames) = open_frames(bytes, format, limits).map_err(decode_error)? else {
+            return Ok(None);
+        };
+        let capacity = 2 * max_frames as usize;
+        let mut kept = Vec::with_capacity(capacity);
+        let mut stride: u32 = 1;
+        let mut frame_count: u32 = 0;
+        let mut duration_ms = 0.0;
+        for frame in frames {
+            let frame = frame.map_err(decode_error)?;
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            duration_ms += f64::from(numer) / f64::from(denom.max(1));
+            if frame_count.is_multiple_of(stride) {
+                kept.push(DynamicImage::ImageRgba8(frame.into_buffer()));
+                if kept.len() >= capacity {
+                    kept = kept.into_iter().step_by(2).collect();
+                    stride *= 2;
+                }
+            }
+            frame_count += 1;
+        }
+        if frame_count <= 1 {
+            return Ok(None);
+        }
+
+        let indices = sample_indices(kept.len() as u32, max_frames);
+        let mut wanted = indices.iter().peekable();
+        let mut sampled = Vec::with_capacity(indices.len());
+        for (index, frame) in kept.into_iter().enumerate() {
+            if wanted.next_if_eq(&&(index as u32)).is_some() {
+                sampled.push(frame);
+            }
+        }
+
+        tracing::trace!(
+            "  Animation: sampled {} of {} frames ({:.0}ms)",
+            sampled.len(),
+            frame_count,
+            duration_ms
+        );
+        Ok(Some(SampledAnimation {
+            frames: sampled,
+            frame_count,
+            duration_ms: duration_ms.round() as u64,
+        }))
+    }
+
+    /// Decode the embedded JPEG preview extracted from a camera RAW file.
+    fn decode_raw_preview(
+        preview: &[u8],
+        raw_format: RawFormat,
+        path: &Path,
+        file_size: u64,
+        limits: &LimitsConfig,
+    ) -> Result<DecodedImage, PipelineError> {
+        tracing::trace!(
+            "  RAW preview: {} bytes from {} container",
+            preview.len(),
+            raw_format.as_str()
+        );
+
+        let image = Self::decode_checked(preview, ImageFormat::Jpeg, path, limits).map_err(
+            |e| match e {
+                PipelineError::Decode { path, message } => PipelineError::Decode {
+                    path,
+                    message: format!("Failed to decode RAW preview: {message}"),
+                },
+                other => other,
+            },
+        )?;
+
+        let (width, height) = image.dimensions();
+        Ok(DecodedImage {
+            image,
+            format: ImageFormat::Jpeg,
+            width,
+            height,
+            file_size,
+            raw_format: Some(raw_format),
+            frames: Vec::new(),
+            frame_count: None,
+            duration_ms: None,
+            xmp: None,
+        })
+    }
+
+    /// Read the dimensions from the header and check them against the limits.
+    fn check_header(
+        bytes: &[u8],
+        format: ImageFormat,
+        path: &Path,
+        limits: &LimitsConfig,
+    ) -> Result<(u32, u32), PipelineError> {
+        let (width, height) = ImageReader::with_format(Cursor::new(bytes), format)
+            .into_dimensions()
+            .map_err(|e| PipelineError::Decode {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            })?;
+        check_dimensions(width, height, path, limits)?;
+        Ok((width, height))
+    }
+
+    /// Check header dimensions against the limits, then decode with
+    /// allocation capped by `image::Limits`.
+    fn decode_checked(
+        bytes: &[u8],
+        format: ImageFormat,
+        path: &Path,
+        limits: &LimitsConfig,
+    ) -> Result<DynamicImage, PipelineError> {
+        let decode_error = |e: image::ImageError| PipelineError::Decode {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        };
+
+        let (width, height) = Self::check_header(bytes, format, path, limits)?;
+
+        let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+        reader.limits(decode_limits(limits));
+        reader.decode().map_err(|e| match e {
+            // The header passed but the decoder still hit the allocation cap
+            image::ImageError::Limits(_) => too_large(width, height, path, limits),
+            other => decode_error(other),
+        })
+    }
+}
+
+/// Open a frame iterator for an animated GIF or WebP, with decoder limits applied.
+///
+/// Returns `None` for other formats and for still WebP images.
+fn open_frames<'a>(
+    bytes: &'a [u8],
+    format: ImageFormat,
+    limits: &LimitsConfig,
+) -> image::ImageResult<Option<Frames<'a>>> {
+    match format {
+        ImageFormat::Gif => {
+            let mut decoder = GifDecoder::new(Cursor::new(bytes))?;
+            decoder.set_limits(decode_limits(limits))?;
+            Ok(Some(decoder.into_frames()))
+        }
+        ImageFormat::WebP => {
+            let mut decoder = WebPDecoder::new(Cursor::new(bytes))?;
+            if !decoder.has_animation() {
+                return Ok(None);
+            }
+            decoder.set_limits(decode_limits(limits))?;
+            Ok(Some(decoder.into_frames()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Indices of up to `max_frames` evenly spaced frames out of `frame_count`,
+/// always starting at frame 0.
+fn sample_indices(frame_count: u32, max_frames: u32) -> Vec<u32> {
+    if frame_count <= max_frames {
+        return (0..frame_count).collect();
+    }
+    (0..max_frames)
+        .map(|i| (u64::from(i) * u64::from(frame_count) / u64::from(max_frames)) as u32)
+        .collect()
+}
+
+/// Bytes per pixel assumed when capping decoder allocations (16-bit RGBA).
+const MAX_BYTES_PER_PIXEL: u64 = 8;
+
+/// Reject images whose header dimensions exceed the configured limits.
+fn check_dimensions(
+    width: u32,
+    height: u32,
+    path: &Path,
+    limits: &LimitsConfig,
+) -> Result<(), PipelineError> {
+    let pixels = u64::from(width) * u64::from(height);
+    if width > limits.max_image_dimension
+        || height > limits.max_image_dimension
+        || pixels > max_pixels(limits)
+    {
+        return Err(too_large(width, height, path, limits));
+    }
+    Ok(())
+}
+
+fn too_large(width: u32, height: u32, path: &Path, limits: &LimitsConfig) -> PipelineError {
+    PipelineError::ImageTooLarge {
+        path: path.to_path_buf(),
+        width,
+        height,
+        max_dim: limits.max_image_dimension,
+        max_megapixels: limits.max_megapixels,
+    }
+}
+
+fn max_pixels(limits: &LimitsConfig) -> u64 {
+    u64::from(limits.max_megapixels) * 1_000_000
+}
+
+/// Decoder limits derived from the config: per-side dimension caps and an
+/// allocation budget sized for the megapixel limit.
+fn decode_limits(limits: &LimitsConfig) -> Limits {
+    let mut decode_limits = Limits::default();
+    decode_limits.max_image_width = Some(limits.max_image_dimension);
+    decode_limits.max_image_height = Some(limits.max_image_dimension);
+    decode_limits.max_alloc = Some(max_pixels(limits) * MAX_BYTES_PER_PIXEL);
+    decode_limits
 }
 
 /// Convert an ImageFormat to a string representation.
@@ -155,7 +522,312 @@ mod tests {
         std::fs::copy(&fixture, &misnamed).unwrap();
 
         let bytes = std::fs::read(&misnamed).unwrap();
-        let result = ImageDecoder::decode_bytes_sync(bytes, &misnamed).unwrap();
+        let result =
+            ImageDecoder::decode_bytes_sync(bytes, &misnamed, &LimitsConfig::default(), 1).unwrap();
         assert_eq!(result.format, ImageFormat::Png);
+        assert!(result.raw_format.is_none());
+        assert_eq!(result.format_name(), "png");
+    }
+
+    fn decoded(width: u32, height: u32) -> DecodedImage {
+        DecodedImage {
+            image: DynamicImage::new_rgb8(width, height),
+            format: ImageFormat::Jpeg,
+            width,
+            height,
+            file_size: 0,
+            raw_format: None,
+            frames: Vec::new(),
+            frame_count: None,
+            duration_ms: None,
+            xmp: None,
+        }
+    }
+
+    #[test]
+    fn test_apply_orientation_rotations_swap_dimensions() {
+        for orientation in [5, 6, 7, 8] {
+            let mut img = decoded(40, 30);
+            img.apply_orientation(orientation);
+            assert_eq!(
+                (img.width, img.height),
+                (30, 40),
+                "orientation {orientation}"
+            );
+            assert_eq!(img.image.dimensions(), (30, 40));
+        }
+        for orientation in [1, 2, 3, 4] {
+            let mut img = decoded(40, 30);
+            img.apply_orientation(orientation);
+            assert_eq!(
+                (img.width, img.height),
+                (40, 30),
+                "orientation {orientation}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply_orientation_moves_pixels() {
+        // Mark the top-left pixel, rotate 90° CW (orientation 6) → it lands top-right
+        let mut img = decoded(4, 2);
+        if let DynamicImage::ImageRgb8(buf) = &mut img.image {
+            buf.put_pixel(0, 0, image::Rgb([255, 0, 0]));
+        }
+        img.apply_orientation(6);
+        let rgb = img.image.to_rgb8();
+        assert_eq!(rgb.get_pixel(1, 0), &image::Rgb([255, 0, 0]));
+    }
+
+    #[test]
+    fn test_apply_orientation_ignores_invalid_values() {
+        let mut img = decoded(40, 30);
+        img.apply_orientation(0);
+        img.apply_orientation(9);
+        assert_eq!((img.width, img.height), (40, 30));
+    }
+
+    /// A bare 24-bit BMP header claiming the given dimensions, with no pixel data.
+    fn bmp_header(width: i32, height: i32) -> Vec<u8> {
+        let mut bytes = b"BM".to_vec();
+        bytes.extend_from_slice(&54u32.to_le_bytes()); // file size
+        bytes.extend_from_slice(&[0; 4]); // reserved
+        bytes.extend_from_slice(&54u32.to_le_bytes()); // pixel data offset
+        bytes.extend_from_slice(&40u32.to_le_bytes()); // DIB header size
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // planes
+        bytes.extend_from_slice(&24u16.to_le_bytes()); // bits per pixel
+        bytes.extend_from_slice(&[0; 24]); // compression, sizes, palette
+        bytes
+    }
+
+    #[test]
+    fn test_bomb_rejected_from_header() {
+        // 60000x60000 would need ~10 GB to decode; must fail before allocating
+        let bytes = bmp_header(60000, 60000);
+        let limits = LimitsConfig {
+            max_image_dimension: 100_000,
+            ..LimitsConfig::default()
+        };
+        match ImageDecoder::decode_bytes_sync(bytes, Path::new("bomb.bmp"), &limits, 1) {
+            Err(PipelineError::ImageTooLarge {
+                width,
+                height,
+                max_megapixels,
+                ..
+            }) => {
+                assert_eq!((width, height), (60000, 60000));
+                assert_eq!(max_megapixels, limits.max_megapixels);
+            }
+            Err(other) => panic!("expected ImageTooLarge, got: {other}"),
+            Ok(_) => panic!("expected ImageTooLarge, got a decoded image"),
+        }
+    }
+
+    #[test]
+    fn test_megapixel_limit_applies_within_dimension_limit() {
+        let bytes = bmp_header(2000, 1000);
+        let limits = LimitsConfig {
+            max_megapixels: 1,
+            ..LimitsConfig::default()
+        };
+        let err = ImageDecoder::decode_bytes_sync(bytes, Path::new("wide.bmp"), &limits, 1)
+            .err()
+            .unwrap();
+        assert!(matches!(err, PipelineError::ImageTooLarge { .. }));
+    }
+
+    #[test]
+    fn test_decode_limits_follow_config() {
+        let limits = LimitsConfig {
+            max_image_dimension: 4000,
+            max_megapixels: 12,
+            ..LimitsConfig::default()
+        };
+        let decode_limits = decode_limits(&limits);
+        assert_eq!(decode_limits.max_image_width, Some(4000));
+        assert_eq!(decode_limits.max_image_height, Some(4000));
+        assert_eq!(
+            decode_limits.max_alloc,
+            Some(12_000_000 * MAX_BYTES_PER_PIXEL)
+        );
+    }
+
+    /// Encode an animated GIF with one solid-colour frame per entry, 100ms each.
+    fn animated_gif(colors: &[[u8; 4]]) -> Vec<u8> {
+        use image::codecs::gif::GifEncoder;
+        use image::{Delay, Frame, RgbaImage};
+
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut bytes);
+            let frames = colors.iter().map(|&c| {
+                Frame::from_parts(
+                    RgbaImage::from_pixel(8, 6, image::Rgba(c)),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(100, 1),
+                )
+            });
+            encoder.encode_frames(frames).unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_sample_indices_evenly_spaced() {
+        assert_eq!(sample_indices(3, 8), vec![0, 1, 2]);
+        assert_eq!(sample_indices(10, 4), vec![0, 2, 5, 7]);
+        assert_eq!(sample_indices(100, 1), vec![0]);
+    }
+
+    #[test]
+    fn test_animated_gif_samples_frames() {
+        let bytes = animated_gif(&[
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+            [255, 255, 255, 255],
+        ]);
+        let decoded = ImageDecoder::decode_bytes_sync(
+            bytes,
+            Path::new("anim.gif"),
+            &LimitsConfig::default(),
+            2,
+        )
+        .unwrap();
+
+        assert_eq!(decoded.frame_count, Some(4));
+        assert_eq!(decoded.duration_ms, Some(400));
+        assert_eq!(decoded.frames.len(), 2);
+        assert_eq!((decoded.width, decoded.height), (8, 6));
+        // Frames 0 and 2 are sampled; `image` is the first frame
+        let first = decoded.frames[0].to_rgb8();
+        let second = decoded.frames[1].to_rgb8();
+        assert_eq!(first.get_pixel(0, 0), &image::Rgb([255, 0, 0]));
+        assert_eq!(second.get_pixel(0, 0), &image::Rgb([0, 0, 255]));
+        assert_eq!(
+            decoded.image.to_rgb8().get_pixel(0, 0),
+            &image::Rgb([255, 0, 0])
+        );
+    }
+
+    #[test]
+    fn test_long_animation_sampled_in_one_pass() {
+        let colors: Vec<[u8; 4]> = (0..10).map(|i| [i * 20, 0, 0, 255]).collect();
+        let decoded = ImageDecoder::decode_bytes_sync(
+            animated_gif(&colors),
+            Path::new("anim.gif"),
+            &LimitsConfig::default(),
+            3,
+        )
+        .unwrap();
+
+        assert_eq!(decoded.frame_count, Some(10));
+        assert_eq!(decoded.duration_ms, Some(1000));
+        let sampled: Vec<u8> = decoded
+            .frames
+            .iter()
+            .map(|frame| frame.to_rgb8().get_pixel(0, 0)[0] / 20)
+            .collect();
+        assert_eq!(sampled, vec![0, 2, 6]);
+    }
+
+    #[test]
+    fn test_animation_sampling_disabled_decodes_first_frame() {
+        let bytes = animated_gif(&[[255, 0, 0, 255], [0, 255, 0, 255]]);
+        let decoded = ImageDecoder::decode_bytes_sync(
+            bytes,
+            Path::new("anim.gif"),
+            &LimitsConfig::default(),
+            1,
+        )
+        .unwrap();
+        assert!(decoded.frames.is_empty());
+        assert_eq!(decoded.frame_count, None);
+    }
+
+    #[test]
+    fn test_single_frame_gif_is_still_image() {
+        let bytes = animated_gif(&[[255, 0, 0, 255]]);
+        let decoded = ImageDecoder::decode_bytes_sync(
+            bytes,
+            Path::new("still.gif"),
+            &LimitsConfig::default(),
+            8,
+        )
+        .unwrap();
+        assert!(decoded.frames.is_empty());
+        assert_eq!(decoded.duration_ms, None);
+    }
+
+    #[test]
+    fn test_raw_without_preview_is_decode_error() {
+        // Bare TIFF header with an empty IFD0 and a .nef extension
+        let bytes = b"II\x2A\x00\x08\x00\x00\x00\x00\x00\x00\x00\x00\x00".to_vec();
+        let err = ImageDecoder::decode_bytes_sync(
+            bytes,
+            Path::new("empty.nef"),
+            &LimitsConfig::default(),
+            1,
+        )
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("No embedded JPEG preview"));
+    }
+
+    /// Uncompressed 8-bit grayscale TIFF carrying a camera `Make` tag, as
+    /// written by scanners and editors exporting camera files.
+    fn tiff_with_make(make: &str, width: u32, height: u32) -> Vec<u8> {
+        let make = format!("{make}\0");
+        let tags: [(u16, u16, u32, u32); 9] = [
+            (0x0100, 4, 1, width),             // ImageWidth
+            (0x0101, 4, 1, height),            // ImageLength
+            (0x0102, 3, 1, 8),                 // BitsPerSample
+            (0x0103, 3, 1, 1),                 // Compression: none
+            (0x0106, 3, 1, 1),                 // Photometric: black is zero
+            (0x010F, 2, make.len() as u32, 0), // Make (offset patched below)
+            (0x0111, 4, 1, 0),                 // StripOffsets (patched below)
+            (0x0116, 4, 1, height),            // RowsPerStrip
+            (0x0117, 4, 1, width * height),    // StripByteCounts
+        ];
+        let data_off = 8 + 2 + tags.len() as u32 * 12 + 4;
+        let pixels_off = data_off + make.len() as u32;
+
+        let mut out = b"II\x2A\x00\x08\x00\x00\x00".to_vec();
+        out.extend_from_slice(&(tags.len() as u16).to_le_bytes());
+        for (tag, ty, count, value) in tags {
+            let value = match tag {
+                0x010F => data_off,
+                0x0111 => pixels_off,
+                _ => value,
+            };
+            out.extend_from_slice(&tag.to_le_bytes());
+            out.extend_from_slice(&ty.to_le_bytes());
+            out.extend_from_slice(&count.to_le_bytes());
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(make.as_bytes());
+        out.resize(out.len() + (width * height) as usize, 0x80);
+        out
+    }
+
+    #[test]
+    fn test_tiff_with_camera_make_decodes_at_full_size() {
+        let bytes = tiff_with_make("NIKON CORPORATION", 40, 30);
+        for name in ["export.tif", "export"] {
+            let decoded = ImageDecoder::decode_bytes_sync(
+                bytes.clone(),
+                Path::new(name),
+                &LimitsConfig::default(),
+                1,
+            )
+            .unwrap();
+            assert_eq!((decoded.width, decoded.height), (40, 30), "{name}");
+            assert_eq!(decoded.format, ImageFormat::Tiff);
+            assert!(decoded.raw_format.is_none());
+        }
     }
 }
@@ -1,54 +1,150 @@
 //! File discovery for finding images in directories.
 
-use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
 
 use crate::config::ProcessingConfig;
 
+use super::archive::{self, ArchiveEntry, ArchiveKind};
+
+/// Name of the gitignore-style files honoured during directory walks.
+pub const IGNORE_FILE_NAME: &str = ".photonignore";
+
 /// Discovers image files in directories.
+#[derive(Clone)]
 pub struct FileDiscovery {
     config: ProcessingConfig,
+    /// Compiled `processing.include` globs (`None` when the list is empty)
+    include: Option<Gitignore>,
+    /// Compiled `processing.exclude` globs
+    exclude: Gitignore,
+    /// Modification-time and size filters
+    filters: DiscoveryFilters,
+}
+
+/// Attribute filters applied to every discovered file, including files
+/// named explicitly. Checked from filesystem metadata, so dropped files are
+/// never opened.
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryFilters {
+    /// Keep only files modified at or after this time
+    pub modified_since: Option<SystemTime>,
+    /// Keep only files of at least this many bytes
+    pub min_size: Option<u64>,
+    /// Keep only files of at most this many bytes
+    pub max_size: Option<u64>,
+}
+
+/// How many files each [`DiscoveryFilters`] check dropped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FilterCounts {
+    /// Files last modified before `modified_since` (an unmodified archive
+    /// counts once, since its entries are not listed)
+    pub not_modified: u64,
+    /// Files smaller than `min_size`
+    pub too_small: u64,
+    /// Files larger than `max_size`
+    pub too_large: u64,
 }
 
 /// Information about a discovered file.
 #[derive(Debug, Clone)]
 pub struct DiscoveredFile {
-    /// Full path to the file
+    /// Full path to the file, or an `archive.zip!/inner/path.jpg` locator
+    /// for archive entries
     pub path: PathBuf,
-    /// File size in bytes
+    /// File size in bytes (uncompressed size for archive entries)
     pub size: u64,
+    /// Last modification time (the archive's for archive entries), if the
+    /// platform reports one
+    pub modified: Option<SystemTime>,
+    /// Set when the file lives inside a zip or tar archive
+    pub archive_entry: Option<ArchiveEntry>,
 }
 
 impl FileDiscovery {
     /// Create a new file discovery instance.
+    ///
+    /// Invalid include/exclude globs are logged and skipped; config
+    /// validation rejects them before this point for file-based config.
     pub fn new(config: ProcessingConfig) -> Self {
-        Self { config }
+        let include = (!config.include.is_empty()).then(|| build_globs(&config.include));
+        let exclude = build_globs(&config.exclude);
+        Self {
+            config,
+            include,
+            exclude,
+            filters: DiscoveryFilters::default(),
+        }
+    }
+
+    /// Drop files by modification time and size (see [`DiscoveryFilters`]).
+    pub fn with_filters(mut self, filters: DiscoveryFilters) -> Self {
+        self.filters = filters;
+        self
     }
 
     /// Discover all supported image files at a path.
     ///
-    /// If path is a file, returns it if supported.
-    /// If path is a directory, recursively finds all supported files.
+    /// If path is a file, returns it if supported and it passes the
+    /// [`DiscoveryFilters`] (the globs and hidden check don't apply to an
+    /// explicitly named file).
+    /// If path is a directory, recursively finds all supported files, up to
+    /// `processing.max_depth` levels deep, skipping hidden entries (when
+    /// `include_hidden` is off), anything matched by `exclude` or a `.photonignore`
+    /// file, and files not matched by `include` (when set). Globs use
+    /// gitignore syntax relative to `path`.
+    /// Zip and tar(.gz) archives are treated as directories and their
+    /// supported entries are listed (unless `processing.archives` is off);
+    /// the same filters apply to the paths inside the archive.
     pub fn discover(&self, path: &Path) -> Vec<DiscoveredFile> {
+        self.discover_counted(path).0
+    }
+
+    /// Like [`discover`](Self::discover), also reporting how many files the
+    /// [`DiscoveryFilters`] dropped.
+    pub fn discover_counted(&self, path: &Path) -> (Vec<DiscoveredFile>, FilterCounts) {
+        let mut counts = FilterCounts::default();
+
         if path.is_file() {
-            if self.is_supported(path) {
+            let mut files = vec![];
+            if let Some(kind) = self.archive_kind(path) {
+                files = self.discover_archive(path, kind, &mut counts);
+            } else if self.is_supported(path) {
                 if let Ok(meta) = std::fs::metadata(path) {
-                    return vec![DiscoveredFile {
-                        path: path.to_path_buf(),
-                        size: meta.len(),
-                    }];
+                    files.extend(self.keep_file(path, &meta, &mut counts));
                 }
             }
-            return vec![];
+            return (files, counts);
         }
 
         let mut files = Vec::new();
 
-        for entry in WalkDir::new(path)
-            .follow_links(true)
-            .max_depth(256)
-            .into_iter()
-        {
+        // Excluded directories are pruned here so they are never descended into
+        let root = path.to_path_buf();
+        let exclude = self.exclude.clone();
+        let walker = WalkBuilder::new(path)
+            .standard_filters(false)
+            .hidden(!self.config.include_hidden)
+            .parents(false)
+            .add_custom_ignore_filename(IGNORE_FILE_NAME)
+            .follow_links(self.config.follow_links)
+            .max_depth(Some(self.config.max_depth))
+            .filter_entry(move |entry| {
+                if entry.depth() == 0 {
+                    return true;
+                }
+                let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                !exclude
+                    .matched(relative_to(&root, entry.path()), is_dir)
+                    .is_ignore()
+            })
+            .build();
+
+        for entry in walker {
             let entry = match entry {
                 Ok(e) => e,
                 Err(e) => {
@@ -56,20 +152,131 @@ impl FileDiscovery {
                     continue;
                 }
             };
+            if let Some(e) = entry.error() {
+                tracing::warn!("Problem reading {IGNORE_FILE_NAME}: {e}");
+            }
             let entry_path = entry.path();
-            if entry_path.is_file() && self.is_supported(entry_path) {
+            if !entry_path.is_file() || !self.is_included(relative_to(path, entry_path)) {
+                continue;
+            }
+            if let Some(kind) = self.archive_kind(entry_path) {
+                files.extend(self.discover_archive(entry_path, kind, &mut counts));
+            } else if self.is_supported(entry_path) {
                 if let Ok(meta) = entry.metadata() {
-                    files.push(DiscoveredFile {
-                        path: entry_path.to_path_buf(),
-                        size: meta.len(),
-                    });
+                    files.extend(self.keep_file(entry_path, &meta, &mut counts));
                 }
             }
         }
 
-        // Sort by path for deterministic ordering
-        files.sort_by(|a, b| a.path.cmp(&b.path));
-        files
+        // Sort by path for deterministic ordering. Archive entries sort by
+        // their archive and stay in archive order, so tar streams are read
+        // front to back.
+        files.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+        (files, counts)
+    }
+
+    /// A plain file as a `DiscoveredFile`, unless the filters drop it.
+    fn keep_file(
+        &self,
+        path: &Path,
+        meta: &std::fs::Metadata,
+        counts: &mut FilterCounts,
+    ) -> Option<DiscoveredFile> {
+        let modified = meta.modified().ok();
+        self.filters
+            .keep(meta.len(), modified, counts)
+            .then(|| DiscoveredFile {
+                path: path.to_path_buf(),
+                size: meta.len(),
+                modified,
+                archive_entry: None,
+            })
+    }
+
+    /// List the supported entries of an archive.
+    ///
+    /// An archive last modified before `modified_since` is skipped without
+    /// listing its entries.
+    fn discover_archive(
+        &self,
+        path: &Path,
+        kind: ArchiveKind,
+        counts: &mut FilterCounts,
+    ) -> Vec<DiscoveredFile> {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if !self.filters.is_modified(modified) {
+            counts.not_modified += 1;
+            return vec![];
+        }
+
+        let keep = |name: &Path| self.is_supported(name) && self.keeps_archive_entry(name);
+        match archive::list_entries(path, kind, keep) {
+            Ok(entries) => entries
+                .into_iter()
+                .filter(|entry| self.filters.keep(entry.size, modified, counts))
+                .map(|entry| DiscoveredFile {
+                    path: entry.locator(),
+                    size: entry.size,
+                    modified,
+                    archive_entry: Some(entry),
+                })
+                .collect(),
+            Err(e) => {
+                tracing::warn!("Skipping unreadable archive {:?}: {e}", path);
+                vec![]
+            }
+        }
+    }
+
+    /// Archive type of a path, if archive expansion is enabled.
+    fn archive_kind(&self, path: &Path) -> Option<ArchiveKind> {
+        if !self.config.archives {
+            return None;
+        }
+        ArchiveKind::from_path(path)
+    }
+
+    /// Whether a file passes the `include` globs (always true when unset).
+    ///
+    /// Archives are only checked against `include` entry by entry, so
+    /// `include = ["*.jpg"]` still finds the JPEGs inside `photos.zip`.
+    fn is_included(&self, relative: &Path) -> bool {
+        let Some(include) = &self.include else {
+            return true;
+        };
+        self.archive_kind(relative).is_some()
+            || include
+                .matched_path_or_any_parents(relative, false)
+                .is_ignore()
+    }
+
+    /// Whether a path inside an archive passes the hidden, exclude and
+    /// include filters.
+    ///
+    /// Entry names come from the archive as stored, so root, `.` and `..`
+    /// components are dropped first: the matcher requires a relative path.
+    fn keeps_archive_entry(&self, name: &Path) -> bool {
+        let name: PathBuf = name
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .collect();
+        let name = name.as_path();
+        let hidden = name
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+        if hidden && !self.config.include_hidden {
+            return false;
+        }
+        if self
+            .exclude
+            .matched_path_or_any_parents(name, false)
+            .is_ignore()
+        {
+            return false;
+        }
+        self.include
+            .as_ref()
+            .is_none_or(|include| include.matched_path_or_any_parents(name, false).is_ignore())
     }
 
     /// Check if a file has a supported extension.
@@ -92,6 +299,85 @@ impl FileDiscovery {
     }
 }
 
+/// Compile gitignore-style globs, logging and skipping invalid ones.
+fn build_globs(patterns: &[String]) -> Gitignore {
+    let mut builder = GitignoreBuilder::new("");
+    for pattern in patterns {
+        if let Err(e) = builder.add_line(None, pattern) {
+            tracing::warn!("Ignoring invalid glob {pattern:?}: {e}");
+        }
+    }
+    builder.build().unwrap_or_else(|e| {
+        tracing::warn!("Ignoring discovery globs: {e}");
+        Gitignore::empty()
+    })
+}
+
+/// Check that a discovery glob compiles (used by config validation).
+pub(crate) fn check_glob(pattern: &str) -> Result<(), String> {
+    GitignoreBuilder::new("")
+        .add_line(None, pattern)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// `path` relative to the walk root (globs are matched against this).
+fn relative_to<'a>(root: &Path, path: &'a Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+impl DiscoveryFilters {
+    /// Check a file against the filters, counting it if dropped.
+    fn keep(&self, size: u64, modified: Option<SystemTime>, counts: &mut FilterCounts) -> bool {
+        if !self.is_modified(modified) {
+            counts.not_modified += 1;
+            false
+        } else if self.min_size.is_some_and(|min| size < min) {
+            counts.too_small += 1;
+            false
+        } else if self.max_size.is_some_and(|max| size > max) {
+            counts.too_large += 1;
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Whether a modification time passes `modified_since`. Files without
+    /// one are kept so they are never silently dropped.
+    fn is_modified(&self, modified: Option<SystemTime>) -> bool {
+        match (self.modified_since, modified) {
+            (Some(since), Some(modified)) => modified >= since,
+            _ => true,
+        }
+    }
+}
+
+impl FilterCounts {
+    /// Total number of files dropped.
+    pub fn total(&self) -> u64 {
+        self.not_modified + self.too_small + self.too_large
+    }
+}
+
+impl std::ops::AddAssign for FilterCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.not_modified += other.not_modified;
+        self.too_small += other.too_small;
+        self.too_large += other.too_large;
+    }
+}
+
+impl DiscoveredFile {
+    /// Ordering key: the container path, then the position inside it.
+    fn sort_key(&self) -> (&Path, usize) {
+        match &self.archive_entry {
+            Some(entry) => (entry.archive.as_path(), entry.index),
+            None => (self.path.as_path(), 0),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,8 +392,13 @@ mod tests {
         assert!(discovery.is_supported(Path::new("test.jpeg")));
         assert!(discovery.is_supported(Path::new("test.png")));
         assert!(discovery.is_supported(Path::new("test.webp")));
+        assert!(discovery.is_supported(Path::new("test.CR2")));
         assert!(!discovery.is_supported(Path::new("test.txt")));
         assert!(!discovery.is_supported(Path::new("test.pdf")));
+
+        // No decode path, so never picked up by default
+        assert!(!discovery.is_supported(Path::new("test.heic")));
+        assert!(!discovery.is_supported(Path::new("test.raw")));
     }
 
     #[cfg(unix)]
@@ -144,16 +435,270 @@ mod tests {
         assert!(files[0].path.ends_with("a.jpg"));
     }
 
+    /// Create `rel` under `root` (with parent directories) containing `contents`.
+    fn touch(root: &Path, rel: &str, contents: &[u8]) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    /// Discovered paths relative to `root`, as `/`-separated strings.
+    fn relative_paths(root: &Path, files: &[DiscoveredFile]) -> Vec<String> {
+        files
+            .iter()
+            .map(|f| {
+                f.path
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_discover_hidden_toggle() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "a.jpg", b"x");
+        touch(root, "._a.jpg", b"x");
+        touch(root, ".thumbnails/b.jpg", b"x");
+
+        // Hidden files are processed by default, as before the toggle existed
+        let discovery = FileDiscovery::new(ProcessingConfig::default());
+        assert_eq!(
+            relative_paths(root, &discovery.discover(root)),
+            ["._a.jpg", ".thumbnails/b.jpg", "a.jpg"]
+        );
+
+        let discovery = FileDiscovery::new(ProcessingConfig {
+            include_hidden: false,
+            ..ProcessingConfig::default()
+        });
+        assert_eq!(relative_paths(root, &discovery.discover(root)), ["a.jpg"]);
+    }
+
+    #[test]
+    fn test_discover_include_and_exclude_globs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "2024/a.jpg", b"x");
+        touch(root, "2024/b.png", b"x");
+        touch(root, "2024/@eaDir/a.jpg", b"x");
+        touch(root, "2023/c.jpg", b"x");
+
+        let discovery = FileDiscovery::new(ProcessingConfig {
+            include: vec!["*.jpg".into()],
+            exclude: vec!["@eaDir".into(), "/2023".into()],
+            ..ProcessingConfig::default()
+        });
+        assert_eq!(
+            relative_paths(root, &discovery.discover(root)),
+            ["2024/a.jpg"]
+        );
+    }
+
+    #[test]
+    fn test_discover_respects_max_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "a.jpg", b"x");
+        touch(root, "one/b.jpg", b"x");
+        touch(root, "one/two/c.jpg", b"x");
+
+        let discovery = FileDiscovery::new(ProcessingConfig {
+            max_depth: 2,
+            ..ProcessingConfig::default()
+        });
+        assert_eq!(
+            relative_paths(root, &discovery.discover(root)),
+            ["a.jpg", "one/b.jpg"]
+        );
+    }
+
+    #[test]
+    fn test_discover_honours_nested_photonignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, IGNORE_FILE_NAME, b"drafts/\n*.png\n");
+        touch(root, "a.jpg", b"x");
+        touch(root, "a.png", b"x");
+        touch(root, "drafts/b.jpg", b"x");
+        touch(root, "trip/c.jpg", b"x");
+        touch(root, "trip/d.jpg", b"x");
+        touch(root, "trip/keep.png", b"x");
+        touch(
+            root,
+            &format!("trip/{IGNORE_FILE_NAME}"),
+            b"d.jpg\n!keep.png\n",
+        );
+
+        let discovery = FileDiscovery::new(ProcessingConfig::default());
+        assert_eq!(
+            relative_paths(root, &discovery.discover(root)),
+            ["a.jpg", "trip/c.jpg", "trip/keep.png"]
+        );
+    }
+
+    #[test]
+    fn test_discover_filters_archive_entries() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let zip_path = dir.path().join("set.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&zip_path).unwrap());
+        for name in ["a.jpg", "__MACOSX/._a.jpg", "raw/b.jpg", "c.png"] {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(b"x").unwrap();
+        }
+        zip.finish().unwrap();
+
+        let discovery = FileDiscovery::new(ProcessingConfig {
+            include: vec!["*.jpg".into()],
+            exclude: vec!["raw/".into()],
+            include_hidden: false,
+            ..ProcessingConfig::default()
+        });
+        let names: Vec<_> = discovery
+            .discover(dir.path())
+            .into_iter()
+            .map(|f| f.archive_entry.unwrap().name)
+            .collect();
+        assert_eq!(names, ["a.jpg"]);
+    }
+
+    #[test]
+    fn test_discover_rooted_archive_entry_names() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let zip_path = dir.path().join("set.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&zip_path).unwrap());
+        for name in ["//abs/x.jpg", "/raw/../y.jpg", "./z.png"] {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(b"x").unwrap();
+        }
+        zip.finish().unwrap();
+
+        let tar_path = dir.path().join("set.tar");
+        let mut tar = tar::Builder::new(std::fs::File::create(&tar_path).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(1);
+        header.set_mode(0o644);
+        header.set_entry_type(tar::EntryType::Regular);
+        // `append_data` refuses rooted names, so write the raw header field
+        header.as_old_mut().name[..11].copy_from_slice(b"//abs/w.jpg");
+        header.set_cksum();
+        tar.append(&header, &b"x"[..]).unwrap();
+        tar.finish().unwrap();
+
+        // Any glob sends entry names through the gitignore matcher
+        let discovery = FileDiscovery::new(ProcessingConfig {
+            include: vec!["*.jpg".into()],
+            exclude: vec!["raw/".into()],
+            include_hidden: false,
+            ..ProcessingConfig::default()
+        });
+        let mut names: Vec<_> = discovery
+            .discover(dir.path())
+            .into_iter()
+            .map(|f| f.archive_entry.unwrap().name)
+            .collect();
+        names.sort();
+        assert_eq!(names, ["//abs/w.jpg", "//abs/x.jpg"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_discover_follow_links_toggle() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        touch(outside.path(), "linked.jpg", b"x");
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+
+        let discovery = FileDiscovery::new(ProcessingConfig::default());
+        assert_eq!(discovery.discover(dir.path()).len(), 1);
+
+        let discovery = FileDiscovery::new(ProcessingConfig {
+            follow_links: false,
+            ..ProcessingConfig::default()
+        });
+        assert!(discovery.discover(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_discover_size_filters_are_counted() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "small.jpg", &[0; 10]);
+        touch(root, "medium.jpg", &[0; 100]);
+        touch(root, "large.jpg", &[0; 1000]);
+
+        let discovery =
+            FileDiscovery::new(ProcessingConfig::default()).with_filters(DiscoveryFilters {
+                min_size: Some(50),
+                max_size: Some(500),
+                ..DiscoveryFilters::default()
+            });
+        let (files, counts) = discovery.discover_counted(root);
+        assert_eq!(relative_paths(root, &files), ["medium.jpg"]);
+        assert_eq!(
+            counts,
+            FilterCounts {
+                not_modified: 0,
+                too_small: 1,
+                too_large: 1,
+            }
+        );
+        assert!(files[0].modified.is_some());
+    }
+
+    #[test]
+    fn test_discover_modified_since() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "old.jpg", b"x");
+        touch(root, "new.jpg", b"x");
+        let an_hour_ago = SystemTime::now() - std::time::Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(root.join("old.jpg"))
+            .unwrap()
+            .set_modified(an_hour_ago - std::time::Duration::from_secs(60))
+            .unwrap();
+
+        let discovery =
+            FileDiscovery::new(ProcessingConfig::default()).with_filters(DiscoveryFilters {
+                modified_since: Some(an_hour_ago),
+                ..DiscoveryFilters::default()
+            });
+        let (files, counts) = discovery.discover_counted(root);
+        assert_eq!(relative_paths(root, &files), ["new.jpg"]);
+        assert_eq!(counts.not_modified, 1);
+        assert_eq!(counts.total(), 1);
+
+        // Explicitly named files are filtered too
+        let (files, counts) = discovery.discover_counted(&root.join("old.jpg"));
+        assert!(files.is_empty());
+        assert_eq!(counts.not_modified, 1);
+    }
+
     #[test]
     fn test_total_size() {
         let files = vec![
             DiscoveredFile {
                 path: PathBuf::from("a.jpg"),
                 size: 100,
+                modified: None,
+                archive_entry: None,
             },
             DiscoveredFile {
                 path: PathBuf::from("b.jpg"),
                 size: 200,
+                modified: None,
+                archive_entry: None,
             },
         ];
 
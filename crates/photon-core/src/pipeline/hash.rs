@@ -3,16 +3,35 @@
 use blake3::Hasher as Blake3Hasher;
 use image::DynamicImage;
 use image_hasher::{HashAlg, HasherConfig, ImageHash};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{BufReader, Read};
 use std::path::Path;
 
+use crate::config::{HashingConfig, PerceptualHashConfig};
+
+/// Perceptual hash algorithm names accepted in `hashing.perceptual`.
+///
+/// `phash` is the DCT-based hash (mean of the low-frequency DCT block),
+/// `dhash` the row gradient, `ahash` the mean, `blockhash` Yang et al.'s
+/// block-mean hash; the rest are further `image_hasher` variants.
+pub const PERCEPTUAL_ALGORITHMS: &[&str] = &[
+    "ahash",
+    "dhash",
+    "phash",
+    "blockhash",
+    "median",
+    "vert_gradient",
+    "double_gradient",
+];
+
 /// Provides content hashing and perceptual hashing for images.
 ///
-/// The perceptual hasher is pre-configured and cached to avoid
+/// The perceptual hashers are pre-configured and cached to avoid
 /// re-allocating the same `HasherConfig` for every image.
 pub struct Hasher {
-    phash_hasher: image_hasher::Hasher,
+    /// One hasher per configured algorithm, in config order.
+    phash_hashers: Vec<(String, image_hasher::Hasher)>,
 }
 
 impl Default for Hasher {
@@ -22,13 +41,34 @@ impl Default for Hasher {
 }
 
 impl Hasher {
-    /// Create a new hasher with a pre-configured perceptual hash algorithm.
+    /// Create a new hasher with the default perceptual hash (DoubleGradient, 16x16).
     pub fn new() -> Self {
-        let phash_hasher = HasherConfig::new()
-            .hash_alg(HashAlg::DoubleGradient)
-            .hash_size(16, 16)
-            .to_hasher();
-        Self { phash_hasher }
+        Self::from_config(&HashingConfig::default())
+    }
+
+    /// Create a hasher for the configured perceptual hash algorithms.
+    ///
+    /// Unknown algorithm names are skipped with a warning; config validation
+    /// rejects them up front.
+    pub fn from_config(config: &HashingConfig) -> Self {
+        let phash_hashers = config
+            .perceptual
+            .iter()
+            .filter_map(|entry| match build_hasher(entry) {
+                Some(hasher) => Some((entry.algorithm.clone(), hasher)),
+                None => {
+                    tracing::warn!("Unknown perceptual hash algorithm {:?}", entry.algorithm);
+                    None
+                }
+            })
+            .collect();
+        Self { phash_hashers }
+    }
+
+    /// Name of the first configured algorithm, whose hash is also emitted as
+    /// `perceptual_hash`.
+    pub fn primary_algorithm(&self) -> Option<&str> {
+        self.phash_hashers.first().map(|(name, _)| name.as_str())
     }
 
     /// Generate a BLAKE3 hash of file contents for exact deduplication.
@@ -63,32 +103,86 @@ impl Hasher {
         hasher.finalize().to_hex().to_string()
     }
 
-    /// Generate a perceptual hash for near-duplicate detection.
+    /// Generate the primary perceptual hash for near-duplicate detection.
     ///
     /// Uses the pre-configured hasher to avoid per-image allocation overhead.
     /// Similar images will have similar hashes, allowing detection of
-    /// resized, cropped, or slightly modified versions.
+    /// resized, cropped, or slightly modified versions. Returns an empty
+    /// string if no algorithm is configured.
     pub fn perceptual_hash(&self, image: &DynamicImage) -> String {
-        let hash = self.phash_hasher.hash_image(image);
-        hash.to_base64()
+        self.phash_hashers
+            .first()
+            .map(|(_, hasher)| hasher.hash_image(image).to_base64())
+            .unwrap_or_default()
+    }
+
+    /// Generate every configured perceptual hash, keyed by algorithm name.
+    pub fn perceptual_hashes(&self, image: &DynamicImage) -> BTreeMap<String, String> {
+        self.phash_hashers
+            .iter()
+            .map(|(name, hasher)| (name.clone(), hasher.hash_image(image).to_base64()))
+            .collect()
     }
 
     /// Compare two perceptual hashes and return their Hamming distance.
     ///
-    /// Returns `None` if either hash is invalid.
+    /// Each hash is given with the name of the algorithm that produced it.
+    /// Returns `None` — refusing to compare — if the algorithms differ, the
+    /// hashes have different sizes, or either hash is invalid: distances
+    /// between such hashes are meaningless.
     /// A distance of 0 means identical images.
-    /// Distances < 10 typically indicate very similar images.
-    pub fn perceptual_distance(hash1: &str, hash2: &str) -> Option<u32> {
+    /// Distances < 10 typically indicate very similar images (16x16 hashes).
+    pub fn perceptual_distance(
+        algorithm1: &str,
+        hash1: &str,
+        algorithm2: &str,
+        hash2: &str,
+    ) -> Option<u32> {
+        if algorithm1 != algorithm2 {
+            tracing::debug!("Refusing to compare {algorithm1} and {algorithm2} perceptual hashes");
+            return None;
+        }
         let h1 = ImageHash::<Vec<u8>>::from_base64(hash1).ok()?;
         let h2 = ImageHash::<Vec<u8>>::from_base64(hash2).ok()?;
+        if h1.as_bytes().len() != h2.as_bytes().len() {
+            return None;
+        }
         Some(h1.dist(&h2))
     }
 }
 
+/// Build the `image_hasher` hasher for one configured algorithm.
+fn build_hasher(entry: &PerceptualHashConfig) -> Option<image_hasher::Hasher> {
+    let config = HasherConfig::new().hash_size(entry.size, entry.size);
+    let config = match entry.algorithm.as_str() {
+        "ahash" => config.hash_alg(HashAlg::Mean),
+        "dhash" => config.hash_alg(HashAlg::Gradient),
+        "phash" => config.hash_alg(HashAlg::Mean).preproc_dct(),
+        "blockhash" => config.hash_alg(HashAlg::Blockhash),
+        "median" => config.hash_alg(HashAlg::Median),
+        "vert_gradient" => config.hash_alg(HashAlg::VertGradient),
+        "double_gradient" => config.hash_alg(HashAlg::DoubleGradient),
+        _ => return None,
+    };
+    Some(config.to_hasher())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn config(entries: &[(&str, u32)]) -> HashingConfig {
+        HashingConfig {
+            perceptual: entries
+                .iter()
+                .map(|&(algorithm, size)| PerceptualHashConfig {
+                    algorithm: algorithm.to_string(),
+                    size,
+                })
+                .collect(),
+        }
+    }
+
     #[test]
     fn test_perceptual_hash_consistency() {
         // Same image should produce same hash
@@ -104,13 +198,69 @@ mod tests {
         let hasher = Hasher::new();
         let img = DynamicImage::new_rgb8(100, 100);
         let hash = hasher.perceptual_hash(&img);
-        let distance = Hasher::perceptual_distance(&hash, &hash);
+        let distance =
+            Hasher::perceptual_distance("double_gradient", &hash, "double_gradient", &hash);
         assert_eq!(distance, Some(0));
     }
 
     #[test]
     fn test_perceptual_distance_invalid_hash() {
-        let distance = Hasher::perceptual_distance("invalid", "also_invalid");
+        let distance = Hasher::perceptual_distance("phash", "invalid", "phash", "also_invalid");
         assert!(distance.is_none());
     }
+
+    #[test]
+    fn test_perceptual_distance_refuses_mixed_algorithms() {
+        let hasher = Hasher::from_config(&config(&[("phash", 8), ("dhash", 8)]));
+        let hashes = hasher.perceptual_hashes(&DynamicImage::new_rgb8(64, 64));
+        assert!(
+            Hasher::perceptual_distance("phash", &hashes["phash"], "dhash", &hashes["dhash"])
+                .is_none()
+        );
+        assert_eq!(
+            Hasher::perceptual_distance("phash", &hashes["phash"], "phash", &hashes["phash"]),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_perceptual_distance_refuses_mixed_sizes() {
+        let img = DynamicImage::new_rgb8(64, 64);
+        let small = Hasher::from_config(&config(&[("dhash", 8)])).perceptual_hash(&img);
+        let large = Hasher::from_config(&config(&[("dhash", 16)])).perceptual_hash(&img);
+        assert!(Hasher::perceptual_distance("dhash", &small, "dhash", &large).is_none());
+    }
+
+    #[test]
+    fn test_multiple_hashes_keyed_by_algorithm() {
+        let hasher = Hasher::from_config(&config(&[
+            ("phash", 8),
+            ("dhash", 8),
+            ("ahash", 8),
+            ("blockhash", 8),
+        ]));
+        assert_eq!(hasher.primary_algorithm(), Some("phash"));
+
+        let hashes = hasher.perceptual_hashes(&DynamicImage::new_rgb8(64, 64));
+        assert_eq!(
+            hashes.keys().collect::<Vec<_>>(),
+            ["ahash", "blockhash", "dhash", "phash"]
+        );
+        assert_eq!(
+            hasher.perceptual_hash(&DynamicImage::new_rgb8(64, 64)),
+            hashes["phash"]
+        );
+    }
+
+    #[test]
+    fn test_unknown_algorithm_skipped() {
+        let hasher = Hasher::from_config(&config(&[("sha1", 8), ("dhash", 8)]));
+        assert_eq!(hasher.primary_algorithm(), Some("dhash"));
+
+        let empty = Hasher::from_config(&config(&[]));
+        assert!(empty.primary_algorithm().is_none());
+        assert!(empty
+            .perceptual_hash(&DynamicImage::new_rgb8(8, 8))
+            .is_empty());
+    }
 }
@@ -0,0 +1,234 @@
+//! IPTC-IIM reader for descriptive metadata in JPEG files.
+//!
+//! Legacy IPTC data lives in the JPEG APP13 segment as a Photoshop image
+//! resource (`8BIM` block 0x0404) holding IIM datasets. Only the record 2
+//! fields that map onto [`XmpData`] are read.
+
+use crate::types::XmpData;
+
+/// JPEG APP13 payload prefix for Photoshop image resources.
+const PHOTOSHOP_SIGNATURE: &[u8] = b"Photoshop 3.0\0";
+/// Image resource ID holding the IPTC-IIM data.
+const IPTC_RESOURCE_ID: u16 = 0x0404;
+/// IIM `CodedCharacterSet` value (record 1, dataset 90) meaning UTF-8.
+const UTF8_CHARSET: &[u8] = b"\x1b%G";
+
+// Record 2 (application record) datasets
+const OBJECT_NAME: u8 = 5;
+const KEYWORDS: u8 = 25;
+const BY_LINE: u8 = 80;
+const COPYRIGHT_NOTICE: u8 = 116;
+const CAPTION_ABSTRACT: u8 = 120;
+
+/// Read the IPTC-IIM fields of a JPEG file.
+///
+/// Returns `None` for non-JPEG input or when no IPTC block is present.
+pub(crate) fn read(bytes: &[u8]) -> Option<XmpData> {
+    let resources = find_app13(bytes)?;
+    let iim = find_resource(resources, IPTC_RESOURCE_ID)?;
+    let datasets = parse_datasets(iim);
+
+    let utf8 = datasets
+        .iter()
+        .any(|&(record, dataset, value)| record == 1 && dataset == 90 && value == UTF8_CHARSET);
+    let datasets = &datasets;
+    let values = move |wanted: u8| {
+        datasets
+            .iter()
+            .filter(move |&&(record, dataset, _)| record == 2 && dataset == wanted)
+            .filter_map(move |&(_, _, value)| decode(value, utf8))
+    };
+
+    let creators: Vec<String> = values(BY_LINE).collect();
+    let data = XmpData {
+        title: values(OBJECT_NAME).next(),
+        caption: values(CAPTION_ABSTRACT).next(),
+        keywords: values(KEYWORDS).collect(),
+        creator: (!creators.is_empty()).then(|| creators.join("; ")),
+        copyright: values(COPYRIGHT_NOTICE).next(),
+        rating: None,
+        label: None,
+    };
+
+    (data != XmpData::default()).then_some(data)
+}
+
+/// Walk the JPEG marker segments up to the image data and return the
+/// Photoshop resource block of the APP13 segment.
+fn find_app13(bytes: &[u8]) -> Option<&[u8]> {
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut pos = 2;
+    while pos + 4 <= bytes.len() {
+        if bytes[pos] != 0xFF {
+            return None;
+        }
+        let marker = bytes[pos + 1];
+        // Fill bytes and standalone markers carry no length
+        if marker == 0xFF {
+            pos += 1;
+            continue;
+        }
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            pos += 2;
+            continue;
+        }
+        // Start of scan: metadata segments all come before it
+        if marker == 0xDA || marker == 0xD9 {
+            return None;
+        }
+        let len = u16::from_be_bytes([bytes[pos + 2], bytes[pos + 3]]) as usize;
+        let payload = bytes.get(pos + 4..pos + 2 + len)?;
+        if marker == 0xED {
+            if let Some(resources) = payload.strip_prefix(PHOTOSHOP_SIGNATURE) {
+                return Some(resources);
+            }
+        }
+        pos += 2 + len;
+    }
+    None
+}
+
+/// Find a Photoshop image resource (`8BIM` block) by ID.
+fn find_resource(mut data: &[u8], id: u16) -> Option<&[u8]> {
+    while data.len() >= 12 && data.starts_with(b"8BIM") {
+        let resource_id = u16::from_be_bytes([data[4], data[5]]);
+        // Pascal-string name, padded so length byte + name is even
+        let name_len = data[6] as usize;
+        let name_field = (1 + name_len).next_multiple_of(2);
+        let size_at = 6 + name_field;
+        let size = u32::from_be_bytes(data.get(size_at..size_at + 4)?.try_into().ok()?) as usize;
+        let start = size_at + 4;
+        let block = data.get(start..start + size)?;
+        if resource_id == id {
+            return Some(block);
+        }
+        data = data.get(start + size.next_multiple_of(2)..)?;
+    }
+    None
+}
+
+/// Split IIM data into `(record, dataset, value)` triples.
+fn parse_datasets(mut data: &[u8]) -> Vec<(u8, u8, &[u8])> {
+    let mut datasets = Vec::new();
+    while data.len() >= 5 && data[0] == 0x1C {
+        let (record, dataset) = (data[1], data[2]);
+        let mut len = u16::from_be_bytes([data[3], data[4]]) as usize;
+        let mut start = 5;
+        // Extended dataset: the low 15 bits give the size of the length field
+        if len & 0x8000 != 0 {
+            let len_bytes = len & 0x7FFF;
+            let Some(field) = data.get(5..5 + len_bytes).filter(|_| len_bytes <= 4) else {
+                break;
+            };
+            len = field.iter().fold(0, |acc, &b| (acc << 8) | b as usize);
+            start += len_bytes;
+        }
+        let Some(value) = data.get(start..start + len) else {
+            break;
+        };
+        datasets.push((record, dataset, value));
+        data = &data[start + len..];
+    }
+    datasets
+}
+
+/// Decode an IIM string: UTF-8 when declared or valid, otherwise Latin-1.
+fn decode(value: &[u8], utf8: bool) -> Option<String> {
+    let text = match std::str::from_utf8(value) {
+        Ok(text) => text.to_string(),
+        Err(_) if utf8 => String::from_utf8_lossy(value).into_owned(),
+        Err(_) => value.iter().map(|&b| b as char).collect(),
+    };
+    let text = text.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Encode one IIM dataset.
+    fn dataset(record: u8, number: u8, value: &[u8]) -> Vec<u8> {
+        let mut out = vec![0x1C, record, number];
+        out.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        out.extend_from_slice(value);
+        out
+    }
+
+    /// Build a JPEG APP13 segment holding the given IIM datasets.
+    pub(crate) fn app13_segment(iim: &[u8]) -> Vec<u8> {
+        let mut resource = b"8BIM".to_vec();
+        resource.extend_from_slice(&IPTC_RESOURCE_ID.to_be_bytes());
+        resource.extend_from_slice(&[0, 0]); // empty name, padded
+        resource.extend_from_slice(&(iim.len() as u32).to_be_bytes());
+        resource.extend_from_slice(iim);
+        if iim.len() % 2 == 1 {
+            resource.push(0);
+        }
+
+        let mut payload = PHOTOSHOP_SIGNATURE.to_vec();
+        payload.extend_from_slice(&resource);
+        let mut segment = vec![0xFF, 0xED];
+        segment.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        segment.extend_from_slice(&payload);
+        segment
+    }
+
+    /// IIM block with UTF-8 charset, title, two keywords, by-line and copyright.
+    pub(crate) fn sample_iim() -> Vec<u8> {
+        [
+            dataset(1, 90, UTF8_CHARSET),
+            dataset(2, OBJECT_NAME, "Café terrace".as_bytes()),
+            dataset(2, KEYWORDS, b"coffee"),
+            dataset(2, KEYWORDS, b"street"),
+            dataset(2, BY_LINE, b"Ana Silva"),
+            dataset(2, COPYRIGHT_NOTICE, b"(c) Ana Silva"),
+        ]
+        .concat()
+    }
+
+    fn jpeg_with(segment: &[u8]) -> Vec<u8> {
+        [
+            &[0xFF, 0xD8][..],
+            segment,
+            &[0xFF, 0xDA, 0x00, 0x02, 0xFF, 0xD9],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_read_iptc_from_jpeg() {
+        let data = read(&jpeg_with(&app13_segment(&sample_iim()))).unwrap();
+        assert_eq!(data.title.as_deref(), Some("Café terrace"));
+        assert_eq!(data.keywords, ["coffee", "street"]);
+        assert_eq!(data.creator.as_deref(), Some("Ana Silva"));
+        assert_eq!(data.copyright.as_deref(), Some("(c) Ana Silva"));
+        assert!(data.caption.is_none());
+        assert!(data.rating.is_none());
+    }
+
+    #[test]
+    fn test_latin1_without_charset() {
+        let iim = dataset(2, CAPTION_ABSTRACT, b"Caf\xe9");
+        let data = read(&jpeg_with(&app13_segment(&iim))).unwrap();
+        assert_eq!(data.caption.as_deref(), Some("Café"));
+    }
+
+    #[test]
+    fn test_extended_length_dataset() {
+        let mut iim = vec![0x1C, 2, CAPTION_ABSTRACT, 0x80, 0x02, 0x00, 0x03];
+        iim.extend_from_slice(b"abc");
+        let data = read(&jpeg_with(&app13_segment(&iim))).unwrap();
+        assert_eq!(data.caption.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn test_no_iptc() {
+        assert!(read(b"\x89PNG\r\n\x1a\n").is_none());
+        assert!(read(&jpeg_with(&[0xFF, 0xE0, 0x00, 0x04, 0x4A, 0x46])).is_none());
+        // Truncated segment length
+        assert!(read(&[0xFF, 0xD8, 0xFF, 0xED, 0x40, 0x00, 0x00]).is_none());
+    }
+}
@@ -1,13 +1,16 @@
-//! EXIF metadata extraction from images.
+//! EXIF, XMP and IPTC metadata extraction from images.
 
+use chrono::{DateTime, FixedOffset, NaiveDateTime, SecondsFormat, Timelike};
 use exif::{In, Reader, Tag, Value};
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufRead, BufReader, Cursor, Seek};
 use std::path::Path;
 
-use crate::types::ExifData;
+use crate::types::{ExifData, XmpData};
 
-/// Extracts EXIF metadata from image files.
+use super::{iptc, xmp};
+
+/// Extracts EXIF, XMP and IPTC metadata from image files.
 pub struct MetadataExtractor;
 
 impl MetadataExtractor {
@@ -17,11 +20,56 @@ impl MetadataExtractor {
     /// This method is intentionally lenient - it returns partial data if available.
     pub fn extract(path: &Path) -> Option<ExifData> {
         let file = File::open(path).ok()?;
-        let mut reader = BufReader::new(file);
-        let exif = Reader::new().read_from_container(&mut reader).ok()?;
+        Self::extract_from_reader(&mut BufReader::new(file))
+    }
+
+    /// Extract EXIF data from an in-memory image file.
+    ///
+    /// Same leniency as [`MetadataExtractor::extract`].
+    pub fn extract_from_bytes(bytes: &[u8]) -> Option<ExifData> {
+        Self::extract_from_reader(&mut Cursor::new(bytes))
+    }
+
+    /// Extract descriptive metadata (title, caption, keywords, ...) from an
+    /// in-memory image file's XMP packet and IPTC-IIM block.
+    ///
+    /// XMP values win; IPTC fills in what the XMP packet lacks. Returns `None`
+    /// if neither is present.
+    pub fn extract_xmp_from_bytes(bytes: &[u8]) -> Option<XmpData> {
+        let from_xmp = xmp::find_packet(bytes).and_then(xmp::parse);
+        let from_iptc = iptc::read(bytes);
+        match (from_xmp, from_iptc) {
+            (Some(xmp), Some(iptc)) => Some(Self::merge_descriptive(xmp, iptc)),
+            (xmp, iptc) => xmp.or(iptc),
+        }
+    }
+
+    /// Fill fields missing from `primary` with those of `fallback`.
+    fn merge_descriptive(primary: XmpData, fallback: XmpData) -> XmpData {
+        XmpData {
+            title: primary.title.or(fallback.title),
+            caption: primary.caption.or(fallback.caption),
+            keywords: if primary.keywords.is_empty() {
+                fallback.keywords
+            } else {
+                primary.keywords
+            },
+            creator: primary.creator.or(fallback.creator),
+            copyright: primary.copyright.or(fallback.copyright),
+            rating: primary.rating.or(fallback.rating),
+            label: primary.label.or(fallback.label),
+        }
+    }
+
+    fn extract_from_reader<R: BufRead + Seek>(reader: &mut R) -> Option<ExifData> {
+        let exif = Reader::new().read_from_container(reader).ok()?;
 
+        let gps_timestamp = Self::get_gps_timestamp(&exif);
+        let (captured_at, captured_at_raw) =
+            Self::get_capture_time(&exif, gps_timestamp.as_deref());
         let data = ExifData {
-            captured_at: Self::get_datetime(&exif),
+            captured_at,
+            captured_at_raw,
             camera_make: Self::get_string(&exif, Tag::Make),
             camera_model: Self::get_string(&exif, Tag::Model),
             gps_latitude: Self::get_gps_coord(&exif, Tag::GPSLatitude, Tag::GPSLatitudeRef),
@@ -31,20 +79,24 @@ impl MetadataExtractor {
             shutter_speed: Self::get_shutter_speed(&exif),
             focal_length: Self::get_focal_length(&exif),
             orientation: Self::get_u32(&exif, Tag::Orientation),
+            lens_model: Self::get_string(&exif, Tag::LensModel),
+            exposure_program: Self::get_display(&exif, Tag::ExposureProgram),
+            exposure_bias: Self::get_rational(&exif, Tag::ExposureBiasValue).map(|v| v as f32),
+            white_balance: Self::get_display(&exif, Tag::WhiteBalance),
+            flash: Self::get_display(&exif, Tag::Flash),
+            metering_mode: Self::get_display(&exif, Tag::MeteringMode),
+            gps_altitude: Self::get_gps_altitude(&exif),
+            gps_timestamp,
+            image_direction: Self::get_rational(&exif, Tag::GPSImgDirection),
+            offset_time_original: Self::get_string(&exif, Tag::OffsetTimeOriginal),
+            serial_number: Self::get_ascii(&exif, Tag::BodySerialNumber),
+            lens_serial_number: Self::get_ascii(&exif, Tag::LensSerialNumber),
+            owner_name: Self::get_ascii(&exif, Tag::CameraOwnerName)
+                .or_else(|| Self::get_ascii(&exif, Tag::Artist)),
         };
 
         // Only return if we have at least some data
-        if data.captured_at.is_some()
-            || data.camera_make.is_some()
-            || data.camera_model.is_some()
-            || data.gps_latitude.is_some()
-            || data.gps_longitude.is_some()
-            || data.iso.is_some()
-            || data.aperture.is_some()
-            || data.shutter_speed.is_some()
-            || data.focal_length.is_some()
-            || data.orientation.is_some()
-        {
+        if data != ExifData::default() {
             Some(data)
         } else {
             None
@@ -60,6 +112,25 @@ impl MetadataExtractor {
         })
     }
 
+    /// Get an enumerated field as its human-readable EXIF description
+    /// (e.g., "aperture priority"). Unknown values are skipped.
+    fn get_display(exif: &exif::Exif, tag: Tag) -> Option<String> {
+        exif.get_field(tag, In::PRIMARY)
+            .map(|f| f.display_value().to_string())
+            .filter(|s| !s.is_empty() && !s.starts_with("unknown"))
+    }
+
+    /// Get the first value of a rational or signed rational field.
+    fn get_rational(exif: &exif::Exif, tag: Tag) -> Option<f64> {
+        exif.get_field(tag, In::PRIMARY)
+            .and_then(|f| match &f.value {
+                Value::Rational(v) => v.first().map(|r| r.to_f64()),
+                Value::SRational(v) => v.first().map(|r| r.to_f64()),
+                _ => None,
+            })
+            .filter(|v| v.is_finite())
+    }
+
     /// Get a u32 field from EXIF data.
     fn get_u32(exif: &exif::Exif, tag: Tag) -> Option<u32> {
         exif.get_field(tag, In::PRIMARY)
@@ -70,14 +141,118 @@ impl MetadataExtractor {
             })
     }
 
-    /// Get the capture datetime, preferring DateTimeOriginal over DateTime.
-    fn get_datetime(exif: &exif::Exif) -> Option<String> {
-        exif.get_field(Tag::DateTimeOriginal, In::PRIMARY)
-            .or_else(|| exif.get_field(Tag::DateTime, In::PRIMARY))
-            .map(|f| {
-                let s = f.display_value().to_string();
-                s.trim_matches('"').to_string()
-            })
+    /// Get a raw ASCII field, without the display formatting.
+    fn get_ascii(exif: &exif::Exif, tag: Tag) -> Option<String> {
+        match &exif.get_field(tag, In::PRIMARY)?.value {
+            Value::Ascii(values) => values
+                .first()
+                .map(|v| {
+                    String::from_utf8_lossy(v)
+                        .trim_matches(['\0', ' '])
+                        .to_string()
+                })
+                .filter(|s| !s.is_empty()),
+            _ => None,
+        }
+    }
+
+    /// Get the capture time as `(normalized, raw)`.
+    ///
+    /// Prefers DateTimeOriginal with its SubSecTimeOriginal and
+    /// OffsetTimeOriginal tags, falling back to DateTime with SubSecTime and
+    /// OffsetTime. See [`Self::normalize_capture_time`] for the rules.
+    fn get_capture_time(
+        exif: &exif::Exif,
+        gps_timestamp: Option<&str>,
+    ) -> (Option<String>, Option<String>) {
+        let (raw, subsec, offset) = match Self::get_ascii(exif, Tag::DateTimeOriginal) {
+            Some(raw) => (
+                Some(raw),
+                Self::get_ascii(exif, Tag::SubSecTimeOriginal),
+                Self::get_ascii(exif, Tag::OffsetTimeOriginal),
+            ),
+            None => (
+                Self::get_ascii(exif, Tag::DateTime),
+                Self::get_ascii(exif, Tag::SubSecTime),
+                Self::get_ascii(exif, Tag::OffsetTime),
+            ),
+        };
+        let normalized = Self::normalize_capture_time(
+            raw.as_deref(),
+            subsec.as_deref(),
+            offset.as_deref(),
+            gps_timestamp,
+        );
+        (normalized, raw)
+    }
+
+    /// Build an RFC 3339 capture time from EXIF date parts.
+    ///
+    /// - `raw` ("YYYY:MM:DD HH:MM:SS") plus `subsec` digits give the local time.
+    /// - The offset comes from `offset` ("+02:00"); if that is missing, it is
+    ///   derived from the difference to the GPS UTC timestamp, rounded to
+    ///   15 minutes and only if within ±14 hours.
+    /// - With no offset either way, the local time is returned without one.
+    /// - With no parseable `raw`, the GPS timestamp itself is used.
+    fn normalize_capture_time(
+        raw: Option<&str>,
+        subsec: Option<&str>,
+        offset: Option<&str>,
+        gps_timestamp: Option<&str>,
+    ) -> Option<String> {
+        let gps = gps_timestamp.and_then(|t| DateTime::parse_from_rfc3339(t).ok());
+        let Some(local) = raw.and_then(|raw| Self::parse_exif_datetime(raw, subsec)) else {
+            return gps.map(|t| t.to_rfc3339_opts(SecondsFormat::AutoSi, true));
+        };
+
+        let offset = offset.and_then(Self::parse_offset).or_else(|| {
+            let gps = gps?;
+            let difference = (local - gps.naive_utc()).num_seconds();
+            let rounded = (difference as f64 / 900.0).round() as i32 * 900;
+            (rounded.abs() <= 14 * 3600)
+                .then(|| FixedOffset::east_opt(rounded))
+                .flatten()
+        });
+
+        match offset.and_then(|o| local.and_local_timezone(o).single()) {
+            Some(time) => Some(time.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+            None => Some(local.format("%Y-%m-%dT%H:%M:%S%.f").to_string()),
+        }
+    }
+
+    /// Parse an EXIF "YYYY:MM:DD HH:MM:SS" value, adding SubSecTime digits
+    /// as the fractional second.
+    fn parse_exif_datetime(raw: &str, subsec: Option<&str>) -> Option<NaiveDateTime> {
+        let raw = raw.trim();
+        let time = NaiveDateTime::parse_from_str(raw, "%Y:%m:%d %H:%M:%S")
+            .or_else(|_| NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S"))
+            .ok()?;
+
+        let digits: String = subsec
+            .unwrap_or_default()
+            .trim()
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .take(9)
+            .collect();
+        if digits.is_empty() {
+            return Some(time);
+        }
+        let nanos: u32 = format!("{digits:0<9}").parse().ok()?;
+        time.with_nanosecond(nanos)
+    }
+
+    /// Parse an EXIF offset ("+02:00", "-05:30").
+    fn parse_offset(offset: &str) -> Option<FixedOffset> {
+        let offset = offset.trim();
+        let sign = match offset.as_bytes().first()? {
+            b'+' => 1,
+            b'-' => -1,
+            _ => return None,
+        };
+        let (hours, minutes) = offset[1..].split_once(':')?;
+        let seconds = hours.parse::<i32>().ok()? * 3600 + minutes.parse::<i32>().ok()? * 60;
+        FixedOffset::east_opt(sign * seconds)
     }
 
     /// Get GPS coordinate, converting from degrees/minutes/seconds to decimal.
@@ -99,6 +274,53 @@ impl MetadataExtractor {
         Some(sign * degrees)
     }
 
+    /// Get GPS altitude in meters, negative when GPSAltitudeRef marks it as
+    /// below sea level.
+    fn get_gps_altitude(exif: &exif::Exif) -> Option<f64> {
+        let altitude = Self::get_rational(exif, Tag::GPSAltitude)?;
+        let below_sea_level = exif
+            .get_field(Tag::GPSAltitudeRef, In::PRIMARY)
+            .is_some_and(|f| matches!(&f.value, Value::Byte(v) if v.first() == Some(&1)));
+        Some(if below_sea_level { -altitude } else { altitude })
+    }
+
+    /// Combine GPSDateStamp ("YYYY:MM:DD") and GPSTimeStamp (h, m, s
+    /// rationals) into an RFC 3339 UTC timestamp.
+    fn get_gps_timestamp(exif: &exif::Exif) -> Option<String> {
+        let date = Self::get_string(exif, Tag::GPSDateStamp)?;
+        let time = exif.get_field(Tag::GPSTimeStamp, In::PRIMARY)?;
+        let Value::Rational(hms) = &time.value else {
+            return None;
+        };
+        if hms.len() < 3 {
+            return None;
+        }
+
+        let mut parts = date.trim().split([':', '-']);
+        let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return None;
+        }
+        let (hours, minutes, seconds) = (hms[0].to_f64(), hms[1].to_f64(), hms[2].to_f64());
+        if !(0.0..24.0).contains(&hours)
+            || !(0.0..60.0).contains(&minutes)
+            || !(0.0..61.0).contains(&seconds)
+        {
+            return None;
+        }
+
+        let seconds = if seconds.fract() == 0.0 {
+            format!("{:02}", seconds as u32)
+        } else {
+            let s = format!("{seconds:06.3}");
+            s.trim_end_matches('0').to_string()
+        };
+        Some(format!(
+            "{year}-{month}-{day}T{:02}:{:02}:{seconds}Z",
+            hours as u32, minutes as u32
+        ))
+    }
+
     /// Parse GPS rationals (degrees, minutes, seconds) to decimal degrees.
     fn parse_gps_rationals(value: &Value) -> Option<f64> {
         match value {
@@ -145,4 +367,103 @@ mod tests {
         let result = MetadataExtractor::extract(Path::new("/nonexistent/file.jpg"));
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_capture_time_with_subsec_and_offset() {
+        let time = MetadataExtractor::normalize_capture_time(
+            Some("2023:01:01 12:00:00"),
+            Some("25"),
+            Some("+02:00"),
+            None,
+        );
+        assert_eq!(time.as_deref(), Some("2023-01-01T12:00:00.250+02:00"));
+    }
+
+    #[test]
+    fn test_capture_time_offset_from_gps() {
+        // Local 12:00, GPS says 10:58:40 UTC → +01:00 after rounding
+        let time = MetadataExtractor::normalize_capture_time(
+            Some("2023:06:01 12:00:00"),
+            None,
+            None,
+            Some("2023-06-01T10:58:40Z"),
+        );
+        assert_eq!(time.as_deref(), Some("2023-06-01T12:00:00+01:00"));
+    }
+
+    #[test]
+    fn test_capture_time_ignores_implausible_gps_offset() {
+        let time = MetadataExtractor::normalize_capture_time(
+            Some("2023:06:01 12:00:00"),
+            Some("5"),
+            None,
+            Some("2023-06-03T12:00:00Z"),
+        );
+        assert_eq!(time.as_deref(), Some("2023-06-01T12:00:00.500"));
+    }
+
+    #[test]
+    fn test_capture_time_without_zone_is_local() {
+        let time = MetadataExtractor::normalize_capture_time(
+            Some("2023:01:01 12:00:00"),
+            None,
+            Some("   :  "),
+            None,
+        );
+        assert_eq!(time.as_deref(), Some("2023-01-01T12:00:00"));
+    }
+
+    #[test]
+    fn test_capture_time_falls_back_to_gps() {
+        let time = MetadataExtractor::normalize_capture_time(
+            Some("    :  :     :  :  "),
+            None,
+            None,
+            Some("2023-06-01T10:15:30.5Z"),
+        );
+        assert_eq!(time.as_deref(), Some("2023-06-01T10:15:30.500Z"));
+        assert!(MetadataExtractor::normalize_capture_time(None, None, None, None).is_none());
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        let offset = |s| MetadataExtractor::parse_offset(s).map(|o| o.local_minus_utc());
+        assert_eq!(offset("+02:00"), Some(7200));
+        assert_eq!(offset("-05:30"), Some(-19800));
+        assert_eq!(offset("02:00"), None);
+        assert_eq!(offset(""), None);
+    }
+
+    #[test]
+    fn test_extract_xmp_prefers_xmp_over_iptc() {
+        let mut bytes = vec![0xFF, 0xD8];
+        bytes.extend(iptc::tests::app13_segment(&iptc::tests::sample_iim()));
+        bytes.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x02]);
+        bytes.extend_from_slice(xmp::tests::PACKET.as_bytes());
+
+        let data = MetadataExtractor::extract_xmp_from_bytes(&bytes).unwrap();
+        // Present in both: XMP wins
+        assert_eq!(data.title.as_deref(), Some("Beach at dusk"));
+        assert_eq!(data.keywords, ["beach", "sunset"]);
+        // XMP-only fields
+        assert_eq!(data.rating, Some(4));
+        assert_eq!(data.caption.as_deref(), Some("Waves & sand"));
+    }
+
+    #[test]
+    fn test_extract_xmp_falls_back_to_iptc() {
+        let mut bytes = vec![0xFF, 0xD8];
+        bytes.extend(iptc::tests::app13_segment(&iptc::tests::sample_iim()));
+        bytes.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02]);
+
+        let data = MetadataExtractor::extract_xmp_from_bytes(&bytes).unwrap();
+        assert_eq!(data.title.as_deref(), Some("Café terrace"));
+        assert_eq!(data.keywords, ["coffee", "street"]);
+        assert!(data.rating.is_none());
+    }
+
+    #[test]
+    fn test_extract_xmp_none_without_metadata() {
+        assert!(MetadataExtractor::extract_xmp_from_bytes(b"\xFF\xD8\xFF\xD9").is_none());
+    }
 }
@@ -2,25 +2,46 @@
 //!
 //! This module contains all the stages of the image processing pipeline:
 //! - **decode**: Load and decode images from various formats
-//! - **metadata**: Extract EXIF metadata from images
+//! - **raw**: Extract embedded JPEG previews from camera RAW files
+//! - **metadata**: Extract EXIF, XMP and IPTC metadata from images
+//! - **xmp**: Parse XMP packets for descriptive metadata
+//! - **iptc**: Read IPTC-IIM blocks from JPEG files
 //! - **hash**: Generate content and perceptual hashes
-//! - **thumbnail**: Generate WebP thumbnails
+//! - **thumbnail**: Generate WebP, JPEG, PNG or AVIF thumbnails
+//! - **placeholder**: BlurHash and ThumbHash placeholder strings
+//! - **palette**: Extract dominant colors (k-means in Lab space)
+//! - **quality**: Sharpness, exposure, noise and colorfulness metrics
 //! - **discovery**: Find image files in directories
+//! - **archive**: List and read images inside zip and tar(.gz) archives
 //! - **validate**: Pre-processing validation
+//! - **privacy**: Redact location and identifying metadata from results
 //! - **processor**: Orchestrates the full pipeline
 
+pub(crate) mod archive;
 pub(crate) mod decode;
 pub(crate) mod discovery;
 pub(crate) mod hash;
+pub(crate) mod iptc;
 pub(crate) mod metadata;
+pub(crate) mod palette;
+pub(crate) mod placeholder;
+pub(crate) mod privacy;
 pub(crate) mod processor;
+pub(crate) mod quality;
+pub(crate) mod raw;
 pub(crate) mod thumbnail;
 pub(crate) mod validate;
+pub(crate) mod xmp;
 
 // Re-exports for convenient access
+pub use archive::{ArchiveEntry, ArchiveKind, ArchiveReader};
 pub use decode::ImageDecoder;
-pub use discovery::{DiscoveredFile, FileDiscovery};
+pub use discovery::{DiscoveredFile, DiscoveryFilters, FileDiscovery, FilterCounts};
 pub use hash::Hasher;
 pub use metadata::MetadataExtractor;
+pub use palette::PaletteExtractor;
+pub use placeholder::PlaceholderGenerator;
+pub use privacy::Redactor;
 pub use processor::{ImageProcessor, ProcessOptions};
+pub use quality::QualityAnalyzer;
 pub use thumbnail::ThumbnailGenerator;
@@ -0,0 +1,360 @@
+//! Dominant color palette extraction.
+//!
+//! Clusters the pixels of a downscaled copy with k-means in CIE Lab, where
+//! Euclidean distance roughly tracks perceived color difference, so clusters
+//! split where a viewer would see different colors rather than along RGB
+//! channel noise. Initialization is k-means++ with a fixed seed, so the same
+//! image always yields the same palette.
+
+use image::DynamicImage;
+use rand::{Rng, SeedableRng};
+
+use crate::config::PaletteConfig;
+use crate::types::PaletteColor;
+
+/// Lloyd iteration cap; a 64px sample converges well before this.
+const MAX_ITERATIONS: usize = 20;
+
+/// Pixels with lower alpha are ignored (transparent PNG/WebP backgrounds).
+const MIN_ALPHA: u8 = 128;
+
+/// Named reference colors (sRGB). Names follow the `color` terms of the
+/// supplemental vocabulary where one exists, so palette and tag search agree.
+const NAMED_COLORS: &[(&str, [u8; 3])] = &[
+    ("black", [0, 0, 0]),
+    ("dark gray", [64, 64, 64]),
+    ("gray", [128, 128, 128]),
+    ("silver", [192, 192, 192]),
+    ("white", [255, 255, 255]),
+    ("red", [220, 20, 30]),
+    ("maroon", [128, 0, 0]),
+    ("brown", [139, 69, 19]),
+    ("tan", [210, 180, 140]),
+    ("beige", [245, 245, 220]),
+    ("cream", [255, 253, 208]),
+    ("orange", [255, 140, 0]),
+    ("gold", [255, 215, 0]),
+    ("yellow", [255, 255, 0]),
+    ("olive", [128, 128, 0]),
+    ("green", [34, 139, 34]),
+    ("dark green", [0, 80, 0]),
+    ("teal", [0, 128, 128]),
+    ("turquoise", [64, 224, 208]),
+    ("light blue", [135, 190, 235]),
+    ("blue", [30, 80, 220]),
+    ("navy", [0, 0, 128]),
+    ("purple", [128, 0, 128]),
+    ("lavender", [200, 170, 230]),
+    ("pink", [255, 170, 190]),
+    ("magenta", [255, 0, 255]),
+];
+
+/// Extracts the dominant colors of an image.
+pub struct PaletteExtractor {
+    config: PaletteConfig,
+}
+
+impl PaletteExtractor {
+    /// Create a new palette extractor with the given configuration.
+    pub fn new(config: PaletteConfig) -> Self {
+        Self { config }
+    }
+
+    /// Compute up to `config.colors` dominant colors, largest share first.
+    ///
+    /// Returns an empty palette if extraction is disabled or every pixel is
+    /// transparent. Images with fewer distinct colors yield fewer entries.
+    pub fn extract(&self, image: &DynamicImage) -> Vec<PaletteColor> {
+        if !self.config.enabled {
+            return vec![];
+        }
+
+        // Only ever downscale: upscaling would just blend in extra colors
+        let size = self.config.sample_size;
+        let sample = if image.width() > size || image.height() > size {
+            image.thumbnail(size, size).to_rgba8()
+        } else {
+            image.to_rgba8()
+        };
+        let pixels: Vec<[f32; 3]> = sample
+            .pixels()
+            .filter(|p| p.0[3] >= MIN_ALPHA)
+            .map(|p| srgb_to_lab([p.0[0], p.0[1], p.0[2]]))
+            .collect();
+        if pixels.is_empty() {
+            return vec![];
+        }
+
+        let (centers, counts) = kmeans(&pixels, self.config.colors);
+        let total = pixels.len() as f32;
+
+        let mut palette: Vec<PaletteColor> = centers
+            .iter()
+            .zip(counts)
+            .filter(|&(_, count)| count > 0)
+            .map(|(&lab, count)| {
+                let [r, g, b] = lab_to_srgb(lab);
+                PaletteColor {
+                    hex: format!("#{r:02x}{g:02x}{b:02x}"),
+                    lab: lab.map(|v| (v * 100.0).round() / 100.0),
+                    share: (count as f32 / total * 10_000.0).round() / 10_000.0,
+                    name: nearest_name(lab).to_string(),
+                }
+            })
+            .collect();
+        palette.sort_by(|a, b| b.share.total_cmp(&a.share));
+        palette
+    }
+
+    /// Check if palette extraction is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+}
+
+/// Cluster `pixels` into at most `k` groups. Returns centers and member counts.
+fn kmeans(pixels: &[[f32; 3]], k: usize) -> (Vec<[f32; 3]>, Vec<usize>) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+
+    // k-means++: each further center is drawn with probability proportional
+    // to the squared distance from the nearest existing one
+    let mut centers = vec![pixels[rng.gen_range(0..pixels.len())]];
+    let mut nearest_sq: Vec<f32> = pixels.iter().map(|p| dist_sq(p, &centers[0])).collect();
+    while centers.len() < k {
+        let total: f32 = nearest_sq.iter().sum();
+        if total <= f32::EPSILON {
+            // Fewer distinct colors than k
+            break;
+        }
+        let mut target = rng.gen::<f32>() * total;
+        let index = nearest_sq
+            .iter()
+            .position(|&d| {
+                target -= d;
+                target <= 0.0
+            })
+            .unwrap_or(pixels.len() - 1);
+        let center = pixels[index];
+        for (best, pixel) in nearest_sq.iter_mut().zip(pixels) {
+            *best = best.min(dist_sq(pixel, &center));
+        }
+        centers.push(center);
+    }
+
+    let mut assignment = vec![usize::MAX; pixels.len()];
+    let mut counts = vec![0; centers.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let mut changed = false;
+        for (slot, pixel) in assignment.iter_mut().zip(pixels) {
+            let nearest = nearest_center(pixel, &centers);
+            if *slot != nearest {
+                *slot = nearest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+
+        let mut sums = vec![[0.0f32; 3]; centers.len()];
+        counts = vec![0; centers.len()];
+        for (&cluster, pixel) in assignment.iter().zip(pixels) {
+            for c in 0..3 {
+                sums[cluster][c] += pixel[c];
+            }
+            counts[cluster] += 1;
+        }
+        for ((center, sum), &count) in centers.iter_mut().zip(&sums).zip(&counts) {
+            if count > 0 {
+                *center = sum.map(|s| s / count as f32);
+            }
+        }
+    }
+
+    (centers, counts)
+}
+
+fn nearest_center(pixel: &[f32; 3], centers: &[[f32; 3]]) -> usize {
+    (0..centers.len())
+        .min_by(|&a, &b| dist_sq(pixel, &centers[a]).total_cmp(&dist_sq(pixel, &centers[b])))
+        .unwrap_or(0)
+}
+
+/// Name of the reference color closest in Lab (CIE76 ΔE).
+fn nearest_name(lab: [f32; 3]) -> &'static str {
+    NAMED_COLORS
+        .iter()
+        .map(|&(name, rgb)| (name, dist_sq(&lab, &srgb_to_lab(rgb))))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(name, _)| name)
+        .unwrap_or("gray")
+}
+
+fn dist_sq(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum()
+}
+
+// sRGB (D65) ↔ CIE Lab conversion
+
+const WHITE: [f32; 3] = [0.950_47, 1.0, 1.088_83];
+const DELTA: f32 = 6.0 / 29.0;
+
+fn srgb_to_lab(rgb: [u8; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb.map(|c| {
+        let c = c as f32 / 255.0;
+        if c <= 0.040_45 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    });
+    let xyz = [
+        0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b,
+        0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b,
+        0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b,
+    ];
+    let [fx, fy, fz] = [0, 1, 2].map(|i| {
+        let t = xyz[i] / WHITE[i];
+        if t > DELTA.powi(3) {
+            t.cbrt()
+        } else {
+            t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+        }
+    });
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn lab_to_srgb(lab: [f32; 3]) -> [u8; 3] {
+    let fy = (lab[0] + 16.0) / 116.0;
+    let f = [fy + lab[1] / 500.0, fy, fy - lab[2] / 200.0];
+    let [x, y, z] = [0, 1, 2].map(|i| {
+        let t = if f[i] > DELTA {
+            f[i].powi(3)
+        } else {
+            3.0 * DELTA * DELTA * (f[i] - 4.0 / 29.0)
+        };
+        t * WHITE[i]
+    });
+    let linear = [
+        3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z,
+        -0.969_266 * x + 1.876_010_8 * y + 0.041_556 * z,
+        0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z,
+    ];
+    linear.map(|c| {
+        let c = c.clamp(0.0, 1.0);
+        let c = if c <= 0.003_130_8 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        };
+        (c * 255.0).round() as u8
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    fn extractor() -> PaletteExtractor {
+        PaletteExtractor::new(PaletteConfig {
+            enabled: true,
+            ..PaletteConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_lab_roundtrip() {
+        let white = srgb_to_lab([255, 255, 255]);
+        assert!((white[0] - 100.0).abs() < 0.01);
+        assert!(white[1].abs() < 0.01 && white[2].abs() < 0.01);
+        assert_eq!(srgb_to_lab([0, 0, 0])[0], 0.0);
+
+        for rgb in [[255, 0, 0], [12, 200, 97], [30, 58, 95], [128, 128, 128]] {
+            assert_eq!(lab_to_srgb(srgb_to_lab(rgb)), rgb);
+        }
+    }
+
+    #[test]
+    fn test_solid_image_single_color() {
+        let image =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(100, 80, Rgba([0, 0, 128, 255])));
+        let palette = extractor().extract(&image);
+
+        assert_eq!(palette.len(), 1);
+        assert_eq!(palette[0].hex, "#000080");
+        assert_eq!(palette[0].share, 1.0);
+        assert_eq!(palette[0].name, "navy");
+    }
+
+    #[test]
+    fn test_two_colors_sorted_by_share() {
+        // Left three quarters red, right quarter white
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_fn(64, 64, |x, _| {
+            if x < 48 {
+                Rgba([220, 20, 30, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            }
+        }));
+        let palette = extractor().extract(&image);
+
+        assert_eq!(palette.len(), 2);
+        assert_eq!(palette[0].name, "red");
+        assert!((palette[0].share - 0.75).abs() < 0.01);
+        assert_eq!(palette[1].hex, "#ffffff");
+        assert!((palette[1].share - 0.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_transparent_pixels_ignored() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_fn(32, 32, |x, _| {
+            if x < 8 {
+                Rgba([255, 215, 0, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        }));
+        let palette = extractor().extract(&image);
+        assert_eq!(palette.len(), 1);
+        assert_eq!(palette[0].name, "gold");
+        assert_eq!(palette[0].share, 1.0);
+
+        let clear = DynamicImage::ImageRgba8(RgbaImage::new(16, 16));
+        assert!(extractor().extract(&clear).is_empty());
+    }
+
+    #[test]
+    fn test_gradient_respects_k_and_is_deterministic() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_fn(128, 128, |x, y| {
+            Rgba([(x * 2) as u8, (y * 2) as u8, 128, 255])
+        }));
+        let first = extractor().extract(&image);
+        let second = extractor().extract(&image);
+
+        assert_eq!(first.len(), PaletteConfig::default().colors);
+        assert_eq!(first, second);
+        let total: f32 = first.iter().map(|c| c.share).sum();
+        assert!((total - 1.0).abs() < 0.001);
+        assert!(first.windows(2).all(|w| w[0].share >= w[1].share));
+    }
+
+    #[test]
+    fn test_disabled() {
+        let extractor = PaletteExtractor::new(PaletteConfig {
+            enabled: false,
+            ..PaletteConfig::default()
+        });
+        assert!(!extractor.is_enabled());
+        assert!(extractor
+            .extract(&DynamicImage::new_rgb8(10, 10))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_nearest_name() {
+        assert_eq!(nearest_name(srgb_to_lab([250, 250, 250])), "white");
+        assert_eq!(nearest_name(srgb_to_lab([20, 130, 40])), "green");
+        assert_eq!(nearest_name(srgb_to_lab([140, 195, 240])), "light blue");
+    }
+}
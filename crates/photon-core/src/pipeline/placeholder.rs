@@ -0,0 +1,406 @@
+//! Compact image placeholders for clients to show while the real image loads.
+//!
+//! - **BlurHash** (<https://blurha.sh>): a few DCT components of the image,
+//!   base83-encoded into a short ASCII string. Aspect ratio is not encoded.
+//! - **ThumbHash** (<https://evanw.github.io/thumbhash/>): ~25 bytes holding
+//!   luminance, chroma and alpha DCT terms plus the aspect ratio, emitted as
+//!   base64.
+//!
+//! Both follow the reference encoders, so the standard decoders on web and
+//! mobile read them as-is.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use image::DynamicImage;
+use std::f32::consts::PI;
+
+use crate::config::PlaceholderConfig;
+
+/// Longest edge the image is reduced to before BlurHash encoding; more
+/// pixels don't change the result noticeably.
+const BLURHASH_SAMPLE_SIZE: u32 = 32;
+
+/// ThumbHash is defined for images of at most 100×100.
+const THUMBHASH_MAX_SIZE: u32 = 100;
+
+const BASE83: &[u8; 83] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+/// Computes BlurHash and ThumbHash placeholder strings.
+pub struct PlaceholderGenerator {
+    config: PlaceholderConfig,
+}
+
+impl PlaceholderGenerator {
+    /// Create a new placeholder generator with the given configuration.
+    pub fn new(config: PlaceholderConfig) -> Self {
+        Self { config }
+    }
+
+    /// BlurHash of the image, or `None` if disabled.
+    pub fn blurhash(&self, image: &DynamicImage) -> Option<String> {
+        if !self.config.blurhash {
+            return None;
+        }
+        let rgb = downscale(image, BLURHASH_SAMPLE_SIZE).to_rgb8();
+        Some(encode_blurhash(
+            rgb.as_raw(),
+            rgb.width() as usize,
+            rgb.height() as usize,
+            self.config.components_x as usize,
+            self.config.components_y as usize,
+        ))
+    }
+
+    /// Base64-encoded ThumbHash of the image, or `None` if disabled.
+    pub fn thumbhash(&self, image: &DynamicImage) -> Option<String> {
+        if !self.config.thumbhash {
+            return None;
+        }
+        let rgba = downscale(image, THUMBHASH_MAX_SIZE).to_rgba8();
+        let hash = encode_thumbhash(rgba.as_raw(), rgba.width() as usize, rgba.height() as usize);
+        Some(BASE64.encode(hash))
+    }
+
+    /// Check if any placeholder is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.config.blurhash || self.config.thumbhash
+    }
+}
+
+/// Shrink so the longest edge is at most `size`, never enlarging.
+fn downscale(image: &DynamicImage, size: u32) -> DynamicImage {
+    if image.width() > size || image.height() > size {
+        image.thumbnail(size, size)
+    } else {
+        image.clone()
+    }
+}
+
+/// BlurHash of packed RGB8 pixels with `cx` × `cy` components (1-9 each).
+fn encode_blurhash(rgb: &[u8], width: usize, height: usize, cx: usize, cy: usize) -> String {
+    let linear: Vec<[f32; 3]> = rgb
+        .chunks_exact(3)
+        .map(|p| [p[0], p[1], p[2]].map(srgb_to_linear))
+        .collect();
+
+    let mut factors = Vec::with_capacity(cx * cy);
+    for j in 0..cy {
+        for i in 0..cx {
+            let normalization = if i == 0 && j == 0 { 1.0 } else { 2.0 };
+            let mut sum = [0.0f32; 3];
+            for y in 0..height {
+                let basis_y = (PI * j as f32 * y as f32 / height as f32).cos();
+                for x in 0..width {
+                    let basis = basis_y * (PI * i as f32 * x as f32 / width as f32).cos();
+                    for (s, v) in sum.iter_mut().zip(linear[y * width + x]) {
+                        *s += basis * v;
+                    }
+                }
+            }
+            let scale = normalization / (width * height) as f32;
+            factors.push(sum.map(|s| s * scale));
+        }
+    }
+
+    let mut hash = String::with_capacity(4 + 2 * cx * cy);
+    push_base83(&mut hash, (cx - 1) + (cy - 1) * 9, 1);
+
+    let (dc, ac) = factors.split_first().expect("at least one component");
+    let maximum = if ac.is_empty() {
+        push_base83(&mut hash, 0, 1);
+        1.0
+    } else {
+        let actual = ac
+            .iter()
+            .flat_map(|f| f.iter())
+            .fold(0.0f32, |m, v| m.max(v.abs()));
+        let quantized = (actual * 166.0 - 0.5).floor().clamp(0.0, 82.0) as usize;
+        push_base83(&mut hash, quantized, 1);
+        (quantized + 1) as f32 / 166.0
+    };
+
+    let [r, g, b] = dc.map(linear_to_srgb);
+    push_base83(&mut hash, (r << 16) | (g << 8) | b, 4);
+
+    for factor in ac {
+        let [r, g, b] = factor.map(|v| {
+            let signed = (v / maximum).signum() * (v / maximum).abs().sqrt();
+            (signed * 9.0 + 9.5).floor().clamp(0.0, 18.0) as usize
+        });
+        push_base83(&mut hash, r * 19 * 19 + g * 19 + b, 2);
+    }
+    hash
+}
+
+fn push_base83(hash: &mut String, value: usize, length: u32) {
+    for i in (0..length).rev() {
+        let digit = (value / 83usize.pow(i)) % 83;
+        hash.push(BASE83[digit] as char);
+    }
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let v = f32::from(value) / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> usize {
+    let v = value.clamp(0.0, 1.0);
+    if v <= 0.003_130_8 {
+        (v * 12.92 * 255.0 + 0.5) as usize
+    } else {
+        ((1.055 * v.powf(1.0 / 2.4) - 0.055) * 255.0 + 0.5) as usize
+    }
+}
+
+/// ThumbHash of packed RGBA8 pixels (at most 100×100).
+fn encode_thumbhash(rgba: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let pixels = width * height;
+
+    // Average color, weighted by alpha
+    let (mut avg_r, mut avg_g, mut avg_b, mut avg_a) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+    for p in rgba.chunks_exact(4) {
+        let alpha = f32::from(p[3]) / 255.0;
+        avg_r += alpha / 255.0 * f32::from(p[0]);
+        avg_g += alpha / 255.0 * f32::from(p[1]);
+        avg_b += alpha / 255.0 * f32::from(p[2]);
+        avg_a += alpha;
+    }
+    if avg_a > 0.0 {
+        avg_r /= avg_a;
+        avg_g /= avg_a;
+        avg_b /= avg_a;
+    }
+
+    let has_alpha = avg_a < pixels as f32;
+    // Fewer luminance bits when alpha needs room
+    let l_limit = if has_alpha { 5.0 } else { 7.0 };
+    let longest = width.max(height) as f32;
+    let lx = ((l_limit * width as f32 / longest).round() as usize).max(1);
+    let ly = ((l_limit * height as f32 / longest).round() as usize).max(1);
+
+    // RGBA → LPQA, composited over the average color
+    let mut l = Vec::with_capacity(pixels);
+    let mut p = Vec::with_capacity(pixels);
+    let mut q = Vec::with_capacity(pixels);
+    let mut a = Vec::with_capacity(pixels);
+    for px in rgba.chunks_exact(4) {
+        let alpha = f32::from(px[3]) / 255.0;
+        let r = avg_r * (1.0 - alpha) + alpha / 255.0 * f32::from(px[0]);
+        let g = avg_g * (1.0 - alpha) + alpha / 255.0 * f32::from(px[1]);
+        let b = avg_b * (1.0 - alpha) + alpha / 255.0 * f32::from(px[2]);
+        l.push((r + g + b) / 3.0);
+        p.push((r + g) / 2.0 - b);
+        q.push(r - g);
+        a.push(alpha);
+    }
+
+    let encode = |channel: &[f32], nx: usize, ny: usize| {
+        encode_thumbhash_channel(channel, width, height, nx, ny)
+    };
+    let (l_dc, l_ac, l_scale) = encode(&l, lx.max(3), ly.max(3));
+    let (p_dc, p_ac, p_scale) = encode(&p, 3, 3);
+    let (q_dc, q_ac, q_scale) = encode(&q, 3, 3);
+    let alpha_terms = has_alpha.then(|| encode(&a, 5, 5));
+
+    let is_landscape = width > height;
+    let header24 = (63.0 * l_dc).round() as u32
+        | ((31.5 + 31.5 * p_dc).round() as u32) << 6
+        | ((31.5 + 31.5 * q_dc).round() as u32) << 12
+        | ((31.0 * l_scale).round() as u32) << 18
+        | u32::from(has_alpha) << 23;
+    let header16 = (if is_landscape { ly } else { lx }) as u32
+        | ((63.0 * p_scale).round() as u32) << 3
+        | ((63.0 * q_scale).round() as u32) << 9
+        | u32::from(is_landscape) << 15;
+    let mut hash = vec![
+        (header24 & 255) as u8,
+        ((header24 >> 8) & 255) as u8,
+        (header24 >> 16) as u8,
+        (header16 & 255) as u8,
+        (header16 >> 8) as u8,
+    ];
+    if let Some((a_dc, _, a_scale)) = &alpha_terms {
+        hash.push((15.0 * a_dc).round() as u8 | ((15.0 * a_scale).round() as u8) << 4);
+    }
+
+    // Varying factors, two 4-bit values per byte, low nibble first
+    let mut acs = vec![l_ac, p_ac, q_ac];
+    acs.extend(alpha_terms.map(|(_, ac, _)| ac));
+    for (index, factor) in acs.iter().flatten().enumerate() {
+        let nibble = (15.0 * factor).round() as u8;
+        if index % 2 == 0 {
+            hash.push(nibble);
+        } else {
+            *hash.last_mut().expect("pushed above") |= nibble << 4;
+        }
+    }
+    hash
+}
+
+/// DCT of one channel: DC term, AC terms normalized to 0..1, and their scale.
+fn encode_thumbhash_channel(
+    channel: &[f32],
+    width: usize,
+    height: usize,
+    nx: usize,
+    ny: usize,
+) -> (f32, Vec<f32>, f32) {
+    let mut dc = 0.0;
+    let mut ac = Vec::new();
+    let mut scale = 0.0f32;
+    let mut fx = vec![0.0f32; width];
+    for cy in 0..ny {
+        let mut cx = 0;
+        while cx * ny < nx * (ny - cy) {
+            for (x, f) in fx.iter_mut().enumerate() {
+                *f = (PI / width as f32 * cx as f32 * (x as f32 + 0.5)).cos();
+            }
+            let mut f = 0.0;
+            for (y, row) in channel.chunks_exact(width).enumerate() {
+                let fy = (PI / height as f32 * cy as f32 * (y as f32 + 0.5)).cos();
+                f += row.iter().zip(&fx).map(|(v, fx)| v * fx).sum::<f32>() * fy;
+            }
+            f /= (width * height) as f32;
+            if cx > 0 || cy > 0 {
+                ac.push(f);
+                scale = scale.max(f.abs());
+            } else {
+                dc = f;
+            }
+            cx += 1;
+        }
+    }
+    if scale > 0.0 {
+        for f in &mut ac {
+            *f = 0.5 + 0.5 / scale * *f;
+        }
+    }
+    (dc, ac, scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    fn config() -> PlaceholderConfig {
+        PlaceholderConfig {
+            blurhash: true,
+            thumbhash: true,
+            ..PlaceholderConfig::default()
+        }
+    }
+
+    fn generator() -> PlaceholderGenerator {
+        PlaceholderGenerator::new(config())
+    }
+
+    fn decode83(s: &str) -> usize {
+        s.bytes().fold(0, |value, c| {
+            value * 83 + BASE83.iter().position(|&b| b == c).unwrap()
+        })
+    }
+
+    /// Average color from a ThumbHash header, per the reference decoder.
+    fn thumbhash_average(hash: &[u8]) -> [f32; 4] {
+        let header = u32::from(hash[0]) | u32::from(hash[1]) << 8 | u32::from(hash[2]) << 16;
+        let l = (header & 63) as f32 / 63.0;
+        let p = ((header >> 6) & 63) as f32 / 31.5 - 1.0;
+        let q = ((header >> 12) & 63) as f32 / 31.5 - 1.0;
+        let a = if header >> 23 != 0 {
+            f32::from(hash[5] & 15) / 15.0
+        } else {
+            1.0
+        };
+        let b = l - 2.0 / 3.0 * p;
+        let r = (3.0 * l - b + q) / 2.0;
+        let g = r - q;
+        [r, g, b, a].map(|v| v.clamp(0.0, 1.0))
+    }
+
+    #[test]
+    fn test_blurhash_solid_color() {
+        let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(64, 48, Rgb([200, 100, 50])));
+        let hash = generator().blurhash(&image).unwrap();
+
+        // 4×3 components: size flag, max AC, 4-char DC, 2 chars per AC term
+        assert_eq!(hash.len(), 1 + 1 + 4 + 2 * 11);
+        assert_eq!(&hash[0..1], "L");
+        assert_eq!(decode83(&hash[2..6]), 200 << 16 | 100 << 8 | 50);
+        // Flat image: the only AC energy is the reference basis' edge residue
+        // (odd cosines don't sum to zero over pixel corners), about 2/width
+        assert!(decode83(&hash[1..2]) <= 8);
+    }
+
+    #[test]
+    fn test_blurhash_components_and_detail() {
+        let generator = PlaceholderGenerator::new(PlaceholderConfig {
+            components_x: 1,
+            components_y: 1,
+            ..config()
+        });
+        let gradient = DynamicImage::ImageRgb8(RgbImage::from_fn(64, 64, |x, _| {
+            Rgb([(x * 4) as u8, 0, 255 - (x * 4) as u8])
+        }));
+        assert_eq!(generator.blurhash(&gradient).unwrap().len(), 6);
+
+        // A horizontal gradient puts energy in the first horizontal AC term
+        let hash = self::generator().blurhash(&gradient).unwrap();
+        assert_ne!(&hash[1..2], "0");
+        assert_ne!(decode83(&hash[6..8]), 9 * 19 * 19 + 9 * 19 + 9);
+    }
+
+    #[test]
+    fn test_thumbhash_solid_color() {
+        let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(200, 200, Rgb([200, 100, 50])));
+        let hash = BASE64
+            .decode(generator().thumbhash(&image).unwrap())
+            .unwrap();
+
+        // Square, opaque: 5 header bytes + (27 L + 5 P + 5 Q) AC nibbles
+        assert_eq!(hash.len(), 5 + 19);
+        let [r, g, b, a] = thumbhash_average(&hash);
+        assert!((r - 200.0 / 255.0).abs() < 0.03, "r {r}");
+        assert!((g - 100.0 / 255.0).abs() < 0.03, "g {g}");
+        assert!((b - 50.0 / 255.0).abs() < 0.03, "b {b}");
+        assert_eq!(a, 1.0);
+    }
+
+    #[test]
+    fn test_thumbhash_alpha_and_orientation() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_fn(80, 40, |x, _| {
+            if x < 40 {
+                Rgba([0, 0, 255, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        }));
+        let hash = BASE64
+            .decode(generator().thumbhash(&image).unwrap())
+            .unwrap();
+
+        assert_eq!(hash[2] >> 7, 1, "alpha flag");
+        assert_eq!(hash[4] >> 7, 1, "landscape flag");
+        let [_, _, b, a] = thumbhash_average(&hash);
+        assert!(b > 0.9, "b {b}");
+        assert!((a - 0.5).abs() < 0.1, "a {a}");
+    }
+
+    #[test]
+    fn test_disabled() {
+        let generator = PlaceholderGenerator::new(PlaceholderConfig {
+            blurhash: false,
+            thumbhash: false,
+            ..PlaceholderConfig::default()
+        });
+        let image = DynamicImage::new_rgb8(8, 8);
+        assert!(!generator.is_enabled());
+        assert!(generator.blurhash(&image).is_none());
+        assert!(generator.thumbhash(&image).is_none());
+    }
+}
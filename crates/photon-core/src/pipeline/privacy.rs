@@ -0,0 +1,315 @@
+//! Privacy mode: redacts location and identifying metadata from processed images.
+//!
+//! Runs as the last pipeline stage, so every consumer of [`ProcessedImage`] —
+//! the CLI writers and library callers alike — only ever sees redacted data.
+//! Paths are replaced with a keyed BLAKE3 hash: stable across runs with the
+//! same key (so `--skip-existing` and joins keep working) but not reversible
+//! or guessable without it.
+
+use std::path::{Path, PathBuf};
+
+use crate::config::PrivacyConfig;
+use crate::error::ConfigError;
+use crate::llm::provider::resolve_env_var;
+use crate::types::{ExifData, ProcessedImage, XmpData};
+
+/// Context string for deriving the path-hashing key from the configured secret.
+const KEY_CONTEXT: &str = "photon output privacy v1 path hash";
+
+/// How GPS coordinates are treated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GpsMode {
+    Keep,
+    /// Snap to the center of a grid cell of the given size in degrees.
+    Coarsen(f64),
+    Drop,
+}
+
+/// How `file_path` / `file_name` are treated.
+#[derive(Clone)]
+enum PathMode {
+    Keep,
+    Hash([u8; 32]),
+}
+
+/// Applies the configured privacy redactions to a [`ProcessedImage`].
+#[derive(Clone)]
+pub struct Redactor {
+    gps: GpsMode,
+    strip_identifiers: bool,
+    paths: PathMode,
+}
+
+impl Redactor {
+    /// Build a redactor from config.
+    ///
+    /// Fails if path hashing is requested but the key does not resolve.
+    pub fn new(config: &PrivacyConfig) -> Result<Self, ConfigError> {
+        let gps = match config.gps.as_str() {
+            "keep" => GpsMode::Keep,
+            "coarsen" => GpsMode::Coarsen(config.gps_grid_degrees),
+            _ => GpsMode::Drop,
+        };
+
+        let paths = if !config.hash_paths {
+            PathMode::Keep
+        } else {
+            let secret = path_hash_secret(config).ok_or_else(|| {
+                ConfigError::ValidationError(format!(
+                    "output.privacy.path_hash_key is not set ({}); \
+                         set it or disable output.privacy.hash_paths",
+                    config.path_hash_key
+                ))
+            })?;
+            PathMode::Hash(blake3::derive_key(KEY_CONTEXT, secret.as_bytes()))
+        };
+
+        Ok(Self {
+            gps,
+            strip_identifiers: config.strip_identifiers,
+            paths,
+        })
+    }
+
+    /// Redact an image record in place.
+    ///
+    /// The real path moves to the never-serialized `source_path`, so later
+    /// in-process stages (LLM enrichment) can still read the file.
+    pub fn redact(&self, image: &mut ProcessedImage) {
+        match &self.paths {
+            PathMode::Keep => {}
+            PathMode::Hash(key) => {
+                image.source_path = image.file_path.take();
+                image.file_path = image.source_path.as_deref().map(|p| hash_path(key, p));
+                image.file_name = keyed_hex(key, image.file_name.as_bytes());
+            }
+        }
+
+        if let Some(exif) = image.exif.as_mut() {
+            match self.gps {
+                GpsMode::Keep => {}
+                GpsMode::Coarsen(grid) => {
+                    exif.gps_latitude = exif
+                        .gps_latitude
+                        .map(|lat| snap(lat, grid).clamp(-90.0, 90.0));
+                    exif.gps_longitude = exif.gps_longitude.map(|lon| snap(lon, grid));
+                    exif.gps_altitude = None;
+                    exif.image_direction = None;
+                }
+                GpsMode::Drop => {
+                    exif.gps_latitude = None;
+                    exif.gps_longitude = None;
+                    exif.gps_altitude = None;
+                    exif.gps_timestamp = None;
+                    exif.image_direction = None;
+                }
+            }
+            if self.strip_identifiers {
+                exif.serial_number = None;
+                exif.lens_serial_number = None;
+                exif.owner_name = None;
+            }
+        }
+        match self.gps {
+            GpsMode::Keep => {}
+            // A city can pin the photo down more tightly than the grid cell, and
+            // its distance would let the exact position be triangulated
+            GpsMode::Coarsen(_) => {
+                if let Some(location) = image.location.as_mut() {
+                    location.city = None;
+                    location.distance_km = None;
+                }
+            }
+            GpsMode::Drop => image.location = None,
+        }
+
+        if self.strip_identifiers {
+            if let Some(xmp) = image.xmp.as_mut() {
+                xmp.creator = None;
+            }
+        }
+
+        // Don't leave empty objects behind
+        if image.exif.as_ref() == Some(&ExifData::default()) {
+            image.exif = None;
+        }
+        if image.xmp.as_ref() == Some(&XmpData::default()) {
+            image.xmp = None;
+        }
+    }
+
+    /// The `file_path` a record for `path` carries in redacted output.
+    /// Used to match existing output entries.
+    pub fn redact_path(&self, path: &Path) -> PathBuf {
+        match &self.paths {
+            PathMode::Keep => path.to_path_buf(),
+            PathMode::Hash(key) => hash_path(key, path),
+        }
+    }
+}
+
+/// The resolved path hashing secret, if set and non-empty.
+pub(crate) fn path_hash_secret(config: &PrivacyConfig) -> Option<String> {
+    resolve_env_var(&config.path_hash_key).filter(|key| !key.is_empty())
+}
+
+fn hash_path(key: &[u8; 32], path: &Path) -> PathBuf {
+    PathBuf::from(keyed_hex(key, path.as_os_str().as_encoded_bytes()))
+}
+
+fn keyed_hex(key: &[u8; 32], data: &[u8]) -> String {
+    blake3::keyed_hash(key, data).to_hex().to_string()
+}
+
+/// Center of the grid cell containing `value`.
+fn snap(value: f64, grid: f64) -> f64 {
+    let snapped = ((value / grid).floor() + 0.5) * grid;
+    // Trim float noise (e.g. 38.750000000000004)
+    (snapped * 1e6).round() / 1e6
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Location;
+
+    fn config() -> PrivacyConfig {
+        PrivacyConfig {
+            enabled: true,
+            path_hash_key: "test-secret".to_string(),
+            ..PrivacyConfig::default()
+        }
+    }
+
+    fn sample_image() -> ProcessedImage {
+        ProcessedImage {
+            file_path: Some(PathBuf::from("/photos/alice/beach.jpg")),
+            file_name: "beach.jpg".to_string(),
+            content_hash: "abc123".to_string(),
+            width: 100,
+            height: 100,
+            format: "jpeg".to_string(),
+            file_size: 1000,
+            exif: Some(ExifData {
+                camera_make: Some("Canon".to_string()),
+                gps_latitude: Some(38.71667),
+                gps_longitude: Some(-9.13333),
+                gps_altitude: Some(42.0),
+                serial_number: Some("123456".to_string()),
+                lens_serial_number: Some("7890".to_string()),
+                owner_name: Some("Alice".to_string()),
+                ..ExifData::default()
+            }),
+            xmp: Some(XmpData {
+                creator: Some("Alice".to_string()),
+                ..XmpData::default()
+            }),
+            location: Some(Location {
+                country: Some("Portugal".to_string()),
+                city: Some("Lisbon".to_string()),
+                distance_km: Some(0.4),
+                ..Location::default()
+            }),
+            ..ProcessedImage::default()
+        }
+    }
+
+    #[test]
+    fn test_default_drops_gps_and_identifiers() {
+        let mut image = sample_image();
+        Redactor::new(&config()).unwrap().redact(&mut image);
+
+        let exif = image.exif.unwrap();
+        assert!(exif.gps_latitude.is_none());
+        assert!(exif.gps_longitude.is_none());
+        assert!(exif.gps_altitude.is_none());
+        assert!(exif.serial_number.is_none());
+        assert!(exif.lens_serial_number.is_none());
+        assert!(exif.owner_name.is_none());
+        assert_eq!(exif.camera_make.as_deref(), Some("Canon"));
+        assert!(image.location.is_none());
+        // Only the creator was set, so the XMP object goes away entirely
+        assert!(image.xmp.is_none());
+    }
+
+    #[test]
+    fn test_coarsen_gps() {
+        let mut config = config();
+        config.gps = "coarsen".to_string();
+        config.gps_grid_degrees = 0.5;
+        let mut image = sample_image();
+        Redactor::new(&config).unwrap().redact(&mut image);
+
+        let exif = image.exif.unwrap();
+        assert_eq!(exif.gps_latitude, Some(38.75));
+        assert_eq!(exif.gps_longitude, Some(-9.25));
+        assert!(exif.gps_altitude.is_none());
+        let location = image.location.unwrap();
+        assert_eq!(location.country.as_deref(), Some("Portugal"));
+        assert!(location.city.is_none());
+        assert!(location.distance_km.is_none());
+    }
+
+    #[test]
+    fn test_keep_gps_and_identifiers() {
+        let mut config = config();
+        config.gps = "keep".to_string();
+        config.strip_identifiers = false;
+        let mut image = sample_image();
+        Redactor::new(&config).unwrap().redact(&mut image);
+
+        assert_eq!(image.exif, sample_image().exif);
+        assert_eq!(image.xmp, sample_image().xmp);
+        assert_eq!(image.location, sample_image().location);
+    }
+
+    #[test]
+    fn test_hash_paths_is_keyed_and_stable() {
+        let redactor = Redactor::new(&config()).unwrap();
+        let mut image = sample_image();
+        redactor.redact(&mut image);
+
+        let original = PathBuf::from("/photos/alice/beach.jpg");
+        assert_eq!(image.source_path.as_ref(), Some(&original));
+        assert_eq!(image.file_path, Some(redactor.redact_path(&original)));
+        assert_eq!(image.file_name.len(), 64);
+        assert!(!image.file_name.contains("beach"));
+
+        let mut other_key = config();
+        other_key.path_hash_key = "another-secret".to_string();
+        assert_ne!(
+            Some(Redactor::new(&other_key).unwrap().redact_path(&original)),
+            image.file_path
+        );
+
+        let serialized = serde_json::to_string(&image).unwrap();
+        assert!(!serialized.contains("alice"));
+    }
+
+    #[test]
+    fn test_missing_key_is_an_error() {
+        let mut config = config();
+        config.path_hash_key = "${PHOTON_TEST_UNSET_PRIVACY_KEY}".to_string();
+        let err = Redactor::new(&config).err().unwrap();
+        assert!(err.to_string().contains("path_hash_key"));
+
+        std::env::set_var("PHOTON_TEST_EMPTY_PRIVACY_KEY", "");
+        config.path_hash_key = "${PHOTON_TEST_EMPTY_PRIVACY_KEY}".to_string();
+        assert!(Redactor::new(&config).is_err());
+
+        config.hash_paths = false;
+        assert!(Redactor::new(&config).is_ok());
+    }
+
+    #[test]
+    fn test_hash_paths_disabled() {
+        let mut config = config();
+        config.hash_paths = false;
+        let mut image = sample_image();
+        Redactor::new(&config).unwrap().redact(&mut image);
+
+        assert_eq!(image.file_path, sample_image().file_path);
+        assert_eq!(image.file_name, "beach.jpg");
+        assert!(image.source_path.is_none());
+    }
+}
@@ -1,12 +1,16 @@
 //! Pipeline orchestration - wires together all processing stages.
 
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 use crate::config::Config;
+use crate::embedding::batcher::EmbedBatcher;
+use crate::embedding::manifest::ModelManifest;
 use crate::embedding::EmbeddingEngine;
 use crate::error::{PipelineError, Result};
+use crate::geocode::Geocoder;
 use crate::tagging::label_bank::LabelBank;
 use crate::tagging::neighbors::NeighborExpander;
 use crate::tagging::progressive::ProgressiveEncoder;
@@ -16,10 +20,14 @@ use crate::tagging::text_encoder::SigLipTextEncoder;
 use crate::tagging::{TagScorer, Vocabulary};
 use crate::types::ProcessedImage;
 
-use super::decode::{format_to_string, ImageDecoder};
-use super::discovery::{DiscoveredFile, FileDiscovery};
+use super::decode::ImageDecoder;
+use super::discovery::{DiscoveredFile, DiscoveryFilters, FileDiscovery, FilterCounts};
 use super::hash::Hasher;
 use super::metadata::MetadataExtractor;
+use super::palette::PaletteExtractor;
+use super::placeholder::PlaceholderGenerator;
+use super::privacy::Redactor;
+use super::quality::QualityAnalyzer;
 use super::thumbnail::ThumbnailGenerator;
 use super::validate::Validator;
 
@@ -30,23 +38,44 @@ pub struct ProcessOptions {
     pub skip_thumbnail: bool,
     /// Skip perceptual hash generation
     pub skip_perceptual_hash: bool,
+    /// Skip BlurHash and ThumbHash placeholders
+    pub skip_placeholders: bool,
+    /// Skip color palette extraction
+    pub skip_palette: bool,
+    /// Skip image-quality metrics
+    pub skip_quality: bool,
     /// Skip embedding generation
     pub skip_embedding: bool,
     /// Skip zero-shot tagging
     pub skip_tagging: bool,
+    /// Skip embedding and tagging for images whose quality-stage sharpness
+    /// is below this (images without quality metrics are unaffected)
+    pub min_sharpness: Option<f32>,
 }
 
 /// The main image processor that orchestrates the full pipeline.
 pub struct ImageProcessor {
     decoder: ImageDecoder,
     thumbnail_gen: ThumbnailGenerator,
+    placeholder_gen: PlaceholderGenerator,
+    palette: PaletteExtractor,
+    quality: QualityAnalyzer,
     validator: Validator,
     discovery: FileDiscovery,
     hasher: Hasher,
     embedding_engine: Option<Arc<EmbeddingEngine>>,
+    /// Cross-image embedding batcher, when enabled for batch runs.
+    embed_batcher: Option<EmbedBatcher>,
     tag_scorer: Option<Arc<RwLock<TagScorer>>>,
     relevance_tracker: Option<RwLock<RelevanceTracker>>,
+    geocoder: Option<Arc<Geocoder>>,
+    /// Output redaction, when privacy mode is enabled.
+    redactor: Option<Redactor>,
     embed_timeout_ms: u64,
+    /// Whether to apply EXIF orientation to decoded pixels.
+    auto_orient: bool,
+    /// Whether to keep per-frame embeddings for animations.
+    frame_embeddings: bool,
     /// Sweep interval: run pool transitions every N images.
     sweep_interval: u64,
     /// Whether neighbor expansion is enabled (from config).
@@ -55,20 +84,39 @@ pub struct ImageProcessor {
 
 impl ImageProcessor {
     /// Create a new image processor with the given configuration.
-    pub fn new(config: &Config) -> Self {
-        Self {
-            decoder: ImageDecoder::new(config.limits.clone()),
+    ///
+    /// Fails only when privacy mode is enabled but its redactor can't be
+    /// built (e.g. the path hash key is unset), so no output is ever written
+    /// unredacted. Models are loaded separately.
+    pub fn new(config: &Config) -> Result<Self> {
+        let redactor = if config.output.privacy.enabled {
+            Some(Redactor::new(&config.output.privacy)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            decoder: ImageDecoder::new(config.limits.clone())
+                .with_animation_frames(config.processing.animation_frames),
             thumbnail_gen: ThumbnailGenerator::new(config.thumbnail.clone()),
+            placeholder_gen: PlaceholderGenerator::new(config.placeholder.clone()),
+            palette: PaletteExtractor::new(config.palette.clone()),
+            quality: QualityAnalyzer::new(config.quality.clone()),
             validator: Validator::new(config.limits.clone()),
             discovery: FileDiscovery::new(config.processing.clone()),
-            hasher: Hasher::new(),
+            hasher: Hasher::from_config(&config.hashing),
             embedding_engine: None,
+            embed_batcher: None,
             tag_scorer: None,
             relevance_tracker: None,
+            geocoder: None,
+            redactor,
             embed_timeout_ms: config.limits.embed_timeout_ms,
+            auto_orient: config.processing.auto_orient,
+            frame_embeddings: config.processing.frame_embeddings,
             sweep_interval: 1000,
             neighbor_expansion: config.tagging.relevance.neighbor_expansion,
-        }
+        })
     }
 
     /// Load the embedding model. Call this before processing if you want
@@ -77,7 +125,9 @@ impl ImageProcessor {
     /// If the model files are not available, returns an error. You can check
     /// availability first with [`EmbeddingEngine::model_exists`].
     pub fn load_embedding(&mut self, config: &Config) -> Result<()> {
-        let engine = EmbeddingEngine::load(&config.embedding, &config.model_dir())?;
+        let mut embedding = config.embedding.clone();
+        embedding.pool_size = embedding.effective_pool_size(config.processing.parallel_workers);
+        let engine = EmbeddingEngine::load(&embedding, &config.model_dir())?;
         self.embedding_engine = Some(Arc::new(engine));
         Ok(())
     }
@@ -87,6 +137,68 @@ impl ImageProcessor {
         self.embedding_engine.is_some()
     }
 
+    /// Batch single-image embeddings across concurrent `process_*` calls:
+    /// tensors queue up and run as one ONNX call once a batch is full or
+    /// `pipeline.max_wait_ms` has passed.
+    ///
+    /// A batch holds at most `pipeline.max_batch_size` images and never more
+    /// than `processing.parallel_workers`, since only that many can be
+    /// waiting at once; a larger batch would just run out `max_wait_ms`.
+    ///
+    /// Call after [`load_embedding`](Self::load_embedding), from within a
+    /// tokio runtime. Does nothing without an embedding engine, without a
+    /// runtime, or when the batch size comes out as 1.
+    pub fn enable_embed_batching(&mut self, config: &Config) {
+        let Some(engine) = &self.embedding_engine else {
+            return;
+        };
+        let max_batch_size = config
+            .pipeline
+            .max_batch_size
+            .min(config.processing.parallel_workers);
+        if max_batch_size <= 1 || tokio::runtime::Handle::try_current().is_err() {
+            return;
+        }
+        let engine = Arc::clone(engine);
+        self.embed_batcher = Some(EmbedBatcher::start(
+            Arc::new(move |tensors: &[ndarray::Array4<f32>], paths: &[PathBuf]| {
+                engine.embed_batch_preprocessed(tensors, paths)
+            }),
+            max_batch_size,
+            Duration::from_millis(config.pipeline.max_wait_ms),
+        ));
+    }
+
+    /// Embedding batch sizes so far as `(size, count)`, ascending; empty
+    /// unless [`enable_embed_batching`](Self::enable_embed_batching) took effect.
+    pub fn embed_batch_sizes(&self) -> Vec<(usize, u64)> {
+        self.embed_batcher
+            .as_ref()
+            .map(EmbedBatcher::batch_sizes)
+            .unwrap_or_default()
+    }
+
+    /// Load the offline reverse geocoder (GeoNames cities + country polygons).
+    /// Call this before processing if you want `location` in the output.
+    ///
+    /// If the dataset is not installed, returns an error. You can check
+    /// availability first with [`Geocoder::dataset_exists`].
+    pub fn load_geocoding(&mut self, config: &Config) -> Result<()> {
+        let geocoder = Geocoder::load(&config.geocoding, &config.geocoding_dir())?;
+        self.geocoder = Some(Arc::new(geocoder));
+        Ok(())
+    }
+
+    /// Check whether the reverse geocoder is loaded.
+    pub fn has_geocoding(&self) -> bool {
+        self.geocoder.is_some()
+    }
+
+    /// The privacy redactor applied to every result, if privacy mode is enabled.
+    pub fn redactor(&self) -> Option<&Redactor> {
+        self.redactor.as_ref()
+    }
+
     /// Load the tagging system (vocabulary + label bank + scorer).
     ///
     /// On first run with progressive encoding enabled (default), this encodes
@@ -116,15 +228,20 @@ impl ImageProcessor {
             return Ok(());
         }
 
+        // The text encoder, label bank width and scoring calibration all
+        // follow the embedding model's manifest
+        let manifest = ModelManifest::resolve(&model_dir, &config.embedding.model)?;
+        let text_dir = manifest.text_model_dir(&model_dir)?;
+
         // Load or build label bank
-        let label_bank_path = taxonomy_dir.join("label_bank.bin");
+        let label_bank_path = taxonomy_dir.join(manifest.label_bank_filename());
         let vocab_hash = vocabulary.content_hash();
 
         if LabelBank::exists(&label_bank_path)
             && LabelBank::cache_valid(&label_bank_path, &vocab_hash)
         {
             // FAST PATH: Load cached label bank (subsequent runs)
-            let label_bank = LabelBank::load(&label_bank_path, vocabulary.len())?;
+            let label_bank = LabelBank::load(&label_bank_path, vocabulary.len(), &manifest)?;
 
             // Load or create relevance tracker
             if config.tagging.relevance.enabled {
@@ -139,7 +256,7 @@ impl ImageProcessor {
                 tracing::info!("Vocabulary changed — rebuilding label bank cache...");
             }
 
-            if !SigLipTextEncoder::model_exists(&model_dir) {
+            if !SigLipTextEncoder::model_exists(&text_dir) {
                 tracing::warn!(
                     "Text encoder not found. Run `photon models download` to enable tagging."
                 );
@@ -151,13 +268,18 @@ impl ImageProcessor {
                 tracing::warn!("No tokio runtime — falling back to blocking encode for tagging");
                 return self.load_tagging_blocking(
                     config,
+                    &manifest,
                     vocabulary,
                     &label_bank_path,
                     &vocab_hash,
                 );
             }
 
-            let text_encoder = Arc::new(SigLipTextEncoder::new(&model_dir)?);
+            let text_encoder = Arc::new(SigLipTextEncoder::new(
+                &text_dir,
+                &manifest,
+                &config.embedding.runtime,
+            )?);
 
             let seed_path = vocab_dir.join("seed_terms.txt");
             let seed_indices = SeedSelector::select(
@@ -169,7 +291,7 @@ impl ImageProcessor {
             // Initialize the scorer slot with an empty scorer, then swap in the seed
             let scorer_slot = Arc::new(RwLock::new(TagScorer::new(
                 Vocabulary::empty(),
-                LabelBank::empty(),
+                LabelBank::empty(&manifest),
                 config.tagging.clone(),
             )));
             self.tag_scorer = Some(Arc::clone(&scorer_slot));
@@ -193,7 +315,13 @@ impl ImageProcessor {
             // pool assignments only make sense once the full label bank is cached.
         } else {
             // BLOCKING PATH (legacy): Encode all terms synchronously
-            return self.load_tagging_blocking(config, vocabulary, &label_bank_path, &vocab_hash);
+            return self.load_tagging_blocking(
+                config,
+                &manifest,
+                vocabulary,
+                &label_bank_path,
+                &vocab_hash,
+            );
         }
 
         Ok(())
@@ -203,21 +331,22 @@ impl ImageProcessor {
     fn load_tagging_blocking(
         &mut self,
         config: &Config,
+        manifest: &ModelManifest,
         vocabulary: Vocabulary,
         label_bank_path: &Path,
         vocab_hash: &str,
     ) -> Result<()> {
-        let model_dir = config.model_dir();
+        let text_dir = manifest.text_model_dir(&config.model_dir())?;
         let taxonomy_dir = config.taxonomy_dir();
 
-        if !SigLipTextEncoder::model_exists(&model_dir) {
+        if !SigLipTextEncoder::model_exists(&text_dir) {
             tracing::warn!(
                 "Text encoder not found. Run `photon models download` to enable tagging."
             );
             return Ok(());
         }
 
-        let text_encoder = SigLipTextEncoder::new(&model_dir)?;
+        let text_encoder = SigLipTextEncoder::new(&text_dir, manifest, &config.embedding.runtime)?;
         let bank = LabelBank::encode_all(&vocabulary, &text_encoder, 64)?;
         std::fs::create_dir_all(&taxonomy_dir).map_err(|e| PipelineError::Model {
             message: format!("Failed to create taxonomy dir {:?}: {}", taxonomy_dir, e),
@@ -355,95 +484,219 @@ impl ImageProcessor {
         let read_time = read_start.elapsed();
         tracing::trace!("  File read: {:?} ({} bytes)", read_time, bytes.len());
 
+        self.process_loaded(bytes, path, Some(path), options, start)
+            .await
+    }
+
+    /// Process an image that is already in memory (e.g. an upload).
+    ///
+    /// `hint_name` stands in for the file name: it becomes `file_name` in the
+    /// output, labels errors, and its extension is the fallback for format
+    /// detection. `file_path` is left unset. Runs the same stages as
+    /// [`ImageProcessor::process_with_options`], so the results are identical
+    /// for identical bytes.
+    pub async fn process_bytes(
+        &self,
+        bytes: Vec<u8>,
+        hint_name: &str,
+        options: &ProcessOptions,
+    ) -> Result<ProcessedImage> {
+        let start = std::time::Instant::now();
+        let name = Path::new(hint_name);
+        tracing::debug!(
+            "Processing in-memory image: {:?} ({} bytes)",
+            name,
+            bytes.len()
+        );
+
+        self.validator.validate_bytes(&bytes, name)?;
+        let validate_time = start.elapsed();
+        tracing::trace!("  Validate: {:?}", validate_time);
+
+        self.process_loaded(bytes, name, None, options, start).await
+    }
+
+    /// Process an archive entry that has been read into memory.
+    ///
+    /// `locator` is the entry's `archive.zip!/inner/path.jpg` path (see
+    /// [`ArchiveReader`](super::archive::ArchiveReader)); it is recorded as
+    /// `file_path` so `--skip-existing` can match it on later runs.
+    pub async fn process_archive_entry(
+        &self,
+        bytes: Vec<u8>,
+        locator: &Path,
+        options: &ProcessOptions,
+    ) -> Result<ProcessedImage> {
+        let start = std::time::Instant::now();
+        tracing::debug!("Processing archive entry: {:?}", locator);
+
+        self.validator.validate_bytes(&bytes, locator)?;
+        let validate_time = start.elapsed();
+        tracing::trace!("  Validate: {:?}", validate_time);
+
+        self.process_loaded(bytes, locator, Some(locator), options, start)
+            .await
+    }
+
+    /// Run every stage after validation on the image bytes.
+    ///
+    /// `source` names the image in errors and output; `file_path` is recorded
+    /// in the output when the image came from disk.
+    async fn process_loaded(
+        &self,
+        bytes: Vec<u8>,
+        source: &Path,
+        file_path: Option<&Path>,
+        options: &ProcessOptions,
+        start: std::time::Instant,
+    ) -> Result<ProcessedImage> {
+        let path = source;
+
         // Generate content hash from bytes (no second file read)
         let hash_start = std::time::Instant::now();
         let content_hash = Hasher::content_hash_from_bytes(&bytes);
         let hash_time = hash_start.elapsed();
         tracing::trace!("  Content hash: {:?}", hash_time);
 
+        // Extract EXIF from the same bytes (sync, cheap — parses the EXIF tags).
+        // Runs before decode because the decoder takes ownership of the buffer;
+        // the XMP/IPTC scan happens inside the decoder's blocking task. The GPS
+        // position is reverse-geocoded in-memory.
+        let metadata_start = std::time::Instant::now();
+        let exif = MetadataExtractor::extract_from_bytes(&bytes);
+        let location = self.geocoder.as_ref().and_then(|geocoder| {
+            let exif = exif.as_ref()?;
+            geocoder.lookup(exif.gps_latitude?, exif.gps_longitude?)
+        });
+        let metadata_time = metadata_start.elapsed();
+        tracing::trace!("  Metadata: {:?}", metadata_time);
+
         // Decode from bytes (no second file read)
         let decode_start = std::time::Instant::now();
-        let decoded = self.decoder.decode_from_bytes(bytes, path).await?;
+        let mut decoded = self.decoder.decode_from_bytes(bytes, path).await?;
+        let xmp = decoded.xmp.take();
         let decode_time = decode_start.elapsed();
         tracing::trace!("  Decode: {:?}", decode_time);
 
-        // Extract metadata (non-blocking, sync operation — reads EXIF tags from file)
-        let metadata_start = std::time::Instant::now();
-        let exif = MetadataExtractor::extract(path);
-        let metadata_time = metadata_start.elapsed();
-        tracing::trace!("  Metadata: {:?}", metadata_time);
+        // Apply EXIF orientation so every downstream stage sees the displayed image
+        if self.auto_orient {
+            if let Some(orientation) = exif.as_ref().and_then(|e| e.orientation) {
+                decoded.apply_orientation(orientation);
+            }
+        }
 
         // Generate perceptual hash
         let phash_start = std::time::Instant::now();
-        let perceptual_hash = if options.skip_perceptual_hash {
-            None
+        let (perceptual_hash, perceptual_hashes) = if options.skip_perceptual_hash {
+            (None, BTreeMap::new())
         } else {
-            Some(self.hasher.perceptual_hash(&decoded.image))
+            let hashes = self.hasher.perceptual_hashes(&decoded.image);
+            let primary = self
+                .hasher
+                .primary_algorithm()
+                .and_then(|algorithm| hashes.get(algorithm).cloned());
+            (primary, hashes)
         };
         let phash_time = phash_start.elapsed();
         tracing::trace!("  Perceptual hash: {:?}", phash_time);
 
         // Generate thumbnail
         let thumb_start = std::time::Instant::now();
-        let thumbnail = if options.skip_thumbnail {
-            None
+        let (thumbnail, renditions) = if options.skip_thumbnail {
+            (None, Vec::new())
         } else {
-            self.thumbnail_gen.generate(&decoded.image)
+            (
+                self.thumbnail_gen.generate(&decoded.image),
+                self.thumbnail_gen
+                    .write_renditions(&decoded.image, &content_hash)
+                    .await,
+            )
         };
         let thumb_time = thumb_start.elapsed();
         tracing::trace!("  Thumbnail: {:?}", thumb_time);
 
+        // Compute BlurHash and ThumbHash placeholders
+        let placeholder_start = std::time::Instant::now();
+        let (blurhash, thumbhash) = if options.skip_placeholders {
+            (None, None)
+        } else {
+            (
+                self.placeholder_gen.blurhash(&decoded.image),
+                self.placeholder_gen.thumbhash(&decoded.image),
+            )
+        };
+        let placeholder_time = placeholder_start.elapsed();
+        tracing::trace!("  Placeholders: {:?}", placeholder_time);
+
+        // Extract dominant colors (k-means on a small downscaled copy)
+        let palette_start = std::time::Instant::now();
+        let colors = if options.skip_palette {
+            vec![]
+        } else {
+            self.palette.extract(&decoded.image)
+        };
+        let palette_time = palette_start.elapsed();
+        tracing::trace!("  Palette: {:?} ({} colors)", palette_time, colors.len());
+
+        // Compute quality metrics (sharpness, exposure, noise, colorfulness)
+        let quality_start = std::time::Instant::now();
+        let quality = if options.skip_quality {
+            None
+        } else {
+            self.quality.analyze(&decoded.image)
+        };
+        let quality_time = quality_start.elapsed();
+        tracing::trace!("  Quality: {:?}", quality_time);
+
+        // Too blurry to keep: don't spend the model stages on it
+        let below_sharpness = match (options.min_sharpness, &quality) {
+            (Some(min), Some(metrics)) => metrics.sharpness < min,
+            _ => false,
+        };
+        if below_sharpness {
+            tracing::debug!("  Below min_sharpness, skipping embedding and tagging");
+        }
+
         // Generate embedding (Phase 3)
         // Preprocess here (outside spawn_blocking) to avoid cloning the full
         // DynamicImage (~49MB for 4032x3024) into the blocking task. The
         // preprocessed tensor is only ~600KB (224x224x3xf32).
+        // Animations embed their sampled frames in one batch and use the
+        // pooled vector; the frames are moved into the task, not cloned.
         let embed_start = std::time::Instant::now();
-        let embedding = if options.skip_embedding {
-            vec![]
+        let (embedding, frame_embeddings) = if options.skip_embedding || below_sharpness {
+            (vec![], None)
         } else if let Some(engine) = &self.embedding_engine {
-            let tensor =
-                crate::embedding::preprocess::preprocess(&decoded.image, engine.image_size());
             let engine = Arc::clone(engine);
-            let timeout_duration = Duration::from_millis(self.embed_timeout_ms);
             let embed_path = path.to_path_buf();
-            let embed_path_inner = embed_path.clone();
-
-            let result = tokio::time::timeout(timeout_duration, async {
-                tokio::task::spawn_blocking(move || {
-                    engine.embed_preprocessed(&tensor, &embed_path_inner)
-                })
-                .await
-            })
-            .await;
-
-            match result {
-                Ok(Ok(Ok(emb))) => emb,
-                Ok(Ok(Err(e))) => return Err(e.into()),
-                Ok(Err(e)) => {
-                    return Err(PipelineError::Embedding {
-                        path: embed_path,
-                        message: format!("Embedding task panicked: {e}"),
-                    }
-                    .into())
-                }
-                Err(_) => {
-                    return Err(PipelineError::Timeout {
-                        path: embed_path,
-                        stage: "embed".to_string(),
-                        timeout_ms: self.embed_timeout_ms,
+            if decoded.frames.len() > 1 {
+                let frames = std::mem::take(&mut decoded.frames);
+                let (pooled, per_frame) = self
+                    .run_embedding(path, move || engine.embed_frames(&frames, &embed_path))
+                    .await?;
+                (pooled, self.frame_embeddings.then_some(per_frame))
+            } else {
+                let tensor = engine.preprocess(&decoded.image);
+                let embedding = match &self.embed_batcher {
+                    Some(batcher) => self.run_batched_embedding(batcher, tensor, path).await?,
+                    None => {
+                        self.run_embedding(path, move || {
+                            engine.embed_preprocessed(&tensor, &embed_path)
+                        })
+                        .await?
                     }
-                    .into())
-                }
+                };
+                (embedding, None)
             }
         } else {
-            vec![]
+            (vec![], None)
         };
         let embed_time = embed_start.elapsed();
         tracing::trace!("  Embed: {:?}", embed_time);
 
         // Generate tags using embedding (Phase 4)
         let tag_start = std::time::Instant::now();
-        let tags = if !options.skip_tagging {
+        let tags = if !options.skip_tagging && !below_sharpness {
             match (&self.tag_scorer, &self.relevance_tracker, &embedding) {
                 // Pool-aware scoring (relevance pruning enabled)
                 (Some(scorer_lock), Some(tracker_lock), emb) if !emb.is_empty() => {
@@ -575,21 +828,92 @@ impl ImageProcessor {
             }
         );
 
-        Ok(ProcessedImage {
-            file_path: path.to_path_buf(),
+        let mut image = ProcessedImage {
+            file_path: file_path.map(Path::to_path_buf),
+            source_path: None,
             file_name,
             content_hash,
             width: decoded.width,
             height: decoded.height,
-            format: format_to_string(decoded.format),
+            format: decoded.format_name(),
             file_size: decoded.file_size,
+            frame_count: decoded.frame_count,
+            duration_ms: decoded.duration_ms,
             embedding,
+            frame_embeddings,
             exif,
+            xmp,
+            location,
             tags,
             description: None, // Placeholder - Phase 5
+            thumbnail_format: thumbnail
+                .as_ref()
+                .map(|_| self.thumbnail_gen.format().to_string()),
             thumbnail,
+            renditions,
+            blurhash,
+            thumbhash,
             perceptual_hash,
-        })
+            perceptual_hashes,
+            colors,
+            quality,
+        };
+
+        // Redact last, so nothing downstream (writers, library callers) sees
+        // the original path, position or identifiers
+        if let Some(redactor) = self.redactor() {
+            redactor.redact(&mut image);
+        }
+
+        Ok(image)
+    }
+
+    /// Run an embedding job on the blocking pool, bounded by the embed timeout.
+    async fn run_embedding<T, F>(&self, path: &Path, job: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> std::result::Result<T, PipelineError> + Send + 'static,
+    {
+        let timeout_duration = Duration::from_millis(self.embed_timeout_ms);
+        let result = tokio::time::timeout(timeout_duration, tokio::task::spawn_blocking(job)).await;
+
+        match result {
+            Ok(Ok(Ok(output))) => Ok(output),
+            Ok(Ok(Err(e))) => Err(e.into()),
+            Ok(Err(e)) => Err(PipelineError::Embedding {
+                path: path.to_path_buf(),
+                message: format!("Embedding task panicked: {e}"),
+            }
+            .into()),
+            Err(_) => Err(PipelineError::Timeout {
+                path: path.to_path_buf(),
+                stage: "embed".to_string(),
+                timeout_ms: self.embed_timeout_ms,
+            }
+            .into()),
+        }
+    }
+
+    /// Queue a tensor on the batcher, bounded by the embed timeout (which
+    /// includes the time spent waiting for the batch to fill).
+    async fn run_batched_embedding(
+        &self,
+        batcher: &EmbedBatcher,
+        tensor: ndarray::Array4<f32>,
+        path: &Path,
+    ) -> Result<Vec<f32>> {
+        let timeout_duration = Duration::from_millis(self.embed_timeout_ms);
+        match tokio::time::timeout(timeout_duration, batcher.embed(tensor, path.to_path_buf()))
+            .await
+        {
+            Ok(result) => Ok(result?),
+            Err(_) => Err(PipelineError::Timeout {
+                path: path.to_path_buf(),
+                stage: "embed".to_string(),
+                timeout_ms: self.embed_timeout_ms,
+            }
+            .into()),
+        }
     }
 
     /// Discover all image files at a path.
@@ -597,10 +921,30 @@ impl ImageProcessor {
         self.discovery.discover(path)
     }
 
+    /// Discover image files, also reporting how many the discovery filters dropped.
+    pub fn discover_counted(&self, path: &Path) -> (Vec<DiscoveredFile>, FilterCounts) {
+        self.discovery.discover_counted(path)
+    }
+
+    /// Set the modification-time and size filters applied during discovery.
+    pub fn set_discovery_filters(&mut self, filters: DiscoveryFilters) {
+        self.discovery = self.discovery.clone().with_filters(filters);
+    }
+
     /// Check if thumbnail generation is enabled.
     pub fn thumbnails_enabled(&self) -> bool {
         self.thumbnail_gen.is_enabled()
     }
+
+    /// Check if BlurHash or ThumbHash placeholders are enabled.
+    pub fn placeholders_enabled(&self) -> bool {
+        self.placeholder_gen.is_enabled()
+    }
+
+    /// Check if image-quality metrics are enabled.
+    pub fn quality_enabled(&self) -> bool {
+        self.quality.is_enabled()
+    }
 }
 
 #[cfg(test)]
@@ -612,6 +956,9 @@ mod tests {
         let options = ProcessOptions::default();
         assert!(!options.skip_thumbnail);
         assert!(!options.skip_perceptual_hash);
+        assert!(!options.skip_placeholders);
+        assert!(!options.skip_palette);
+        assert!(!options.skip_quality);
         assert!(!options.skip_embedding);
     }
 }
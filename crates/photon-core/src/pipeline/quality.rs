@@ -0,0 +1,304 @@
+//! Technical image-quality metrics for culling: focus, exposure, noise, color.
+//!
+//! Everything is computed on one downscaled copy (`quality.sample_size` on
+//! the longest edge), so scores are comparable across cameras and resolutions
+//! and the cost stays flat. Intensities are on the 0–255 scale throughout.
+
+use image::{DynamicImage, RgbImage};
+
+use crate::config::QualityConfig;
+use crate::types::QualityMetrics;
+
+/// Number of luminance histogram bins.
+const HISTOGRAM_BINS: usize = 16;
+
+/// Luma at or above this counts as a clipped highlight.
+const HIGHLIGHT_CLIP: u8 = 250;
+
+/// Luma at or below this counts as a clipped shadow.
+const SHADOW_CLIP: u8 = 5;
+
+/// Computes sharpness, exposure, noise and colorfulness metrics.
+pub struct QualityAnalyzer {
+    config: QualityConfig,
+}
+
+impl QualityAnalyzer {
+    /// Create a new quality analyzer with the given configuration.
+    pub fn new(config: QualityConfig) -> Self {
+        Self { config }
+    }
+
+    /// Analyze an image. Returns `None` if the stage is disabled.
+    pub fn analyze(&self, image: &DynamicImage) -> Option<QualityMetrics> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let size = self.config.sample_size;
+        let rgb = if image.width() > size || image.height() > size {
+            image.thumbnail(size, size).to_rgb8()
+        } else {
+            image.to_rgb8()
+        };
+        let luma = luma_plane(&rgb);
+        let (width, height) = (rgb.width() as usize, rgb.height() as usize);
+
+        let pixel_count = luma.len().max(1) as f32;
+        let mut histogram = [0usize; HISTOGRAM_BINS];
+        let (mut highlights, mut shadows) = (0usize, 0usize);
+        for &value in &luma {
+            let byte = value.round() as u8;
+            histogram[byte as usize * HISTOGRAM_BINS / 256] += 1;
+            highlights += usize::from(byte >= HIGHLIGHT_CLIP);
+            shadows += usize::from(byte <= SHADOW_CLIP);
+        }
+
+        Some(QualityMetrics {
+            sharpness: round2(laplacian_variance(&luma, width, height)),
+            noise: round2(noise_sigma(&luma, width, height)),
+            colorfulness: round2(colorfulness(&rgb)),
+            mean_luminance: round2(luma.iter().sum::<f32>() / pixel_count),
+            clipped_highlights_pct: round2(highlights as f32 / pixel_count * 100.0),
+            clipped_shadows_pct: round2(shadows as f32 / pixel_count * 100.0),
+            luminance_histogram: histogram
+                .iter()
+                .map(|&count| (count as f32 / pixel_count * 10_000.0).round() / 10_000.0)
+                .collect(),
+        })
+    }
+
+    /// Check if quality analysis is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+}
+
+/// Rec. 709 luma of each pixel, row-major.
+fn luma_plane(rgb: &RgbImage) -> Vec<f32> {
+    rgb.pixels()
+        .map(|p| {
+            let [r, g, b] = p.0.map(f32::from);
+            0.2126 * r + 0.7152 * g + 0.0722 * b
+        })
+        .collect()
+}
+
+/// Variance of the 4-neighbor Laplacian: high for crisp edges, low when blurred.
+fn laplacian_variance(luma: &[f32], width: usize, height: usize) -> f32 {
+    if width < 3 || height < 3 {
+        return 0.0;
+    }
+    let mut sum = 0.0f64;
+    let mut sum_sq = 0.0f64;
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let i = y * width + x;
+            let response =
+                luma[i - width] + luma[i + width] + luma[i - 1] + luma[i + 1] - 4.0 * luma[i];
+            sum += f64::from(response);
+            sum_sq += f64::from(response) * f64::from(response);
+        }
+    }
+    let n = ((width - 2) * (height - 2)) as f64;
+    let mean = sum / n;
+    (sum_sq / n - mean * mean).max(0.0) as f32
+}
+
+/// Noise standard deviation estimate (Immerkær, "Fast Noise Variance
+/// Estimation", 1996): the mask below is the difference of two Laplacians,
+/// which cancels image structure and leaves mostly noise.
+fn noise_sigma(luma: &[f32], width: usize, height: usize) -> f32 {
+    if width < 3 || height < 3 {
+        return 0.0;
+    }
+    const MASK: [[f32; 3]; 3] = [[1.0, -2.0, 1.0], [-2.0, 4.0, -2.0], [1.0, -2.0, 1.0]];
+    let mut total = 0.0f64;
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let mut response = 0.0f32;
+            for (dy, row) in MASK.iter().enumerate() {
+                for (dx, weight) in row.iter().enumerate() {
+                    response += weight * luma[(y + dy - 1) * width + (x + dx - 1)];
+                }
+            }
+            total += f64::from(response.abs());
+        }
+    }
+    let n = ((width - 2) * (height - 2)) as f64;
+    ((std::f64::consts::FRAC_PI_2).sqrt() * total / (6.0 * n)) as f32
+}
+
+/// Hasler & Süsstrunk colorfulness ("Measuring colourfulness in natural
+/// images", 2003). Roughly: 0 grayscale, ~15 slightly, ~33 moderately,
+/// ~59 quite, 80+ highly colorful.
+fn colorfulness(rgb: &RgbImage) -> f32 {
+    let n = (rgb.width() * rgb.height()).max(1) as f64;
+    let (mut rg_sum, mut rg_sq, mut yb_sum, mut yb_sq) = (0.0f64, 0.0f64, 0.0f64, 0.0f64);
+    for p in rgb.pixels() {
+        let [r, g, b] = p.0.map(f64::from);
+        let rg = r - g;
+        let yb = 0.5 * (r + g) - b;
+        rg_sum += rg;
+        rg_sq += rg * rg;
+        yb_sum += yb;
+        yb_sq += yb * yb;
+    }
+    let (rg_mean, yb_mean) = (rg_sum / n, yb_sum / n);
+    let rg_var = (rg_sq / n - rg_mean * rg_mean).max(0.0);
+    let yb_var = (yb_sq / n - yb_mean * yb_mean).max(0.0);
+    ((rg_var + yb_var).sqrt() + 0.3 * (rg_mean * rg_mean + yb_mean * yb_mean).sqrt()) as f32
+}
+
+fn round2(value: f32) -> f32 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    fn analyzer() -> QualityAnalyzer {
+        QualityAnalyzer::new(QualityConfig {
+            enabled: true,
+            ..QualityConfig::default()
+        })
+    }
+
+    fn checkerboard(size: u32, cell: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(size, size, |x, y| {
+            if (x / cell + y / cell).is_multiple_of(2) {
+                Rgb([255, 255, 255])
+            } else {
+                Rgb([0, 0, 0])
+            }
+        }))
+    }
+
+    #[test]
+    fn test_blur_lowers_sharpness() {
+        let sharp = checkerboard(256, 16);
+        let blurred = sharp.blur(4.0);
+
+        let sharp = analyzer().analyze(&sharp).unwrap();
+        let blurred = analyzer().analyze(&blurred).unwrap();
+        assert!(
+            sharp.sharpness > blurred.sharpness * 10.0,
+            "{} vs {}",
+            sharp.sharpness,
+            blurred.sharpness
+        );
+    }
+
+    #[test]
+    fn test_flat_image() {
+        let gray = DynamicImage::ImageRgb8(RgbImage::from_pixel(64, 48, Rgb([128, 128, 128])));
+        let metrics = analyzer().analyze(&gray).unwrap();
+
+        assert_eq!(metrics.sharpness, 0.0);
+        assert_eq!(metrics.noise, 0.0);
+        assert_eq!(metrics.colorfulness, 0.0);
+        assert_eq!(metrics.mean_luminance, 128.0);
+        assert_eq!(metrics.clipped_highlights_pct, 0.0);
+        assert_eq!(metrics.clipped_shadows_pct, 0.0);
+        assert_eq!(metrics.luminance_histogram.len(), HISTOGRAM_BINS);
+        assert_eq!(metrics.luminance_histogram[8], 1.0);
+    }
+
+    #[test]
+    fn test_clipping_percentages() {
+        // Top quarter blown out, bottom quarter crushed
+        let image = DynamicImage::ImageRgb8(RgbImage::from_fn(40, 40, |_, y| match y {
+            0..10 => Rgb([255, 255, 255]),
+            30.. => Rgb([0, 0, 0]),
+            _ => Rgb([120, 120, 120]),
+        }));
+        let metrics = analyzer().analyze(&image).unwrap();
+
+        assert_eq!(metrics.clipped_highlights_pct, 25.0);
+        assert_eq!(metrics.clipped_shadows_pct, 25.0);
+        assert_eq!(metrics.luminance_histogram[0], 0.25);
+        assert_eq!(metrics.luminance_histogram[15], 0.25);
+        let total: f32 = metrics.luminance_histogram.iter().sum();
+        assert!((total - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_noise_estimate_tracks_added_noise() {
+        // Smooth gradient plus deterministic pseudo-random noise of known spread
+        let mut state = 12345u32;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state % 41) as f32 - 20.0
+        };
+        let clean = RgbImage::from_fn(128, 128, |x, _| Rgb([(x + 60) as u8; 3]));
+        let noisy = RgbImage::from_fn(128, 128, |x, _| {
+            let v = (x as f32 + 60.0 + next()).clamp(0.0, 255.0) as u8;
+            Rgb([v; 3])
+        });
+
+        let clean = analyzer().analyze(&DynamicImage::ImageRgb8(clean)).unwrap();
+        let noisy = analyzer().analyze(&DynamicImage::ImageRgb8(noisy)).unwrap();
+        assert!(clean.noise < 0.5, "clean noise {}", clean.noise);
+        // Uniform noise on [-20, 20] has a standard deviation of ~11.8
+        assert!(
+            (noisy.noise - 11.8).abs() < 2.0,
+            "noisy noise {}",
+            noisy.noise
+        );
+    }
+
+    #[test]
+    fn test_colorfulness_orders_images() {
+        let gray = checkerboard(64, 8);
+        let vivid = DynamicImage::ImageRgb8(RgbImage::from_fn(64, 64, |x, _| {
+            if x < 32 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        }));
+        let muted = DynamicImage::ImageRgb8(RgbImage::from_fn(64, 64, |x, _| {
+            if x < 32 {
+                Rgb([150, 110, 110])
+            } else {
+                Rgb([110, 110, 150])
+            }
+        }));
+
+        let gray = analyzer().analyze(&gray).unwrap().colorfulness;
+        let vivid = analyzer().analyze(&vivid).unwrap().colorfulness;
+        let muted = analyzer().analyze(&muted).unwrap().colorfulness;
+        assert_eq!(gray, 0.0);
+        assert!(vivid > 80.0, "vivid {vivid}");
+        assert!(muted < vivid);
+    }
+
+    #[test]
+    fn test_downscales_to_sample_size() {
+        // A 1px checkerboard averages out to flat gray at a quarter of the size
+        let analyzer = QualityAnalyzer::new(QualityConfig {
+            enabled: true,
+            sample_size: 64,
+        });
+        let metrics = analyzer.analyze(&checkerboard(256, 1)).unwrap();
+        assert!(metrics.sharpness < 100.0, "{}", metrics.sharpness);
+    }
+
+    #[test]
+    fn test_tiny_and_disabled() {
+        let metrics = analyzer().analyze(&DynamicImage::new_rgb8(1, 1)).unwrap();
+        assert_eq!(metrics.sharpness, 0.0);
+        assert_eq!(metrics.clipped_shadows_pct, 100.0);
+
+        let disabled = QualityAnalyzer::new(QualityConfig {
+            enabled: false,
+            ..QualityConfig::default()
+        });
+        assert!(!disabled.is_enabled());
+        assert!(disabled.analyze(&DynamicImage::new_rgb8(8, 8)).is_none());
+    }
+}
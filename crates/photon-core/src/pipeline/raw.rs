@@ -0,0 +1,490 @@
+//! Embedded JPEG preview extraction from TIFF-based camera RAW files.
+//!
+//! Full RAW development (demosaicing, white balance, color matrices) is out of
+//! scope for Photon. Instead, we walk the TIFF IFD tree and pull out the largest
+//! embedded baseline JPEG — every mainstream camera stores a full-size (or
+//! near full-size) preview rendered in-camera, which is exactly what tagging,
+//! hashing, and thumbnailing need.
+
+use std::path::Path;
+
+/// TIFF/EXIF/DNG tag IDs used while walking the IFD tree.
+const TAG_COMPRESSION: u16 = 0x0103;
+const TAG_MAKE: u16 = 0x010F;
+const TAG_STRIP_OFFSETS: u16 = 0x0111;
+const TAG_STRIP_BYTE_COUNTS: u16 = 0x0117;
+const TAG_SUB_IFDS: u16 = 0x014A;
+const TAG_JPEG_OFFSET: u16 = 0x0201;
+const TAG_JPEG_LENGTH: u16 = 0x0202;
+const TAG_EXIF_IFD: u16 = 0x8769;
+const TAG_DNG_VERSION: u16 = 0xC612;
+
+/// TIFF compression values that indicate JPEG-encoded strip data.
+const COMPRESSION_OLD_JPEG: u32 = 6;
+const COMPRESSION_JPEG: u32 = 7;
+
+/// Upper bound on IFDs visited, guarding against cyclic or malicious offsets.
+const MAX_IFDS: usize = 64;
+
+/// Camera RAW container types with embedded-preview support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawFormat {
+    /// Canon CR2
+    Cr2,
+    /// Nikon NEF
+    Nef,
+    /// Sony ARW
+    Arw,
+    /// Adobe Digital Negative
+    Dng,
+}
+
+impl RawFormat {
+    /// Lowercase container name as reported in `ProcessedImage.format`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RawFormat::Cr2 => "cr2",
+            RawFormat::Nef => "nef",
+            RawFormat::Arw => "arw",
+            RawFormat::Dng => "dng",
+        }
+    }
+
+    /// Map a file extension to a RAW format, if it names one.
+    fn from_extension(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "cr2" => Some(RawFormat::Cr2),
+            "nef" => Some(RawFormat::Nef),
+            "arw" => Some(RawFormat::Arw),
+            "dng" => Some(RawFormat::Dng),
+            _ => None,
+        }
+    }
+}
+
+/// Detect whether a buffer is a supported camera RAW container.
+///
+/// Content is checked first (CR2 signature, DNGVersion tag, camera make), with
+/// the file extension as a fallback. Cameras also write their make into
+/// ordinary TIFFs, so the make is only trusted for a RAW extension or a name
+/// with no extension at all. Plain TIFF files return `None` so they keep
+/// going through the regular `image` crate decoder.
+pub fn detect(bytes: &[u8], path: &Path) -> Option<RawFormat> {
+    let tiff = TiffReader::new(bytes)?;
+
+    // CR2: "CR" + major version 2 right after the TIFF header
+    if bytes.len() >= 10 && &bytes[8..10] == b"CR" {
+        return Some(RawFormat::Cr2);
+    }
+
+    let ifd0 = tiff.first_ifd_offset()?;
+    let entries = tiff.read_ifd(ifd0)?;
+    if entries.iter().any(|e| e.tag == TAG_DNG_VERSION) {
+        return Some(RawFormat::Dng);
+    }
+
+    let by_extension = RawFormat::from_extension(path);
+    if by_extension.is_none() && path.extension().is_some() {
+        return None;
+    }
+    if let Some(make) = entries
+        .iter()
+        .find(|e| e.tag == TAG_MAKE)
+        .and_then(|e| tiff.ascii(e))
+    {
+        let make = make.to_uppercase();
+        if make.starts_with("NIKON") {
+            return Some(RawFormat::Nef);
+        }
+        if make.starts_with("SONY") {
+            return Some(RawFormat::Arw);
+        }
+        if make.starts_with("CANON") {
+            return Some(RawFormat::Cr2);
+        }
+    }
+
+    by_extension
+}
+
+/// Extract the largest embedded baseline JPEG preview from a TIFF-based RAW file.
+///
+/// Walks IFD0's chain plus any SubIFDs and the EXIF IFD, collecting JPEG
+/// candidates from both `JPEGInterchangeFormat` tags and JPEG-compressed strips.
+/// Lossless JPEG streams (the actual sensor data in CR2/DNG) are skipped since
+/// they cannot be decoded by the `image` crate. Returns `None` if no usable
+/// preview is found.
+pub fn extract_preview(bytes: &[u8]) -> Option<&[u8]> {
+    let tiff = TiffReader::new(bytes)?;
+
+    let mut pending = vec![tiff.first_ifd_offset()?];
+    let mut visited = Vec::new();
+    let mut best: Option<(&[u8], u64)> = None;
+
+    while let Some(offset) = pending.pop() {
+        if offset == 0 || visited.contains(&offset) || visited.len() >= MAX_IFDS {
+            continue;
+        }
+        visited.push(offset);
+
+        let Some(entries) = tiff.read_ifd(offset) else {
+            continue;
+        };
+
+        let value = |tag: u16| entries.iter().find(|e| e.tag == tag);
+
+        // JPEGInterchangeFormat + length
+        if let (Some(off), Some(len)) = (value(TAG_JPEG_OFFSET), value(TAG_JPEG_LENGTH)) {
+            if let (Some(off), Some(len)) = (tiff.first_u32(off), tiff.first_u32(len)) {
+                consider(&mut best, slice(bytes, off, len));
+            }
+        }
+
+        // Single JPEG-compressed strip (CR2 IFD0, DNG previews)
+        let compression = value(TAG_COMPRESSION).and_then(|e| tiff.first_u32(e));
+        if matches!(
+            compression,
+            Some(COMPRESSION_OLD_JPEG) | Some(COMPRESSION_JPEG)
+        ) {
+            if let (Some(off), Some(len)) = (value(TAG_STRIP_OFFSETS), value(TAG_STRIP_BYTE_COUNTS))
+            {
+                if off.count == 1 {
+                    if let (Some(off), Some(len)) = (tiff.first_u32(off), tiff.first_u32(len)) {
+                        consider(&mut best, slice(bytes, off, len));
+                    }
+                }
+            }
+        }
+
+        // Descend into SubIFDs and the EXIF IFD, then follow the IFD chain
+        for entry in entries
+            .iter()
+            .filter(|e| e.tag == TAG_SUB_IFDS || e.tag == TAG_EXIF_IFD)
+        {
+            pending.extend(tiff.u32_values(entry));
+        }
+        if let Some(next) = tiff.next_ifd_offset(offset, entries.len()) {
+            pending.push(next);
+        }
+    }
+
+    best.map(|(jpeg, _)| jpeg)
+}
+
+/// Keep `candidate` if it is a decodable JPEG larger than the current best.
+fn consider<'a>(best: &mut Option<(&'a [u8], u64)>, candidate: Option<&'a [u8]>) {
+    let Some(jpeg) = candidate else {
+        return;
+    };
+    let Some((width, height)) = baseline_jpeg_dimensions(jpeg) else {
+        return;
+    };
+    let pixels = width as u64 * height as u64;
+    if best.is_none_or(|(_, best_pixels)| pixels > best_pixels) {
+        *best = Some((jpeg, pixels));
+    }
+}
+
+/// Bounds-checked sub-slice.
+fn slice(bytes: &[u8], offset: u32, len: u32) -> Option<&[u8]> {
+    let start = offset as usize;
+    let end = start.checked_add(len as usize)?;
+    bytes.get(start..end)
+}
+
+/// Read dimensions from a JPEG's SOF marker, accepting only baseline,
+/// extended-sequential, and progressive Huffman streams (SOF0–SOF2).
+fn baseline_jpeg_dimensions(jpeg: &[u8]) -> Option<(u32, u32)> {
+    if jpeg.len() < 4 || jpeg[0] != 0xFF || jpeg[1] != 0xD8 {
+        return None;
+    }
+
+    let mut pos = 2;
+    while pos + 4 <= jpeg.len() {
+        if jpeg[pos] != 0xFF {
+            return None;
+        }
+        let marker = jpeg[pos + 1];
+        // Fill bytes / standalone markers carry no length
+        if marker == 0xFF {
+            pos += 1;
+            continue;
+        }
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            pos += 2;
+            continue;
+        }
+        let len = u16::from_be_bytes([jpeg[pos + 2], jpeg[pos + 3]]) as usize;
+        match marker {
+            0xC0..=0xC2 => {
+                let sof = jpeg.get(pos + 4..pos + 9)?;
+                let height = u16::from_be_bytes([sof[1], sof[2]]) as u32;
+                let width = u16::from_be_bytes([sof[3], sof[4]]) as u32;
+                return (width > 0 && height > 0).then_some((width, height));
+            }
+            // Lossless, hierarchical, or arithmetic-coded — not decodable
+            0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => return None,
+            // Start of scan without a frame header
+            0xDA => return None,
+            _ => pos += 2 + len,
+        }
+    }
+    None
+}
+
+/// A single TIFF IFD entry (value or offset left unresolved).
+struct IfdEntry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    /// Byte offset of the 4-byte value/offset field within the buffer.
+    value_pos: usize,
+}
+
+/// Minimal endian-aware TIFF structure reader.
+struct TiffReader<'a> {
+    bytes: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> TiffReader<'a> {
+    /// Parse the TIFF header. Returns `None` if the buffer is not TIFF-based.
+    fn new(bytes: &'a [u8]) -> Option<Self> {
+        if bytes.len() < 8 {
+            return None;
+        }
+        let little_endian = match &bytes[0..4] {
+            [b'I', b'I', 0x2A, 0x00] => true,
+            [b'M', b'M', 0x00, 0x2A] => false,
+            _ => return None,
+        };
+        Some(Self {
+            bytes,
+            little_endian,
+        })
+    }
+
+    fn u16_at(&self, pos: usize) -> Option<u16> {
+        let b: [u8; 2] = self.bytes.get(pos..pos + 2)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u16::from_le_bytes(b)
+        } else {
+            u16::from_be_bytes(b)
+        })
+    }
+
+    fn u32_at(&self, pos: usize) -> Option<u32> {
+        let b: [u8; 4] = self.bytes.get(pos..pos + 4)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u32::from_le_bytes(b)
+        } else {
+            u32::from_be_bytes(b)
+        })
+    }
+
+    fn first_ifd_offset(&self) -> Option<u32> {
+        self.u32_at(4)
+    }
+
+    fn read_ifd(&self, offset: u32) -> Option<Vec<IfdEntry>> {
+        let base = offset as usize;
+        let count = self.u16_at(base)? as usize;
+        let mut entries = Vec::with_capacity(count);
+        for i in 0..count {
+            let pos = base + 2 + i * 12;
+            entries.push(IfdEntry {
+                tag: self.u16_at(pos)?,
+                field_type: self.u16_at(pos + 2)?,
+                count: self.u32_at(pos + 4)?,
+                value_pos: pos + 8,
+            });
+        }
+        Some(entries)
+    }
+
+    fn next_ifd_offset(&self, offset: u32, entry_count: usize) -> Option<u32> {
+        self.u32_at(offset as usize + 2 + entry_count * 12)
+            .filter(|&next| next != 0)
+    }
+
+    /// Size in bytes of one value of the given TIFF field type.
+    fn type_size(field_type: u16) -> Option<usize> {
+        match field_type {
+            1 | 2 | 6 | 7 => Some(1),
+            3 | 8 => Some(2),
+            4 | 9 | 13 => Some(4),
+            _ => None,
+        }
+    }
+
+    /// Position of the entry's data (inline if it fits in 4 bytes).
+    fn data_pos(&self, entry: &IfdEntry) -> Option<usize> {
+        let total = Self::type_size(entry.field_type)?.checked_mul(entry.count as usize)?;
+        if total <= 4 {
+            Some(entry.value_pos)
+        } else {
+            self.u32_at(entry.value_pos).map(|o| o as usize)
+        }
+    }
+
+    /// All SHORT/LONG/IFD values of an entry as u32.
+    fn u32_values(&self, entry: &IfdEntry) -> Vec<u32> {
+        let Some(pos) = self.data_pos(entry) else {
+            return vec![];
+        };
+        (0..entry.count as usize)
+            .filter_map(|i| match entry.field_type {
+                3 => self.u16_at(pos + i * 2).map(u32::from),
+                4 | 13 => self.u32_at(pos + i * 4),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn first_u32(&self, entry: &IfdEntry) -> Option<u32> {
+        self.u32_values(entry).first().copied()
+    }
+
+    fn ascii(&self, entry: &IfdEntry) -> Option<String> {
+        if entry.field_type != 2 {
+            return None;
+        }
+        let pos = self.data_pos(entry)?;
+        let raw = self.bytes.get(pos..pos + entry.count as usize)?;
+        let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+        Some(String::from_utf8_lossy(&raw[..end]).trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, ImageFormat, RgbImage};
+    use std::io::Cursor;
+
+    /// Encode a solid-color JPEG of the given size.
+    fn jpeg(width: u32, height: u32) -> Vec<u8> {
+        let img = DynamicImage::ImageRgb8(RgbImage::new(width, height));
+        let mut buf = Cursor::new(Vec::new());
+        img.write_to(&mut buf, ImageFormat::Jpeg).unwrap();
+        buf.into_inner()
+    }
+
+    /// Build a little-endian TIFF with IFD0 pointing at a small JPEG via
+    /// JPEGInterchangeFormat and IFD1 holding a larger JPEG strip.
+    fn synthetic_raw(make: &str, small: &[u8], large: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(b"II\x2A\x00");
+        out.extend_from_slice(&8u32.to_le_bytes());
+
+        let entry = |out: &mut Vec<u8>, tag: u16, ty: u16, count: u32, value: u32| {
+            out.extend_from_slice(&tag.to_le_bytes());
+            out.extend_from_slice(&ty.to_le_bytes());
+            out.extend_from_slice(&count.to_le_bytes());
+            out.extend_from_slice(&value.to_le_bytes());
+        };
+
+        // Layout: IFD0 (4 entries) @8, IFD1 (3 entries) after it, then data
+        let ifd0_len = 2 + 4 * 12 + 4;
+        let ifd1_off = 8 + ifd0_len;
+        let ifd1_len = 2 + 3 * 12 + 4;
+        let make_off = ifd1_off + ifd1_len;
+        let make_bytes = format!("{make}\0");
+        let small_off = make_off + make_bytes.len();
+        let large_off = small_off + small.len();
+
+        out.extend_from_slice(&4u16.to_le_bytes());
+        entry(
+            &mut out,
+            TAG_MAKE,
+            2,
+            make_bytes.len() as u32,
+            make_off as u32,
+        );
+        entry(&mut out, TAG_JPEG_OFFSET, 4, 1, small_off as u32);
+        entry(&mut out, TAG_JPEG_LENGTH, 4, 1, small.len() as u32);
+        entry(&mut out, 0x0100, 4, 1, 1); // ImageWidth (ignored)
+        out.extend_from_slice(&(ifd1_off as u32).to_le_bytes());
+
+        out.extend_from_slice(&3u16.to_le_bytes());
+        entry(&mut out, TAG_COMPRESSION, 3, 1, COMPRESSION_OLD_JPEG);
+        entry(&mut out, TAG_STRIP_OFFSETS, 4, 1, large_off as u32);
+        entry(&mut out, TAG_STRIP_BYTE_COUNTS, 4, 1, large.len() as u32);
+        out.extend_from_slice(&0u32.to_le_bytes());
+
+        out.extend_from_slice(make_bytes.as_bytes());
+        out.extend_from_slice(small);
+        out.extend_from_slice(large);
+        out
+    }
+
+    #[test]
+    fn test_extract_preview_picks_largest_jpeg() {
+        let small = jpeg(16, 8);
+        let large = jpeg(64, 48);
+        let raw = synthetic_raw("NIKON CORPORATION", &small, &large);
+
+        let preview = extract_preview(&raw).unwrap();
+        assert_eq!(preview, &large[..]);
+        let decoded = image::load_from_memory(preview).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (64, 48));
+    }
+
+    #[test]
+    fn test_detect_by_make() {
+        let raw = synthetic_raw("NIKON CORPORATION", &jpeg(8, 8), &jpeg(16, 16));
+        assert_eq!(detect(&raw, Path::new("DSC_0001")), Some(RawFormat::Nef));
+        assert_eq!(
+            detect(&raw, Path::new("DSC_0001.arw")),
+            Some(RawFormat::Nef)
+        );
+
+        let raw = synthetic_raw("SONY", &jpeg(8, 8), &jpeg(16, 16));
+        assert_eq!(detect(&raw, Path::new("DSC_0001")), Some(RawFormat::Arw));
+    }
+
+    #[test]
+    fn test_detect_make_ignored_for_other_extensions() {
+        let raw = synthetic_raw("NIKON CORPORATION", &jpeg(8, 8), &jpeg(16, 16));
+        assert_eq!(detect(&raw, Path::new("scan.tif")), None);
+        assert_eq!(detect(&raw, Path::new("a.bin")), None);
+    }
+
+    #[test]
+    fn test_detect_cr2_signature() {
+        // TIFF header, IFD0 at 16, then the "CR" v2 marker at offset 8
+        let raw = b"II\x2A\x00\x10\x00\x00\x00CR\x02\x00\x00\x00\x00\x00".to_vec();
+        assert_eq!(detect(&raw, Path::new("a.bin")), Some(RawFormat::Cr2));
+    }
+
+    #[test]
+    fn test_detect_plain_tiff_is_not_raw() {
+        let raw = synthetic_raw("Generic Scanner", &jpeg(8, 8), &jpeg(16, 16));
+        assert_eq!(detect(&raw, Path::new("scan.tif")), None);
+        assert_eq!(detect(&raw, Path::new("scan.dng")), Some(RawFormat::Dng));
+    }
+
+    #[test]
+    fn test_detect_non_tiff() {
+        assert_eq!(detect(&jpeg(8, 8), Path::new("photo.nef")), None);
+    }
+
+    #[test]
+    fn test_baseline_jpeg_rejects_lossless() {
+        // SOI + SOF3 (lossless) header
+        let lossless = [
+            0xFF, 0xD8, 0xFF, 0xC3, 0x00, 0x0B, 0x08, 0x00, 0x10, 0x00, 0x10, 0x01, 0x01, 0x11,
+            0x00,
+        ];
+        assert!(baseline_jpeg_dimensions(&lossless).is_none());
+        assert_eq!(baseline_jpeg_dimensions(&jpeg(40, 30)), Some((40, 30)));
+    }
+
+    #[test]
+    fn test_extract_preview_truncated_input() {
+        let raw = synthetic_raw("NIKON", &jpeg(8, 8), &jpeg(16, 16));
+        assert!(extract_preview(&raw[..20]).is_none());
+        assert!(extract_preview(b"not a tiff").is_none());
+    }
+}
@@ -1,56 +1,272 @@
-//! Thumbnail generation with WebP output.
+//! Thumbnail generation in WebP, JPEG, PNG or AVIF.
+//!
+//! The inline thumbnail is returned as base64; named renditions are written
+//! to `thumbnail.output_dir` as `<content_hash>_<name>.<ext>`, so files are
+//! content-addressed and a re-run overwrites them with identical bytes.
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
-use image::{DynamicImage, ImageFormat};
+use image::codecs::avif::AvifEncoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, ImageResult};
 use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use crate::config::ThumbnailConfig;
+use crate::config::{RenditionConfig, ThumbnailConfig};
+use crate::types::Rendition;
+
+/// Accepted values for `thumbnail.format` and rendition `format`.
+pub const THUMBNAIL_FORMATS: &[&str] = &["webp", "jpeg", "png", "avif"];
+
+/// Accepted values for rendition `fit`.
+pub const RENDITION_FITS: &[&str] = &["fit", "cover", "square"];
+
+/// AVIF encoder speed (1 = slowest/smallest, 10 = fastest). Thumbnails are
+/// small, so a fast setting costs little in size.
+const AVIF_SPEED: u8 = 8;
+
+/// Thumbnail encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ThumbnailFormat {
+    /// Lossless WebP (the `image` crate has no lossy WebP encoder)
+    WebP,
+    Jpeg,
+    Png,
+    Avif,
+}
+
+impl ThumbnailFormat {
+    /// Parse a `thumbnail.format` value.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "webp" => Some(Self::WebP),
+            "jpeg" => Some(Self::Jpeg),
+            "png" => Some(Self::Png),
+            "avif" => Some(Self::Avif),
+            _ => None,
+        }
+    }
+
+    /// The config name, also emitted as `thumbnail_format`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::WebP => "webp",
+            Self::Jpeg => "jpeg",
+            Self::Png => "png",
+            Self::Avif => "avif",
+        }
+    }
+
+    /// File extension for rendition files.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            other => other.name(),
+        }
+    }
+
+    /// Encode an image. `quality` (1-100) applies to JPEG and AVIF.
+    pub fn encode(self, image: &DynamicImage, quality: u8) -> ImageResult<Vec<u8>> {
+        let mut buffer = Cursor::new(Vec::new());
+        match self {
+            Self::WebP => image.write_to(&mut buffer, ImageFormat::WebP)?,
+            Self::Png => image.write_to(&mut buffer, ImageFormat::Png)?,
+            // JPEG has no alpha channel
+            Self::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+                .write_with_encoder(JpegEncoder::new_with_quality(&mut buffer, quality))?,
+            Self::Avif => {
+                let encoder = AvifEncoder::new_with_speed_quality(&mut buffer, AVIF_SPEED, quality);
+                if image.color().has_alpha() {
+                    DynamicImage::ImageRgba8(image.to_rgba8()).write_with_encoder(encoder)?
+                } else {
+                    DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(encoder)?
+                }
+            }
+        }
+        Ok(buffer.into_inner())
+    }
+}
+
+/// How a rendition is sized into its box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resize {
+    /// Inside the box, aspect ratio kept
+    Fit,
+    /// Fill the box, center-cropping the overflow
+    Cover,
+    /// Center crop to a `size` × `size` square
+    Square,
+}
+
+/// A validated rendition ready to render.
+struct RenditionSpec {
+    name: String,
+    width: u32,
+    height: u32,
+    format: ThumbnailFormat,
+    fit: Resize,
+    quality: u8,
+}
+
+impl RenditionSpec {
+    fn new(config: &RenditionConfig) -> Self {
+        let fit = match config.fit.as_str() {
+            "cover" => Resize::Cover,
+            "square" => Resize::Square,
+            _ => Resize::Fit,
+        };
+        let height = match fit {
+            Resize::Square => config.size,
+            Resize::Fit | Resize::Cover => config.height.unwrap_or(config.size),
+        };
+        Self {
+            name: config.name.clone(),
+            width: config.size,
+            height,
+            format: ThumbnailFormat::parse(&config.format).unwrap_or(ThumbnailFormat::WebP),
+            fit,
+            quality: config.quality,
+        }
+    }
+
+    fn resize(&self, image: &DynamicImage) -> DynamicImage {
+        match self.fit {
+            Resize::Fit => image.thumbnail(self.width, self.height),
+            Resize::Cover | Resize::Square => {
+                image.resize_to_fill(self.width, self.height, FilterType::CatmullRom)
+            }
+        }
+    }
+}
 
 /// Generates thumbnails from images.
 pub struct ThumbnailGenerator {
     config: ThumbnailConfig,
+    format: ThumbnailFormat,
+    output_dir: PathBuf,
+    renditions: Vec<RenditionSpec>,
 }
 
 impl ThumbnailGenerator {
     /// Create a new thumbnail generator with the given configuration.
+    ///
+    /// An unknown `format` (only possible if the config was not validated)
+    /// falls back to WebP.
     pub fn new(config: ThumbnailConfig) -> Self {
-        Self { config }
+        let format = ThumbnailFormat::parse(&config.format).unwrap_or(ThumbnailFormat::WebP);
+        let output_dir = PathBuf::from(shellexpand::tilde(&config.output_dir).into_owned());
+        let renditions = config.renditions.iter().map(RenditionSpec::new).collect();
+        Self {
+            config,
+            format,
+            output_dir,
+            renditions,
+        }
     }
 
-    /// Generate a thumbnail and return it as a base64-encoded WebP string.
+    /// Generate a thumbnail and return it as a base64-encoded string in the
+    /// configured format (see [`format`](Self::format)).
     ///
-    /// Returns `None` if thumbnail generation is disabled or fails.
+    /// Returns `None` if thumbnail generation is disabled, `inline` is off,
+    /// or encoding fails.
     pub fn generate(&self, image: &DynamicImage) -> Option<String> {
-        if !self.config.enabled {
+        if !self.config.inline {
             return None;
         }
+        self.generate_bytes(image).map(|bytes| BASE64.encode(bytes))
+    }
 
-        // Resize maintaining aspect ratio (longest edge = config.size)
-        let thumbnail = image.thumbnail(self.config.size, self.config.size);
+    /// Render every configured rendition and write it to the output directory.
+    ///
+    /// Resizing happens on the caller's thread like the other stages; encoding
+    /// (slow for AVIF) and the file writes run on the blocking pool. Each file
+    /// is written under a temporary name and renamed into place, so a reader
+    /// never sees a partial rendition. Renditions that fail to encode or write
+    /// are logged and left out, so the result only lists files that exist.
+    pub async fn write_renditions(
+        &self,
+        image: &DynamicImage,
+        content_hash: &str,
+    ) -> Vec<Rendition> {
+        if !self.config.enabled || self.renditions.is_empty() {
+            return Vec::new();
+        }
 
-        // Encode to WebP
-        let mut buffer = Cursor::new(Vec::new());
-        thumbnail.write_to(&mut buffer, ImageFormat::WebP).ok()?;
+        let resized: Vec<(Rendition, ThumbnailFormat, u8, DynamicImage)> = self
+            .renditions
+            .iter()
+            .map(|spec| {
+                let resized = spec.resize(image);
+                let rendition = Rendition {
+                    name: spec.name.clone(),
+                    path: self.output_dir.join(format!(
+                        "{content_hash}_{}.{}",
+                        spec.name,
+                        spec.format.extension()
+                    )),
+                    format: spec.format.name().to_string(),
+                    width: resized.width(),
+                    height: resized.height(),
+                };
+                (rendition, spec.format, spec.quality, resized)
+            })
+            .collect();
+        let output_dir = self.output_dir.clone();
 
-        // Return as base64
-        Some(BASE64.encode(buffer.into_inner()))
+        let task = tokio::task::spawn_blocking(move || {
+            if let Err(e) = std::fs::create_dir_all(&output_dir) {
+                tracing::warn!("Cannot create thumbnail directory {:?}: {e}", output_dir);
+                return Vec::new();
+            }
+            resized
+                .into_iter()
+                .filter_map(|(rendition, format, quality, image)| {
+                    let result = format
+                        .encode(&image, quality)
+                        .map_err(|e| e.to_string())
+                        .and_then(|bytes| {
+                            write_atomic(&rendition.path, &bytes).map_err(|e| e.to_string())
+                        });
+                    match result {
+                        Ok(()) => Some(rendition),
+                        Err(e) => {
+                            tracing::warn!("Failed to write rendition {:?}: {e}", rendition.path);
+                            None
+                        }
+                    }
+                })
+                .collect()
+        });
+        task.await.unwrap_or_else(|e| {
+            tracing::warn!("Rendition task failed: {e}");
+            Vec::new()
+        })
     }
 
     /// Generate a thumbnail and return the raw bytes.
     ///
     /// Useful for writing directly to disk.
-    #[cfg(test)]
     pub fn generate_bytes(&self, image: &DynamicImage) -> Option<Vec<u8>> {
         if !self.config.enabled {
             return None;
         }
 
+        // Resize maintaining aspect ratio (longest edge = config.size)
         let thumbnail = image.thumbnail(self.config.size, self.config.size);
 
-        let mut buffer = Cursor::new(Vec::new());
-        thumbnail.write_to(&mut buffer, ImageFormat::WebP).ok()?;
+        match self.format.encode(&thumbnail, self.config.quality) {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                tracing::warn!("Failed to encode {} thumbnail: {e}", self.format.name());
+                None
+            }
+        }
+    }
 
-        Some(buffer.into_inner())
+    /// The thumbnail encoding: "webp", "jpeg", "png" or "avif".
+    pub fn format(&self) -> &'static str {
+        self.format.name()
     }
 
     /// Check if thumbnail generation is enabled.
@@ -59,10 +275,35 @@ impl ThumbnailGenerator {
     }
 }
 
+/// Write `bytes` to `path` through a temporary file in the same directory,
+/// renamed into place once complete.
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    // Unique per write: concurrent workers can render the same content hash
+    static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = path.with_file_name(format!(
+        ".{file_name}.{}-{}.tmp",
+        std::process::id(),
+        NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = std::fs::write(&temp, bytes).and_then(|()| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::ThumbnailConfig;
+
+    fn generator(format: &str) -> ThumbnailGenerator {
+        ThumbnailGenerator::new(ThumbnailConfig {
+            size: 64,
+            format: format.to_string(),
+            ..ThumbnailConfig::default()
+        })
+    }
 
     #[test]
     fn test_thumbnail_generation() {
@@ -70,6 +311,8 @@ mod tests {
             enabled: true,
             size: 128,
             format: "webp".to_string(),
+            quality: 80,
+            ..ThumbnailConfig::default()
         };
         let generator = ThumbnailGenerator::new(config);
 
@@ -86,8 +329,7 @@ mod tests {
     fn test_thumbnail_disabled() {
         let config = ThumbnailConfig {
             enabled: false,
-            size: 128,
-            format: "webp".to_string(),
+            ..ThumbnailConfig::default()
         };
         let generator = ThumbnailGenerator::new(config);
 
@@ -99,12 +341,7 @@ mod tests {
 
     #[test]
     fn test_thumbnail_bytes() {
-        let config = ThumbnailConfig {
-            enabled: true,
-            size: 64,
-            format: "webp".to_string(),
-        };
-        let generator = ThumbnailGenerator::new(config);
+        let generator = generator("webp");
 
         let img = DynamicImage::new_rgb8(200, 200);
         let bytes = generator.generate_bytes(&img);
@@ -113,5 +350,138 @@ mod tests {
         let bytes = bytes.unwrap();
         // WebP files start with "RIFF"
         assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(generator.format(), "webp");
+    }
+
+    #[test]
+    fn test_thumbnail_formats_have_matching_signatures() {
+        let img = DynamicImage::new_rgba8(200, 100);
+
+        let jpeg = generator("jpeg").generate_bytes(&img).unwrap();
+        assert_eq!(&jpeg[0..3], &[0xFF, 0xD8, 0xFF]);
+
+        let png = generator("png").generate_bytes(&img).unwrap();
+        assert_eq!(&png[0..8], b"\x89PNG\r\n\x1a\n");
+
+        let avif = generator("avif").generate_bytes(&img).unwrap();
+        assert_eq!(&avif[4..12], b"ftypavif");
+
+        let decoded = image::load_from_memory(&png).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (64, 32));
+    }
+
+    #[test]
+    fn test_jpeg_quality_changes_size() {
+        // Noisy content so quality actually matters
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(256, 256, |x, y| {
+            image::Rgb([(x * 7 + y * 13) as u8, (x * y) as u8, (x ^ y) as u8])
+        }));
+        let encode = |quality| {
+            ThumbnailGenerator::new(ThumbnailConfig {
+                format: "jpeg".to_string(),
+                quality,
+                ..ThumbnailConfig::default()
+            })
+            .generate_bytes(&img)
+            .unwrap()
+            .len()
+        };
+        assert!(encode(20) < encode(95));
+    }
+
+    fn rendition(name: &str, size: u32, fit: &str) -> RenditionConfig {
+        RenditionConfig {
+            name: name.to_string(),
+            size,
+            fit: fit.to_string(),
+            ..RenditionConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_renditions() {
+        let dir = tempfile::tempdir().unwrap();
+        let generator = ThumbnailGenerator::new(ThumbnailConfig {
+            inline: false,
+            output_dir: dir.path().to_string_lossy().into_owned(),
+            renditions: vec![
+                rendition("fit", 100, "fit"),
+                RenditionConfig {
+                    height: Some(50),
+                    format: "jpeg".to_string(),
+                    ..rendition("banner", 200, "cover")
+                },
+                RenditionConfig {
+                    format: "png".to_string(),
+                    ..rendition("sq", 64, "square")
+                },
+            ],
+            ..ThumbnailConfig::default()
+        });
+        let img = DynamicImage::new_rgb8(400, 300);
+
+        assert!(generator.generate(&img).is_none());
+        let renditions = generator.write_renditions(&img, "abc").await;
+        let summary: Vec<(&str, &str, u32, u32)> = renditions
+            .iter()
+            .map(|r| (r.name.as_str(), r.format.as_str(), r.width, r.height))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("fit", "webp", 100, 75),
+                ("banner", "jpeg", 200, 50),
+                ("sq", "png", 64, 64)
+            ]
+        );
+
+        assert_eq!(renditions[1].path, dir.path().join("abc_banner.jpg"));
+        for rendition in &renditions {
+            let decoded = image::open(&rendition.path).unwrap();
+            assert_eq!(
+                (decoded.width(), decoded.height()),
+                (rendition.width, rendition.height)
+            );
+        }
+        // Only the renditions themselves: no temporary files left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_write_renditions_creates_dir_and_skips_when_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a/b");
+        let config = ThumbnailConfig {
+            output_dir: nested.to_string_lossy().into_owned(),
+            renditions: vec![rendition("small", 32, "fit")],
+            ..ThumbnailConfig::default()
+        };
+        let img = DynamicImage::new_rgb8(64, 64);
+
+        let disabled = ThumbnailGenerator::new(ThumbnailConfig {
+            enabled: false,
+            ..config.clone()
+        });
+        assert!(disabled.write_renditions(&img, "abc").await.is_empty());
+        assert!(!nested.exists());
+
+        let renditions = ThumbnailGenerator::new(config)
+            .write_renditions(&img, "abc")
+            .await;
+        assert_eq!(renditions.len(), 1);
+        assert!(nested.join("abc_small.webp").is_file());
+        // No renditions configured → nothing written
+        assert!(generator("webp")
+            .write_renditions(&img, "abc")
+            .await
+            .is_empty());
+    }
+
+    #[test]
+    fn test_format_names_round_trip() {
+        for name in THUMBNAIL_FORMATS {
+            assert_eq!(ThumbnailFormat::parse(name).unwrap().name(), *name);
+        }
+        assert!(ThumbnailFormat::parse("gif").is_none());
     }
 }
@@ -50,6 +50,25 @@ impl Validator {
         Ok(())
     }
 
+    /// Perform the same checks as [`Validator::validate`] on an in-memory buffer.
+    ///
+    /// `name` is only used to label errors.
+    pub fn validate_bytes(&self, bytes: &[u8], name: &Path) -> Result<(), PipelineError> {
+        let max_bytes = self.limits.max_file_size_mb * 1024 * 1024;
+        if bytes.len() as u64 > max_bytes {
+            return Err(PipelineError::FileTooLarge {
+                path: name.to_path_buf(),
+                size_mb: bytes.len() as u64 / (1024 * 1024),
+                max_mb: self.limits.max_file_size_mb,
+            });
+        }
+
+        let mut header = [0u8; 12];
+        let bytes_read = bytes.len().min(header.len());
+        header[..bytes_read].copy_from_slice(&bytes[..bytes_read]);
+        Self::check_header(&header, bytes_read, name)
+    }
+
     /// Check file magic bytes to verify it's a valid image format.
     fn check_magic_bytes(&self, path: &Path) -> Result<(), PipelineError> {
         let mut file = std::fs::File::open(path).map_err(|e| PipelineError::Decode {
@@ -63,6 +82,15 @@ impl Validator {
             message: format!("Failed to read file header: {e}"),
         })?;
 
+        Self::check_header(&header, bytes_read, path)
+    }
+
+    /// Reject headers that are too short or don't match a known image format.
+    fn check_header(
+        header: &[u8; 12],
+        bytes_read: usize,
+        path: &Path,
+    ) -> Result<(), PipelineError> {
         if bytes_read < 4 {
             return Err(PipelineError::Decode {
                 path: path.to_path_buf(),
@@ -71,7 +99,7 @@ impl Validator {
         }
 
         // Check common format signatures
-        let is_valid = Self::is_valid_image_header(&header, bytes_read);
+        let is_valid = Self::is_valid_image_header(header, bytes_read);
 
         if !is_valid {
             return Err(PipelineError::Decode {
@@ -188,6 +216,44 @@ mod tests {
         assert!(Validator::is_valid_image_header(&header, 12));
     }
 
+    #[test]
+    fn test_magic_bytes_cr2() {
+        // Canon CR2: little-endian TIFF header followed by "CR" + version 2
+        let header = [
+            b'I', b'I', 0x2A, 0x00, 0x10, 0, 0, 0, b'C', b'R', 0x02, 0x00,
+        ];
+        assert!(Validator::is_valid_image_header(&header, 12));
+    }
+
+    #[test]
+    fn test_validate_bytes() {
+        let validator = Validator::new(LimitsConfig::default());
+        let name = Path::new("upload.png");
+        let png = [
+            0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0, 0,
+        ];
+        assert!(validator.validate_bytes(&png, name).is_ok());
+
+        let err = validator.validate_bytes(b"not an image", name).unwrap_err();
+        assert!(err.to_string().contains("invalid magic bytes"));
+
+        let err = validator.validate_bytes(b"GI", name).unwrap_err();
+        assert!(err.to_string().contains("too small"));
+    }
+
+    #[test]
+    fn test_validate_bytes_rejects_oversized_buffer() {
+        let validator = Validator::new(LimitsConfig {
+            max_file_size_mb: 1,
+            ..LimitsConfig::default()
+        });
+        let bytes = vec![0xFF; 1024 * 1024 + 1];
+        let err = validator
+            .validate_bytes(&bytes, Path::new("big.jpg"))
+            .unwrap_err();
+        assert!(matches!(err, PipelineError::FileTooLarge { .. }));
+    }
+
     #[test]
     fn test_magic_bytes_bare_ii_rejected() {
         // Bare "II" without TIFF version bytes should not match
@@ -0,0 +1,183 @@
+//! XMP packet parsing for descriptive metadata (title, caption, keywords, ...).
+//!
+//! XMP is an RDF/XML packet embedded verbatim in the file (JPEG APP1, PNG
+//! iTXt, the WebP `XMP ` chunk, TIFF/DNG tag 700, HEIF `mime` items), so the
+//! packet is located with a byte scan rather than per-container parsing.
+//! Compressed PNG iTXt packets are not found.
+
+use memchr::memmem;
+use roxmltree::{Document, Node};
+
+use crate::types::XmpData;
+
+const RDF_NS: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+const XMP_NS: &str = "http://ns.adobe.com/xap/1.0/";
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Locate the XMP packet (`<x:xmpmeta>`, or a bare `<rdf:RDF>`) in file bytes.
+pub(crate) fn find_packet(bytes: &[u8]) -> Option<&str> {
+    for (open, close) in [("<x:xmpmeta", "</x:xmpmeta>"), ("<rdf:RDF", "</rdf:RDF>")] {
+        if let Some(start) = memmem::find(bytes, open.as_bytes()) {
+            let len = memmem::find(&bytes[start..], close.as_bytes())? + close.len();
+            return std::str::from_utf8(&bytes[start..start + len]).ok();
+        }
+    }
+    None
+}
+
+/// Parse the descriptive fields of an XMP packet.
+///
+/// Returns `None` if the packet is not well-formed XML or has none of the fields.
+pub(crate) fn parse(packet: &str) -> Option<XmpData> {
+    let doc = Document::parse(packet).ok()?;
+
+    let creators = list(&doc, DC_NS, "creator");
+    let data = XmpData {
+        title: alt_text(&doc, DC_NS, "title"),
+        caption: alt_text(&doc, DC_NS, "description"),
+        keywords: list(&doc, DC_NS, "subject"),
+        creator: (!creators.is_empty()).then(|| creators.join("; ")),
+        copyright: alt_text(&doc, DC_NS, "rights"),
+        rating: simple(&doc, XMP_NS, "Rating")
+            .and_then(|r| r.parse::<f32>().ok())
+            .map(|r| r.round() as i32),
+        label: simple(&doc, XMP_NS, "Label"),
+    };
+
+    (data != XmpData::default()).then_some(data)
+}
+
+/// First element with the given expanded name.
+fn property<'a, 'i>(doc: &'a Document<'i>, ns: &str, name: &str) -> Option<Node<'a, 'i>> {
+    doc.descendants().find(|n| {
+        n.is_element() && n.tag_name().namespace() == Some(ns) && n.tag_name().name() == name
+    })
+}
+
+/// A simple property, written either as an attribute of `rdf:Description`
+/// (the compact form) or as an element.
+fn simple(doc: &Document, ns: &str, name: &str) -> Option<String> {
+    doc.descendants()
+        .find_map(|n| n.attribute((ns, name)))
+        .or_else(|| property(doc, ns, name).and_then(|n| n.text()))
+        .and_then(non_empty)
+}
+
+/// Items of an `rdf:Bag` / `rdf:Seq` array property.
+fn list(doc: &Document, ns: &str, name: &str) -> Vec<String> {
+    let Some(prop) = property(doc, ns, name) else {
+        return simple(doc, ns, name).into_iter().collect();
+    };
+    let items: Vec<String> = items(prop)
+        .filter_map(|n| n.text().and_then(non_empty))
+        .collect();
+    if items.is_empty() {
+        prop.text().and_then(non_empty).into_iter().collect()
+    } else {
+        items
+    }
+}
+
+/// A language alternative (`rdf:Alt`), preferring the `x-default` entry.
+fn alt_text(doc: &Document, ns: &str, name: &str) -> Option<String> {
+    let Some(prop) = property(doc, ns, name) else {
+        return simple(doc, ns, name);
+    };
+    let alternatives: Vec<Node> = items(prop).collect();
+    let chosen = alternatives
+        .iter()
+        .find(|n| n.attribute((XML_NS, "lang")) == Some("x-default"))
+        .or(alternatives.first());
+    match chosen {
+        Some(item) => item.text().and_then(non_empty),
+        None => prop.text().and_then(non_empty),
+    }
+}
+
+/// The `rdf:li` items below a property element.
+fn items<'a, 'i: 'a>(prop: Node<'a, 'i>) -> impl Iterator<Item = Node<'a, 'i>> {
+    prop.descendants()
+        .filter(|n| n.tag_name().namespace() == Some(RDF_NS) && n.tag_name().name() == "li")
+}
+
+/// Trimmed text, or `None` if blank.
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A Lightroom-style packet with element, attribute and array properties.
+    pub(crate) const PACKET: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmp:Rating="4" xmp:Label="Red">
+   <dc:title><rdf:Alt>
+    <rdf:li xml:lang="de">Strand</rdf:li>
+    <rdf:li xml:lang="x-default">Beach at dusk</rdf:li>
+   </rdf:Alt></dc:title>
+   <dc:description><rdf:Alt><rdf:li xml:lang="x-default">Waves &amp; sand</rdf:li></rdf:Alt></dc:description>
+   <dc:subject><rdf:Bag><rdf:li>beach</rdf:li><rdf:li>sunset</rdf:li></rdf:Bag></dc:subject>
+   <dc:creator><rdf:Seq><rdf:li>Ana Silva</rdf:li><rdf:li>Ben Ode</rdf:li></rdf:Seq></dc:creator>
+   <dc:rights><rdf:Alt><rdf:li xml:lang="x-default">(c) 2023 Ana Silva</rdf:li></rdf:Alt></dc:rights>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>"#;
+
+    #[test]
+    fn test_find_packet_in_surrounding_bytes() {
+        let mut bytes = vec![0xFF, 0xD8, 0xFF, 0xE1, 0x00, 0x10];
+        bytes.extend_from_slice(b"http://ns.adobe.com/xap/1.0/\0<?xpacket begin=''?>");
+        bytes.extend_from_slice(PACKET.as_bytes());
+        bytes.extend_from_slice(b"<?xpacket end='w'?>\xFF\xD9");
+
+        let packet = find_packet(&bytes).unwrap();
+        assert!(packet.starts_with("<x:xmpmeta"));
+        assert!(packet.ends_with("</x:xmpmeta>"));
+    }
+
+    #[test]
+    fn test_find_packet_missing() {
+        assert!(find_packet(b"\xFF\xD8 no metadata here").is_none());
+        assert!(find_packet(b"<x:xmpmeta truncated").is_none());
+    }
+
+    #[test]
+    fn test_parse_lightroom_packet() {
+        let data = parse(PACKET).unwrap();
+        assert_eq!(data.title.as_deref(), Some("Beach at dusk"));
+        assert_eq!(data.caption.as_deref(), Some("Waves & sand"));
+        assert_eq!(data.keywords, ["beach", "sunset"]);
+        assert_eq!(data.creator.as_deref(), Some("Ana Silva; Ben Ode"));
+        assert_eq!(data.copyright.as_deref(), Some("(c) 2023 Ana Silva"));
+        assert_eq!(data.rating, Some(4));
+        assert_eq!(data.label.as_deref(), Some("Red"));
+    }
+
+    #[test]
+    fn test_parse_element_form_rating() {
+        let packet = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description xmlns:xmp="http://ns.adobe.com/xap/1.0/">
+   <xmp:Rating>-1</xmp:Rating>
+  </rdf:Description>
+ </rdf:RDF>"#;
+        let data = parse(packet).unwrap();
+        assert_eq!(data.rating, Some(-1));
+        assert!(data.title.is_none());
+        assert!(data.keywords.is_empty());
+    }
+
+    #[test]
+    fn test_parse_empty_or_malformed() {
+        let empty = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""/></rdf:RDF>"#;
+        assert!(parse(empty).is_none());
+        assert!(parse("<x:xmpmeta><unclosed></x:xmpmeta>").is_none());
+    }
+}
@@ -1,11 +1,13 @@
 //! Pre-computed term embeddings for fast scoring.
 //!
-//! The label bank stores a flat N×768 matrix of text embeddings (one per vocabulary term)
-//! that can be dot-producted against image embeddings for instant scoring.
+//! The label bank stores a flat N×D matrix of text embeddings (one per vocabulary term,
+//! D = the model's embedding width, 768 for SigLIP base) that can be dot-producted
+//! against image embeddings for instant scoring.
 
 use std::io::Read;
 use std::path::Path;
 
+use crate::embedding::manifest::ModelManifest;
 use crate::error::PipelineError;
 
 /// Safety invariant: label bank binary format assumes little-endian f32 layout.
@@ -15,24 +17,41 @@ const _: () = assert!(cfg!(target_endian = "little"));
 use super::text_encoder::SigLipTextEncoder;
 use super::vocabulary::Vocabulary;
 
+/// SigLIP base learned scaling parameters (derived from combined model logits).
+///
+/// These amplify tiny cosine differences into meaningful logits.
+/// See `docs/completions/phase-4-text-encoder-spike.md` for derivation.
+/// Other models carry their own values in their manifest; these calibrate
+/// the synthetic banks in tests.
+#[cfg(test)]
+pub(crate) const DEFAULT_LOGIT_SCALE: f32 = 117.33;
+#[cfg(test)]
+pub(crate) const DEFAULT_LOGIT_BIAS: f32 = -12.93;
+
 /// Pre-computed term embeddings for scoring.
 ///
-/// Stores a single flat matrix (N × 768, row-major) for efficient dot product.
+/// Stores a single flat matrix (N × D, row-major) for efficient dot product,
+/// plus the model's sigmoid calibration so the scorer needs nothing else.
 #[derive(Clone)]
 pub struct LabelBank {
-    /// Flat matrix: N × 768 stored row-major.
+    /// Flat matrix: N × D stored row-major.
     matrix: Vec<f32>,
     embedding_dim: usize,
     term_count: usize,
+    logit_scale: f32,
+    logit_bias: f32,
 }
 
 impl LabelBank {
-    /// Create an empty label bank (placeholder for RwLock initialization).
-    pub fn empty() -> Self {
+    /// Create an empty label bank for `manifest`'s model (placeholder for
+    /// RwLock initialization).
+    pub fn empty(manifest: &ModelManifest) -> Self {
         Self {
             matrix: vec![],
-            embedding_dim: 768,
+            embedding_dim: manifest.embedding_dim,
             term_count: 0,
+            logit_scale: manifest.logit_scale,
+            logit_bias: manifest.logit_bias,
         }
     }
 
@@ -51,6 +70,8 @@ impl LabelBank {
             matrix,
             embedding_dim,
             term_count,
+            logit_scale: DEFAULT_LOGIT_SCALE,
+            logit_bias: DEFAULT_LOGIT_BIAS,
         }
     }
 
@@ -82,7 +103,8 @@ impl LabelBank {
         batch_size: usize,
     ) -> Result<Self, PipelineError> {
         let terms = vocabulary.all_terms();
-        let embedding_dim = 768;
+        let manifest = text_encoder.manifest();
+        let embedding_dim = manifest.embedding_dim;
         let mut matrix: Vec<f32> = Vec::with_capacity(terms.len() * embedding_dim);
 
         tracing::info!(
@@ -128,6 +150,8 @@ impl LabelBank {
             matrix,
             embedding_dim,
             term_count,
+            logit_scale: manifest.logit_scale,
+            logit_bias: manifest.logit_bias,
         })
     }
 
@@ -169,9 +193,13 @@ impl LabelBank {
         Ok(())
     }
 
-    /// Load label bank from a raw f32 binary file.
-    pub fn load(path: &Path, term_count: usize) -> Result<Self, PipelineError> {
-        let embedding_dim = 768;
+    /// Load label bank from a raw f32 binary file written for `manifest`'s model.
+    pub fn load(
+        path: &Path,
+        term_count: usize,
+        manifest: &ModelManifest,
+    ) -> Result<Self, PipelineError> {
+        let embedding_dim = manifest.embedding_dim;
         let float_count = term_count * embedding_dim;
         let expected_bytes = float_count * std::mem::size_of::<f32>();
 
@@ -184,8 +212,8 @@ impl LabelBank {
         if file_len != expected_bytes {
             return Err(PipelineError::Model {
                 message: format!(
-                    "Label bank size mismatch: expected {} bytes ({} terms), got {} bytes",
-                    expected_bytes, term_count, file_len
+                    "Label bank size mismatch: expected {} bytes ({} terms × {} dims), got {} bytes",
+                    expected_bytes, term_count, embedding_dim, file_len
                 ),
             });
         }
@@ -216,6 +244,8 @@ impl LabelBank {
             matrix,
             embedding_dim,
             term_count,
+            logit_scale: manifest.logit_scale,
+            logit_bias: manifest.logit_bias,
         })
     }
 
@@ -242,11 +272,16 @@ impl LabelBank {
         &self.matrix
     }
 
-    /// Get the embedding dimension (768).
+    /// Get the embedding dimension (768 for SigLIP base).
     pub fn embedding_dim(&self) -> usize {
         self.embedding_dim
     }
 
+    /// The model's `(logit_scale, logit_bias)` for sigmoid scoring.
+    pub fn logits(&self) -> (f32, f32) {
+        (self.logit_scale, self.logit_bias)
+    }
+
     /// Get the number of terms in the bank.
     pub fn term_count(&self) -> usize {
         self.term_count
@@ -256,13 +291,27 @@ impl LabelBank {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::embedding::manifest::ModelRegistry;
+
+    fn base_manifest() -> ModelManifest {
+        ModelRegistry::builtin()
+            .get("siglip-base-patch16")
+            .cloned()
+            .unwrap()
+    }
 
     #[test]
     fn test_empty_label_bank() {
-        let bank = LabelBank::empty();
+        let bank = LabelBank::empty(&base_manifest());
         assert_eq!(bank.term_count(), 0);
         assert_eq!(bank.embedding_dim(), 768);
         assert!(bank.matrix().is_empty());
+
+        let wide = ModelManifest {
+            embedding_dim: 1152,
+            ..base_manifest()
+        };
+        assert_eq!(LabelBank::empty(&wide).embedding_dim(), 1152);
     }
 
     #[test]
@@ -272,11 +321,13 @@ mod tests {
             matrix: vec![1.0; 3 * dim],
             embedding_dim: dim,
             term_count: 3,
+            ..LabelBank::empty(&base_manifest())
         };
         let bank_b = LabelBank {
             matrix: vec![2.0; 5 * dim],
             embedding_dim: dim,
             term_count: 5,
+            ..LabelBank::empty(&base_manifest())
         };
 
         bank_a.append(&bank_b).unwrap();
@@ -292,11 +343,13 @@ mod tests {
             matrix: original_data.clone(),
             embedding_dim: dim,
             term_count: 3,
+            ..LabelBank::empty(&base_manifest())
         };
         let bank_b = LabelBank {
             matrix: vec![99.0; 2 * dim],
             embedding_dim: dim,
             term_count: 2,
+            ..LabelBank::empty(&base_manifest())
         };
 
         bank_a.append(&bank_b).unwrap();
@@ -309,8 +362,8 @@ mod tests {
 
     #[test]
     fn test_append_empty_to_empty() {
-        let mut bank_a = LabelBank::empty();
-        let bank_b = LabelBank::empty();
+        let mut bank_a = LabelBank::empty(&base_manifest());
+        let bank_b = LabelBank::empty(&base_manifest());
 
         bank_a.append(&bank_b).unwrap();
         assert_eq!(bank_a.term_count(), 0);
@@ -320,11 +373,12 @@ mod tests {
     #[test]
     fn test_append_to_empty() {
         let dim = 768;
-        let mut bank_a = LabelBank::empty();
+        let mut bank_a = LabelBank::empty(&base_manifest());
         let bank_b = LabelBank {
             matrix: vec![1.0; 3 * dim],
             embedding_dim: dim,
             term_count: 3,
+            ..LabelBank::empty(&base_manifest())
         };
 
         bank_a.append(&bank_b).unwrap();
@@ -338,11 +392,13 @@ mod tests {
             matrix: vec![1.0; 768],
             embedding_dim: 768,
             term_count: 1,
+            ..LabelBank::empty(&base_manifest())
         };
         let bank_b = LabelBank {
             matrix: vec![1.0; 512],
             embedding_dim: 512,
             term_count: 1,
+            ..LabelBank::empty(&base_manifest())
         };
 
         let result = bank_a.append(&bank_b);
@@ -362,13 +418,14 @@ mod tests {
             matrix: matrix.clone(),
             embedding_dim: dim,
             term_count,
+            ..LabelBank::empty(&base_manifest())
         };
 
         let dir = tempfile::tempdir().unwrap();
         let path = dir.path().join("label_bank.bin");
         bank.save(&path, "test_hash").unwrap();
 
-        let loaded = LabelBank::load(&path, term_count).unwrap();
+        let loaded = LabelBank::load(&path, term_count, &base_manifest()).unwrap();
         assert_eq!(loaded.term_count(), term_count);
         assert_eq!(loaded.embedding_dim(), dim);
         assert_eq!(loaded.matrix(), &matrix[..]);
@@ -384,9 +441,36 @@ mod tests {
         let path = dir.path().join("bad_bank.bin");
         std::fs::write(&path, &[0u8; 100]).unwrap();
 
-        let result = LabelBank::load(&path, 10);
+        let result = LabelBank::load(&path, 10, &base_manifest());
         assert!(result.is_err());
         let err_msg = result.err().unwrap().to_string();
         assert!(err_msg.contains("size mismatch"), "got: {err_msg}");
     }
+
+    #[test]
+    fn test_load_uses_manifest_dim_and_logits() {
+        let manifest = ModelManifest {
+            embedding_dim: 1152,
+            logit_scale: 100.0,
+            logit_bias: -10.0,
+            ..base_manifest()
+        };
+        let bank = LabelBank {
+            matrix: vec![0.5; 3 * 1152],
+            embedding_dim: 1152,
+            term_count: 3,
+            ..LabelBank::empty(&base_manifest())
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("label_bank.bin");
+        bank.save(&path, "hash").unwrap();
+
+        let loaded = LabelBank::load(&path, 3, &manifest).unwrap();
+        assert_eq!(loaded.embedding_dim(), 1152);
+        assert_eq!(loaded.logits(), (100.0, -10.0));
+
+        // A 768-d model reading the same file is caught by the size check
+        assert!(LabelBank::load(&path, 3, &base_manifest()).is_err());
+    }
 }
@@ -169,7 +169,10 @@ impl ProgressiveEncoder {
             // This reduces peak memory: the old scorer is dropped by the assignment
             // before we clone back, so we never hold old + current + clone simultaneously.
             let combined_vocab = ctx.full_vocabulary.subset(&encoded_indices);
-            let bank = std::mem::replace(&mut running_bank, LabelBank::empty());
+            let bank = std::mem::replace(
+                &mut running_bank,
+                LabelBank::empty(ctx.text_encoder.manifest()),
+            );
             let new_scorer = TagScorer::new(combined_vocab, bank, ctx.config.clone());
 
             // Atomic swap — write lock held only for the duration of a field swap
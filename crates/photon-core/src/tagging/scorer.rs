@@ -17,13 +17,6 @@ use super::label_bank::LabelBank;
 use super::relevance::RelevanceTracker;
 use super::vocabulary::Vocabulary;
 
-/// SigLIP learned scaling parameters (derived from combined model logits).
-///
-/// These amplify tiny cosine differences into meaningful logits.
-/// See `docs/completions/phase-4-text-encoder-spike.md` for derivation.
-const LOGIT_SCALE: f32 = 117.33;
-const LOGIT_BIAS: f32 = -12.93;
-
 /// Tags with their raw (term_index, confidence) hits for relevance tracking.
 pub type ScoringResult = (Vec<Tag>, Vec<(usize, f32)>);
 
@@ -56,9 +49,10 @@ impl TagScorer {
 
     /// Convert cosine similarity to confidence via SigLIP's sigmoid scoring.
     ///
-    /// `logit = LOGIT_SCALE * cosine + LOGIT_BIAS`, then `sigmoid(logit)`.
-    fn cosine_to_confidence(cosine: f32) -> f32 {
-        let logit = LOGIT_SCALE * cosine + LOGIT_BIAS;
+    /// `logit = logit_scale * cosine + logit_bias`, then `sigmoid(logit)`.
+    /// The scale and bias come from the model's manifest via the label bank.
+    fn cosine_to_confidence(cosine: f32, (logit_scale, logit_bias): (f32, f32)) -> f32 {
+        let logit = logit_scale * cosine + logit_bias;
         1.0 / (1.0 + (-logit).exp())
     }
 
@@ -132,10 +126,11 @@ impl TagScorer {
         let img = ArrayView1::from(image_embedding);
         let cosines = mat.dot(&img);
 
+        let logits = self.label_bank.logits();
         let scores: Vec<(usize, f32)> = cosines
             .iter()
             .enumerate()
-            .map(|(i, &cosine)| (i, Self::cosine_to_confidence(cosine)))
+            .map(|(i, &cosine)| (i, Self::cosine_to_confidence(cosine, logits)))
             .collect();
 
         Ok(self.hits_to_tags(&scores))
@@ -150,6 +145,7 @@ impl TagScorer {
     pub fn score_indices(&self, image_embedding: &[f32], indices: &[usize]) -> Vec<(usize, f32)> {
         let dim = self.label_bank.embedding_dim();
         let matrix = self.label_bank.matrix();
+        let logits = self.label_bank.logits();
         let img = ArrayView1::from(image_embedding);
 
         indices
@@ -158,7 +154,7 @@ impl TagScorer {
                 let offset = i * dim;
                 let row = ArrayView1::from(&matrix[offset..offset + dim]);
                 let cosine = row.dot(&img);
-                let confidence = Self::cosine_to_confidence(cosine);
+                let confidence = Self::cosine_to_confidence(cosine, logits);
                 (confidence >= self.config.min_confidence).then_some((i, confidence))
             })
             .collect()
@@ -199,32 +195,44 @@ impl TagScorer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tagging::label_bank::{DEFAULT_LOGIT_BIAS, DEFAULT_LOGIT_SCALE};
     use crate::tagging::relevance::RelevanceConfig;
 
+    const SIGLIP: (f32, f32) = (DEFAULT_LOGIT_SCALE, DEFAULT_LOGIT_BIAS);
+
     #[test]
     fn test_cosine_to_confidence_range() {
         // Very negative cosine -> near 0 confidence
-        let conf = TagScorer::cosine_to_confidence(-0.2);
+        let conf = TagScorer::cosine_to_confidence(-0.2, SIGLIP);
         assert!(conf < 0.01);
 
         // SigLIP typical matching cosine (~-0.05) should give some confidence
-        let conf = TagScorer::cosine_to_confidence(-0.05);
+        let conf = TagScorer::cosine_to_confidence(-0.05, SIGLIP);
         assert!(conf > 0.0 && conf < 1.0);
 
         // Positive cosine (unusual for SigLIP) -> high confidence
-        let conf = TagScorer::cosine_to_confidence(0.2);
+        let conf = TagScorer::cosine_to_confidence(0.2, SIGLIP);
         assert!(conf > 0.99);
     }
 
     #[test]
     fn test_sigmoid_monotonic() {
-        let c1 = TagScorer::cosine_to_confidence(-0.10);
-        let c2 = TagScorer::cosine_to_confidence(-0.07);
-        let c3 = TagScorer::cosine_to_confidence(-0.05);
+        let c1 = TagScorer::cosine_to_confidence(-0.10, SIGLIP);
+        let c2 = TagScorer::cosine_to_confidence(-0.07, SIGLIP);
+        let c3 = TagScorer::cosine_to_confidence(-0.05, SIGLIP);
         assert!(c1 < c2);
         assert!(c2 < c3);
     }
 
+    #[test]
+    fn test_calibration_follows_model() {
+        // A CLIP-style calibration centered on cosine 0.25
+        let clip = (100.0, -25.0);
+        let at_center = TagScorer::cosine_to_confidence(0.25, clip);
+        assert!((at_center - 0.5).abs() < 1e-6);
+        assert!(TagScorer::cosine_to_confidence(0.25, SIGLIP) > 0.99);
+    }
+
     /// Helper: create a minimal scorer with synthetic embeddings for testing.
     ///
     /// Returns the TempDir alongside the scorer so it stays alive for the
@@ -295,7 +303,7 @@ mod tests {
         // Only term 0 and 2 should appear in results
         let hit_indices: Vec<usize> = hits.iter().map(|(i, _)| *i).collect();
         assert!(hit_indices.contains(&0));
-        // Term 2 is all zeros, cosine = 0, confidence depends on LOGIT_BIAS
+        // Term 2 is all zeros, cosine = 0, confidence depends on the logit bias
         // With min_confidence 0.0, it should still appear
         assert!(hit_indices.contains(&2));
         assert!(!hit_indices.contains(&1));
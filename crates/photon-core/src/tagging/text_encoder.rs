@@ -1,7 +1,8 @@
 //! SigLIP text encoder for generating text embeddings.
 //!
 //! Loads the SigLIP text ONNX model and tokenizer, encodes text strings
-//! to 768-dimensional vectors aligned with the vision encoder's space.
+//! to vectors aligned with the vision encoder's space. Sequence length,
+//! output name and embedding width come from the model's manifest.
 
 use std::path::Path;
 use std::sync::Mutex;
@@ -9,6 +10,9 @@ use std::sync::Mutex;
 use ort::session::Session;
 use ort::value::Value;
 
+use crate::config::RuntimeConfig;
+use crate::embedding::manifest::ModelManifest;
+use crate::embedding::runtime::load_session;
 use crate::error::PipelineError;
 
 /// SigLIP text encoder wrapper.
@@ -17,14 +21,19 @@ use crate::error::PipelineError;
 pub struct SigLipTextEncoder {
     session: Mutex<Session>,
     tokenizer: tokenizers::Tokenizer,
-    embedding_dim: usize,
+    manifest: ModelManifest,
 }
 
 impl SigLipTextEncoder {
     /// Load the text encoder from the model directory.
     ///
-    /// Expects `text_model.onnx` and `tokenizer.json` in `model_dir`.
-    pub fn new(model_dir: &Path) -> Result<Self, PipelineError> {
+    /// Expects `text_model.onnx` and `tokenizer.json` in `model_dir` (see
+    /// [`ModelManifest::text_model_dir`]).
+    pub fn new(
+        model_dir: &Path,
+        manifest: &ModelManifest,
+        runtime: &RuntimeConfig,
+    ) -> Result<Self, PipelineError> {
         let text_model_path = model_dir.join("text_model.onnx");
         let tokenizer_path = model_dir.join("tokenizer.json");
 
@@ -46,13 +55,9 @@ impl SigLipTextEncoder {
             });
         }
 
-        let session = Session::builder()
-            .map_err(|e| PipelineError::Model {
-                message: format!("Failed to create ONNX session builder: {e}"),
-            })?
-            .commit_from_file(&text_model_path)
-            .map_err(|e| PipelineError::Model {
-                message: format!("Failed to load text encoder model: {e}"),
+        let session =
+            load_session(&text_model_path, runtime).map_err(|e| PipelineError::Model {
+                message: format!("Text encoder: {e}"),
             })?;
 
         let tokenizer = tokenizers::Tokenizer::from_file(&tokenizer_path).map_err(|e| {
@@ -78,15 +83,20 @@ impl SigLipTextEncoder {
         Ok(Self {
             session: Mutex::new(session),
             tokenizer,
-            embedding_dim: 768,
+            manifest: manifest.clone(),
         })
     }
 
+    /// The manifest of the model this encoder belongs to.
+    pub fn manifest(&self) -> &ModelManifest {
+        &self.manifest
+    }
+
     /// Encode a batch of text strings to normalized embeddings.
     ///
-    /// Returns a Vec of 768-dim f32 vectors, one per input text.
+    /// Returns a Vec of `manifest.embedding_dim`-wide f32 vectors, one per input text.
     pub fn encode_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, PipelineError> {
-        let max_length = 64; // SigLIP default sequence length
+        let max_length = self.manifest.text_max_length;
         let batch_size = texts.len();
 
         // Tokenize all texts
@@ -97,13 +107,16 @@ impl SigLipTextEncoder {
                 message: format!("Tokenization failed: {e}"),
             })?;
 
-        // Build flat input_ids tensor — SigLIP text model takes input_ids only (no attention_mask)
+        // Build flat input_ids tensor — SigLIP text model takes input_ids only;
+        // models whose manifest asks for it also get an attention_mask
         let mut input_ids = vec![0i64; batch_size * max_length];
+        let mut attention_mask = vec![0i64; batch_size * max_length];
 
         for (i, encoding) in encodings.iter().enumerate() {
             let ids = encoding.get_ids();
             for (j, &id) in ids.iter().take(max_length).enumerate() {
                 input_ids[i * max_length + j] = id as i64;
+                attention_mask[i * max_length + j] = 1;
             }
         }
 
@@ -112,25 +125,32 @@ impl SigLipTextEncoder {
             message: format!("Text encoder lock poisoned: {e}"),
         })?;
 
-        let input_ids_value =
-            Value::from_array((vec![batch_size as i64, max_length as i64], input_ids)).map_err(
-                |e| PipelineError::Model {
-                    message: format!("Failed to create input tensor: {e}"),
-                },
-            )?;
-
-        let outputs = session
-            .run(ort::inputs!["input_ids" => input_ids_value])
-            .map_err(|e| PipelineError::Model {
-                message: format!("Text encoder inference failed: {e}"),
-            })?;
+        let shape = vec![batch_size as i64, max_length as i64];
+        let tensor = |values: Vec<i64>| {
+            Value::from_array((shape.clone(), values)).map_err(|e| PipelineError::Model {
+                message: format!("Failed to create input tensor: {e}"),
+            })
+        };
+        let inputs = if self.manifest.text_attention_mask {
+            ort::inputs![
+                "input_ids" => tensor(input_ids)?,
+                "attention_mask" => tensor(attention_mask)?,
+            ]
+        } else {
+            ort::inputs!["input_ids" => tensor(input_ids)?]
+        };
+
+        let outputs = session.run(inputs).map_err(|e| PipelineError::Model {
+            message: format!("Text encoder inference failed: {e}"),
+        })?;
 
-        // Extract pooler_output by name — the cross-modal embedding
+        // Extract the embedding output by name — the cross-modal embedding
+        let output_name = &self.manifest.text_output;
         let pooler_output = outputs
             .iter()
-            .find(|(name, _)| *name == "pooler_output")
+            .find(|(name, _)| name == output_name)
             .ok_or_else(|| PipelineError::Model {
-                message: "Text encoder did not produce pooler_output".to_string(),
+                message: format!("Text encoder did not produce {output_name}"),
             })?;
 
         let (_shape, data) =
@@ -138,14 +158,23 @@ impl SigLipTextEncoder {
                 .1
                 .try_extract_tensor::<f32>()
                 .map_err(|e| PipelineError::Model {
-                    message: format!("Failed to extract pooler_output: {e}"),
+                    message: format!("Failed to extract {output_name}: {e}"),
                 })?;
 
+        let dim = self.manifest.embedding_dim;
+        if data.len() != batch_size * dim {
+            return Err(PipelineError::Model {
+                message: format!(
+                    "Text encoder produced {} values for {batch_size} texts, expected {dim}-d \
+                     embeddings for model '{}'",
+                    data.len(),
+                    self.manifest.name
+                ),
+            });
+        }
+
         // Split flat output into per-text embeddings and L2-normalize
-        let embeddings: Vec<Vec<f32>> = data
-            .chunks(self.embedding_dim)
-            .map(crate::math::l2_normalize)
-            .collect();
+        let embeddings: Vec<Vec<f32>> = data.chunks(dim).map(crate::math::l2_normalize).collect();
 
         Ok(embeddings)
     }
@@ -3,17 +3,26 @@
 //! These types represent the output of processing an image through the pipeline.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// The complete output for a processed image.
 ///
 /// This struct contains all the data extracted and generated from an image,
 /// including file identification, vector embeddings, metadata, tags, and descriptions.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// `Default` is an empty record, for building one field by field.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ProcessedImage {
     // === File Identification ===
-    /// Absolute path to the source file
-    pub file_path: PathBuf,
+    /// Path to the source file (absent for images processed from memory).
+    /// A keyed hash when privacy mode hashes paths.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<PathBuf>,
+
+    /// Real source path, kept in memory when privacy mode replaces
+    /// `file_path` (so LLM enrichment can still read the file). Never output.
+    #[serde(skip)]
+    pub source_path: Option<PathBuf>,
 
     /// Just the filename portion
     pub file_name: String,
@@ -22,10 +31,10 @@ pub struct ProcessedImage {
     pub content_hash: String,
 
     // === Image Properties ===
-    /// Image width in pixels
+    /// Image width in pixels (as displayed, after EXIF orientation)
     pub width: u32,
 
-    /// Image height in pixels
+    /// Image height in pixels (as displayed, after EXIF orientation)
     pub height: u32,
 
     /// Detected format ("jpeg", "png", "webp", etc.)
@@ -34,15 +43,38 @@ pub struct ProcessedImage {
     /// File size in bytes
     pub file_size: u64,
 
+    /// Number of frames (animated GIF/WebP only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_count: Option<u32>,
+
+    /// Duration of one animation loop in milliseconds (animated GIF/WebP only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+
     // === Vector Embedding ===
-    /// 768-dimensional embedding vector from SigLIP
+    /// Embedding vector from the vision model; its width comes from the model
+    /// manifest (768 for SigLIP base).
+    /// For animations, the normalized mean of the sampled frame embeddings.
     pub embedding: Vec<f32>,
 
+    /// Embeddings of the sampled animation frames, in playback order
+    /// (only when `processing.frame_embeddings` is enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_embeddings: Option<Vec<Vec<f32>>>,
+
     // === Metadata ===
     /// EXIF data if available
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exif: Option<ExifData>,
 
+    /// Descriptive metadata from XMP and IPTC-IIM, if available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub xmp: Option<XmpData>,
+
+    /// Place names reverse-geocoded from the EXIF GPS position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+
     // === AI-Generated Content ===
     /// Semantic tags with confidence scores
     pub tags: Vec<Tag>,
@@ -52,22 +84,61 @@ pub struct ProcessedImage {
     pub description: Option<String>,
 
     // === Optional Outputs ===
-    /// Base64-encoded WebP thumbnail
+    /// Base64-encoded thumbnail, in `thumbnail_format`
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thumbnail: Option<String>,
 
-    /// Perceptual hash for similarity detection
+    /// Encoding of `thumbnail`: "webp", "jpeg", "png" or "avif"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_format: Option<String>,
+
+    /// Thumbnail renditions written to `thumbnail.output_dir`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub renditions: Vec<Rendition>,
+
+    /// BlurHash placeholder string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blurhash: Option<String>,
+
+    /// ThumbHash placeholder (base64)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbhash: Option<String>,
+
+    /// Perceptual hash for similarity detection (the first configured
+    /// algorithm; kept for consumers that expect a single hash)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub perceptual_hash: Option<String>,
+
+    /// Every configured perceptual hash, keyed by algorithm name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub perceptual_hashes: BTreeMap<String, String>,
+
+    /// Dominant colors, largest share first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub colors: Vec<PaletteColor>,
+
+    /// Technical quality metrics (focus, exposure, noise, colorfulness)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<QualityMetrics>,
 }
 
 /// EXIF metadata extracted from an image.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct ExifData {
-    /// When the photo was captured (ISO 8601 format)
+    /// When the photo was captured, as RFC 3339 with fractional seconds and
+    /// UTC offset (e.g., "2023-01-01T12:00:00.250+02:00").
+    ///
+    /// When the file records no time zone (no offset tag, no usable GPS time),
+    /// this is a naive ISO 8601 local time with no offset, e.g.
+    /// "2023-01-01T12:00:00.250". That form is not RFC 3339: it is the camera's
+    /// wall-clock time and must not be read as UTC.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub captured_at: Option<String>,
 
+    /// Capture time exactly as stored in EXIF (e.g., "2023:01:01 12:00:00")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub captured_at_raw: Option<String>,
+
     /// Camera manufacturer
     #[serde(skip_serializing_if = "Option::is_none")]
     pub camera_make: Option<String>,
@@ -103,6 +174,174 @@ pub struct ExifData {
     /// Image orientation (1-8 per EXIF spec)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub orientation: Option<u32>,
+
+    /// Lens model (e.g., "RF24-70mm F2.8 L IS USM")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lens_model: Option<String>,
+
+    /// Exposure program (e.g., "aperture priority")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exposure_program: Option<String>,
+
+    /// Exposure bias in EV (e.g., -0.7)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exposure_bias: Option<f32>,
+
+    /// White balance mode ("auto" or "manual")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub white_balance: Option<String>,
+
+    /// Flash status (e.g., "fired, return light detected")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flash: Option<String>,
+
+    /// Metering mode (e.g., "pattern", "spot")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metering_mode: Option<String>,
+
+    /// GPS altitude in meters (negative below sea level)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gps_altitude: Option<f64>,
+
+    /// GPS date and time in UTC (RFC 3339, e.g., "2023-06-01T10:15:30Z")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gps_timestamp: Option<String>,
+
+    /// Direction the camera was pointing, in degrees (0-360)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_direction: Option<f64>,
+
+    /// UTC offset of the capture time (e.g., "+02:00")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset_time_original: Option<String>,
+
+    /// Camera body serial number
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
+
+    /// Lens serial number
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lens_serial_number: Option<String>,
+
+    /// Camera owner (`CameraOwnerName`, falling back to `Artist`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_name: Option<String>,
+}
+
+/// Descriptive metadata from an image's XMP packet and IPTC-IIM block.
+///
+/// XMP values take precedence; IPTC fills in fields the XMP packet lacks.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct XmpData {
+    /// Title (`dc:title`, IPTC Object Name)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Caption / description (`dc:description`, IPTC Caption-Abstract)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+
+    /// Keywords (`dc:subject`, IPTC Keywords)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+
+    /// Creator(s), joined with "; " (`dc:creator`, IPTC By-line)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+
+    /// Copyright notice (`dc:rights`, IPTC Copyright Notice)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copyright: Option<String>,
+
+    /// Star rating (`xmp:Rating`, -1 = rejected, 0-5)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating: Option<i32>,
+
+    /// Color label (`xmp:Label`, e.g., "Red")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// A place resolved offline from GPS coordinates (GeoNames + country polygons).
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Location {
+    /// Country name (e.g., "Portugal")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+
+    /// ISO 3166-1 alpha-2 country code (e.g., "PT")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+
+    /// First-level administrative division (state, region, province)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub admin1: Option<String>,
+
+    /// Nearest populated place
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+
+    /// Great-circle distance from the GPS position to `city`, in kilometers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance_km: Option<f64>,
+}
+
+/// One dominant color of an image's palette.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PaletteColor {
+    /// sRGB hex value, e.g. `"#1e3a5f"`
+    pub hex: String,
+
+    /// CIE Lab coordinates (D65): L*, a*, b*
+    pub lab: [f32; 3],
+
+    /// Fraction of the (opaque) pixels in this cluster, 0.0–1.0
+    pub share: f32,
+
+    /// Nearest named color (e.g. "navy", "beige")
+    pub name: String,
+}
+
+/// Technical image-quality metrics, computed on a downscaled copy
+/// (`quality.sample_size`). Intensities are on the 0–255 scale.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QualityMetrics {
+    /// Variance of the Laplacian of luma; low values mean blur or missed focus
+    pub sharpness: f32,
+
+    /// Estimated noise standard deviation in luma
+    pub noise: f32,
+
+    /// Hasler–Süsstrunk colorfulness (0 = grayscale, 80+ = highly colorful)
+    pub colorfulness: f32,
+
+    /// Mean luma
+    pub mean_luminance: f32,
+
+    /// Percentage of pixels with luma ≥ 250 (blown highlights)
+    pub clipped_highlights_pct: f32,
+
+    /// Percentage of pixels with luma ≤ 5 (crushed shadows)
+    pub clipped_shadows_pct: f32,
+
+    /// Share of pixels in each of 16 equal luma bins, darkest first
+    pub luminance_histogram: Vec<f32>,
+}
+
+/// A thumbnail rendition written to disk.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Rendition {
+    /// Rendition name from `thumbnail.renditions`
+    pub name: String,
+
+    /// File path (`<output_dir>/<content_hash>_<name>.<ext>`)
+    pub path: PathBuf,
+
+    /// "webp", "jpeg", "png" or "avif"
+    pub format: String,
+
+    pub width: u32,
+    pub height: u32,
 }
 
 /// A semantic tag with confidence score.
@@ -170,6 +409,55 @@ pub enum OutputRecord {
     Enrichment(EnrichmentPatch),
 }
 
+/// How the members of a duplicate cluster match.
+///
+/// Ordered from strongest to weakest; a cluster reports the weakest link
+/// that joined it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateKind {
+    /// Byte-identical files (same BLAKE3 content hash)
+    Exact,
+    /// Perceptual hashes within the Hamming distance threshold
+    Near,
+    /// Embeddings above the cosine similarity threshold
+    Semantic,
+}
+
+/// A group of duplicate images with the copy to keep.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateCluster {
+    pub kind: DuplicateKind,
+
+    /// Suggested copy to keep: highest resolution, then largest file, then sharpest
+    pub keep: DuplicateMember,
+
+    /// The other members, best first
+    pub duplicates: Vec<DuplicateMember>,
+}
+
+/// One image in a [`DuplicateCluster`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateMember {
+    pub file_path: Option<PathBuf>,
+    pub file_name: String,
+    pub content_hash: String,
+    pub width: u32,
+    pub height: u32,
+    pub file_size: u64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sharpness: Option<f32>,
+
+    /// Perceptual hash distance to the kept image, when both have one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hamming_distance: Option<u32>,
+
+    /// Embedding cosine similarity to the kept image, when both have one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f32>,
+}
+
 /// Processing statistics for a batch run.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProcessingStats {
@@ -196,7 +484,7 @@ mod tests {
 
     fn sample_processed_image() -> ProcessedImage {
         ProcessedImage {
-            file_path: PathBuf::from("/photos/beach.jpg"),
+            file_path: Some(PathBuf::from("/photos/beach.jpg")),
             file_name: "beach.jpg".to_string(),
             content_hash: "abc123".to_string(),
             width: 1920,
@@ -204,11 +492,8 @@ mod tests {
             format: "jpeg".to_string(),
             file_size: 2048,
             embedding: vec![0.1, 0.2, 0.3],
-            exif: None,
             tags: vec![Tag::new("beach", 0.95)],
-            description: None,
-            thumbnail: None,
-            perceptual_hash: None,
+            ..ProcessedImage::default()
         }
     }
 
@@ -7,9 +7,11 @@
 //!
 //! All tests use the shared fixtures at `tests/fixtures/images/`.
 
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use photon_core::{
-    Config, DiscoveredFile, ImageProcessor, OutputFormat, OutputRecord, OutputWriter, PhotonError,
-    PipelineError, ProcessOptions, ProcessedImage,
+    config::{PerceptualHashConfig, RenditionConfig},
+    ArchiveReader, Config, DiscoveredFile, Hasher, ImageProcessor, OutputFormat, OutputRecord,
+    OutputWriter, PhotonError, PipelineError, ProcessOptions, ProcessedImage,
 };
 use std::path::{Path, PathBuf};
 
@@ -25,6 +27,17 @@ fn fixtures_dir() -> PathBuf {
     Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests/fixtures/images")
 }
 
+/// Default config with the opt-in image stages (placeholders, palette,
+/// quality) turned on.
+fn config_with_optional_stages() -> Config {
+    let mut config = Config::default();
+    config.placeholder.blurhash = true;
+    config.placeholder.thumbhash = true;
+    config.palette.enabled = true;
+    config.quality.enabled = true;
+    config
+}
+
 // ---------------------------------------------------------------------------
 // Full pipeline (no models)
 // ---------------------------------------------------------------------------
@@ -32,7 +45,7 @@ fn fixtures_dir() -> PathBuf {
 #[tokio::test]
 async fn full_pipeline_without_models() {
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
 
     let result = processor
         .process(fixture("beach.jpg").as_path())
@@ -59,12 +72,23 @@ async fn full_pipeline_without_models() {
         result.thumbnail.is_some(),
         "thumbnail should be generated by default"
     );
+    assert_eq!(result.thumbnail_format.as_deref(), Some("webp"));
 
-    // Perceptual hash generated by default
+    // Perceptual hash generated by default, named by its algorithm
     assert!(
         result.perceptual_hash.is_some(),
         "perceptual hash should be generated by default"
     );
+    assert_eq!(
+        result.perceptual_hashes.get("double_gradient"),
+        result.perceptual_hash.as_ref()
+    );
+
+    // Opt-in stages stay out of default output
+    assert!(result.blurhash.is_none() && result.thumbhash.is_none());
+    assert!(result.colors.is_empty());
+    assert!(result.quality.is_none());
+    assert!(result.location.is_none());
 
     // No models loaded → embedding and tags are empty
     assert!(result.embedding.is_empty(), "no embedding without model");
@@ -74,6 +98,37 @@ async fn full_pipeline_without_models() {
     assert!(result.description.is_none(), "no description without LLM");
 }
 
+// ---------------------------------------------------------------------------
+// Opt-in stages: placeholders, palette, quality
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn optional_stages_when_enabled() {
+    let config = config_with_optional_stages();
+    let processor = ImageProcessor::new(&config).unwrap();
+
+    let result = processor
+        .process(fixture("beach.jpg").as_path())
+        .await
+        .expect("beach.jpg should process successfully");
+
+    assert!(result.blurhash.as_ref().is_some_and(|h| h.len() == 28));
+    assert!(result.thumbhash.is_some());
+
+    // Largest share first
+    assert!(!result.colors.is_empty(), "palette should be extracted");
+    assert!(result.colors.len() <= config.palette.colors);
+    assert!(result.colors.windows(2).all(|w| w[0].share >= w[1].share));
+    assert!(result
+        .colors
+        .iter()
+        .all(|c| c.hex.len() == 7 && !c.name.is_empty()));
+
+    let quality = result.quality.as_ref().expect("quality metrics");
+    assert!(quality.sharpness > 0.0);
+    assert_eq!(quality.luminance_histogram.len(), 16);
+}
+
 // ---------------------------------------------------------------------------
 // ProcessOptions: skip thumbnail
 // ---------------------------------------------------------------------------
@@ -81,7 +136,7 @@ async fn full_pipeline_without_models() {
 #[tokio::test]
 async fn full_pipeline_skips_thumbnail() {
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
 
     let options = ProcessOptions {
         skip_thumbnail: true,
@@ -97,11 +152,136 @@ async fn full_pipeline_skips_thumbnail() {
         result.thumbnail.is_none(),
         "thumbnail should be None when skipped"
     );
+    assert!(result.thumbnail_format.is_none());
     // Other fields still populated
     assert!(!result.content_hash.is_empty());
     assert!(result.width > 0);
 }
 
+#[tokio::test]
+async fn thumbnail_format_follows_config() {
+    let mut config = Config::default();
+    config.thumbnail.format = "jpeg".to_string();
+    config.thumbnail.quality = 60;
+    let processor = ImageProcessor::new(&config).unwrap();
+
+    let result = processor
+        .process(fixture("beach.jpg").as_path())
+        .await
+        .expect("beach.jpg should process");
+
+    assert_eq!(result.thumbnail_format.as_deref(), Some("jpeg"));
+    let bytes = BASE64.decode(result.thumbnail.unwrap()).unwrap();
+    let thumbnail = image::load_from_memory_with_format(&bytes, image::ImageFormat::Jpeg)
+        .expect("thumbnail should decode as JPEG");
+    assert_eq!(
+        thumbnail.width().max(thumbnail.height()),
+        config.thumbnail.size
+    );
+}
+
+#[tokio::test]
+async fn renditions_written_to_output_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = Config::default();
+    config.thumbnail.inline = false;
+    config.thumbnail.output_dir = dir.path().to_string_lossy().into_owned();
+    config.thumbnail.renditions = vec![
+        RenditionConfig {
+            name: "small".to_string(),
+            size: 128,
+            ..RenditionConfig::default()
+        },
+        RenditionConfig {
+            name: "square".to_string(),
+            size: 96,
+            format: "jpeg".to_string(),
+            fit: "square".to_string(),
+            ..RenditionConfig::default()
+        },
+    ];
+    let processor = ImageProcessor::new(&config).unwrap();
+
+    let result = processor
+        .process(fixture("beach.jpg").as_path())
+        .await
+        .expect("beach.jpg should process");
+
+    assert!(result.thumbnail.is_none());
+    assert!(result.thumbnail_format.is_none());
+    assert_eq!(result.renditions.len(), 2);
+    let square = &result.renditions[1];
+    assert_eq!(
+        square.path,
+        dir.path()
+            .join(format!("{}_square.jpg", result.content_hash))
+    );
+    assert_eq!((square.width, square.height), (96, 96));
+    assert!(result.renditions.iter().all(|r| r.path.is_file()));
+
+    // --no-thumbnail skips the renditions too
+    let options = ProcessOptions {
+        skip_thumbnail: true,
+        ..Default::default()
+    };
+    let skipped = processor
+        .process_with_options(fixture("dog.jpg").as_path(), &options)
+        .await
+        .expect("dog.jpg should process");
+    assert!(skipped.renditions.is_empty());
+}
+
+// ---------------------------------------------------------------------------
+// Placeholders: BlurHash components follow config
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn blurhash_components_follow_config() {
+    let mut config = Config::default();
+    config.placeholder.blurhash = true;
+    config.placeholder.components_x = 5;
+    config.placeholder.components_y = 5;
+    config.placeholder.thumbhash = false;
+    let processor = ImageProcessor::new(&config).unwrap();
+
+    let result = processor
+        .process(fixture("beach.jpg").as_path())
+        .await
+        .expect("beach.jpg should process");
+
+    // 1 size + 4 DC + 2 per AC component
+    let blurhash = result.blurhash.expect("blurhash should be computed");
+    assert_eq!(blurhash.len(), 1 + 1 + 4 + 2 * (5 * 5 - 1));
+    assert!(result.thumbhash.is_none());
+}
+
+// ---------------------------------------------------------------------------
+// ProcessOptions: skip palette
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn full_pipeline_skips_palette() {
+    let config = config_with_optional_stages();
+    let processor = ImageProcessor::new(&config).unwrap();
+
+    let options = ProcessOptions {
+        skip_palette: true,
+        ..Default::default()
+    };
+
+    let result = processor
+        .process_with_options(fixture("beach.jpg").as_path(), &options)
+        .await
+        .expect("should process with skip_palette");
+
+    assert!(
+        result.colors.is_empty(),
+        "colors should be empty when skipped"
+    );
+    let json = serde_json::to_string(&result).expect("serialize");
+    assert!(!json.contains("\"colors\""));
+}
+
 // ---------------------------------------------------------------------------
 // ProcessOptions: skip perceptual hash
 // ---------------------------------------------------------------------------
@@ -109,7 +289,7 @@ async fn full_pipeline_skips_thumbnail() {
 #[tokio::test]
 async fn full_pipeline_skips_perceptual_hash() {
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
 
     let options = ProcessOptions {
         skip_perceptual_hash: true,
@@ -136,7 +316,7 @@ async fn full_pipeline_skips_perceptual_hash() {
 #[tokio::test]
 async fn process_multiple_formats() {
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
 
     let cases = vec![
         ("test.png", "png"),
@@ -170,7 +350,7 @@ async fn process_multiple_formats() {
 #[tokio::test]
 async fn process_nonexistent_file() {
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
 
     let result = processor.process(Path::new("/nonexistent/image.jpg")).await;
 
@@ -192,7 +372,7 @@ async fn process_rejects_oversized_dimensions() {
     // Set dimension limit to 1px — any real image will exceed this.
     config.limits.max_image_dimension = 1;
 
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
 
     let result = processor.process(fixture("beach.jpg").as_path()).await;
 
@@ -217,7 +397,7 @@ async fn process_rejects_oversized_dimensions() {
 #[tokio::test]
 async fn discover_finds_fixtures() {
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
 
     let files: Vec<DiscoveredFile> = processor.discover(fixtures_dir().as_path());
 
@@ -250,7 +430,7 @@ async fn discover_finds_fixtures() {
 #[tokio::test]
 async fn output_roundtrip_json() {
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
 
     let original = processor
         .process(fixture("dog.jpg").as_path())
@@ -281,7 +461,7 @@ async fn output_roundtrip_json() {
 #[tokio::test]
 async fn output_roundtrip_jsonl() {
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
 
     let img1 = processor
         .process(fixture("beach.jpg").as_path())
@@ -333,7 +513,7 @@ async fn process_zero_length_file() {
     std::fs::write(&empty_file, b"").expect("create empty file");
 
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
     let result = processor.process(empty_file.as_path()).await;
 
     assert!(result.is_err(), "empty file should fail to process");
@@ -359,7 +539,7 @@ async fn process_1x1_pixel_image() {
     img.save(&tiny_path).expect("save 1x1 PNG");
 
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
     let result = processor
         .process(tiny_path.as_path())
         .await
@@ -387,7 +567,7 @@ async fn process_corrupt_jpeg_header() {
     std::fs::write(&corrupt_path, b"\xFF\xD8\xFF\x00GARBAGE_DATA_HERE").expect("write corrupt jpg");
 
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
     let result = processor.process(corrupt_path.as_path()).await;
 
     assert!(result.is_err(), "corrupt JPEG should fail");
@@ -417,7 +597,7 @@ async fn process_unicode_file_path() {
     std::fs::copy(&source, &unicode_path).expect("copy fixture to unicode path");
 
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
     let result = processor
         .process(unicode_path.as_path())
         .await
@@ -438,7 +618,7 @@ async fn process_unicode_file_path() {
 #[tokio::test]
 async fn deterministic_content_hash() {
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
 
     let result1 = processor
         .process(fixture("beach.jpg").as_path())
@@ -459,6 +639,51 @@ async fn deterministic_content_hash() {
     );
 }
 
+// ---------------------------------------------------------------------------
+// Multiple perceptual hashes
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn configured_perceptual_hashes() {
+    let mut config = Config::default();
+    config.hashing.perceptual = ["phash", "dhash", "blockhash"]
+        .into_iter()
+        .map(|algorithm| PerceptualHashConfig {
+            algorithm: algorithm.to_string(),
+            size: 8,
+        })
+        .collect();
+    let processor = ImageProcessor::new(&config).unwrap();
+
+    let result = processor
+        .process(fixture("beach.jpg").as_path())
+        .await
+        .expect("beach.jpg should process");
+
+    let keys: Vec<&str> = result
+        .perceptual_hashes
+        .keys()
+        .map(String::as_str)
+        .collect();
+    assert_eq!(keys, ["blockhash", "dhash", "phash"]);
+    // The first configured algorithm doubles as the single-hash field
+    assert_eq!(
+        result.perceptual_hash.as_ref(),
+        result.perceptual_hashes.get("phash")
+    );
+
+    let phash = &result.perceptual_hashes["phash"];
+    let dhash = &result.perceptual_hashes["dhash"];
+    assert_eq!(
+        Hasher::perceptual_distance("phash", phash, "phash", phash),
+        Some(0)
+    );
+    assert_eq!(
+        Hasher::perceptual_distance("phash", phash, "dhash", dhash),
+        None
+    );
+}
+
 // ---------------------------------------------------------------------------
 // Boundary: file size at exact limit (F6)
 // ---------------------------------------------------------------------------
@@ -490,7 +715,7 @@ async fn test_file_size_at_exact_limit() {
     }
     assert_eq!(std::fs::metadata(&img_path).unwrap().len(), target_size);
 
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
     let result = processor.process(img_path.as_path()).await;
 
     assert!(
@@ -527,7 +752,7 @@ async fn test_file_size_one_byte_over_limit() {
     }
     assert_eq!(std::fs::metadata(&img_path).unwrap().len(), target_size);
 
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
     let result = processor.process(img_path.as_path()).await;
 
     match result {
@@ -554,7 +779,7 @@ async fn test_image_dimension_at_exact_limit() {
     let mut config = Config::default();
     config.limits.max_image_dimension = 100;
 
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
     let result = processor.process(img_path.as_path()).await;
 
     assert!(
@@ -582,7 +807,7 @@ async fn test_image_dimension_one_over_limit() {
     let mut config = Config::default();
     config.limits.max_image_dimension = 100;
 
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
     let result = processor.process(img_path.as_path()).await;
 
     match result {
@@ -600,20 +825,211 @@ async fn test_image_dimension_one_over_limit() {
     }
 }
 
+// ---------------------------------------------------------------------------
+// In-memory processing
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn process_bytes_matches_path_processing() {
+    let config = Config::default();
+    let processor = ImageProcessor::new(&config).unwrap();
+    let options = ProcessOptions::default();
+    let path = fixture("beach.jpg");
+
+    let from_path = processor
+        .process_with_options(&path, &options)
+        .await
+        .expect("path processing");
+    let bytes = std::fs::read(&path).expect("read fixture");
+    let from_bytes = processor
+        .process_bytes(bytes, "beach.jpg", &options)
+        .await
+        .expect("in-memory processing");
+
+    assert_eq!(from_path.file_path.as_deref(), Some(path.as_path()));
+    assert!(from_bytes.file_path.is_none());
+    assert_eq!(from_bytes.file_name, "beach.jpg");
+    assert_eq!(from_bytes.content_hash, from_path.content_hash);
+    assert_eq!(
+        (from_bytes.width, from_bytes.height),
+        (from_path.width, from_path.height)
+    );
+    assert_eq!(from_bytes.format, from_path.format);
+    assert_eq!(from_bytes.file_size, from_path.file_size);
+    assert_eq!(from_bytes.perceptual_hash, from_path.perceptual_hash);
+    assert_eq!(from_bytes.thumbnail, from_path.thumbnail);
+    assert_eq!(
+        serde_json::to_string(&from_bytes.exif).unwrap(),
+        serde_json::to_string(&from_path.exif).unwrap()
+    );
+
+    // file_path is omitted from the JSON for in-memory images
+    let json = serde_json::to_string(&from_bytes).unwrap();
+    assert!(!json.contains("file_path"));
+}
+
+#[tokio::test]
+async fn process_bytes_rejects_non_image() {
+    let config = Config::default();
+    let processor = ImageProcessor::new(&config).unwrap();
+
+    let result = processor
+        .process_bytes(
+            b"definitely not an image".to_vec(),
+            "upload.jpg",
+            &ProcessOptions::default(),
+        )
+        .await;
+
+    match result {
+        Err(PhotonError::Pipeline(PipelineError::Decode { path, message })) => {
+            assert_eq!(path, PathBuf::from("upload.jpg"));
+            assert!(message.contains("magic bytes"), "got: {message}");
+        }
+        other => panic!("expected Decode error, got: {other:?}"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Archive entries
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn process_zip_archive_entries() {
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let zip_path = dir.path().join("photos.zip");
+    let beach = std::fs::read(fixture("beach.jpg")).expect("read fixture");
+
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(&zip_path).unwrap());
+    writer
+        .start_file("trip/beach.jpg", SimpleFileOptions::default())
+        .unwrap();
+    writer.write_all(&beach).unwrap();
+    writer
+        .start_file("trip/notes.txt", SimpleFileOptions::default())
+        .unwrap();
+    writer.write_all(b"not an image").unwrap();
+    writer.finish().unwrap();
+
+    let config = Config::default();
+    let processor = ImageProcessor::new(&config).unwrap();
+    let files = processor.discover(&zip_path);
+    assert_eq!(files.len(), 1, "only the supported entry is listed");
+    let file = &files[0];
+    assert_eq!(
+        file.path,
+        PathBuf::from(format!("{}!/trip/beach.jpg", zip_path.display()))
+    );
+    assert_eq!(file.size, beach.len() as u64);
+
+    let mut reader = ArchiveReader::new(config.limits.max_file_size_mb);
+    let entry = file.archive_entry.as_ref().expect("archive entry");
+    let bytes = reader.read(entry).expect("read entry");
+    let result = processor
+        .process_archive_entry(bytes, &file.path, &ProcessOptions::default())
+        .await
+        .expect("archive entry should process");
+
+    let direct = processor.process(&fixture("beach.jpg")).await.unwrap();
+    assert_eq!(result.file_path.as_deref(), Some(file.path.as_path()));
+    assert_eq!(result.file_name, "beach.jpg");
+    assert_eq!(result.content_hash, direct.content_hash);
+    assert_eq!(result.file_size, direct.file_size);
+}
+
+// ---------------------------------------------------------------------------
+// Animated GIF: frame count and duration
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn test_animated_gif_reports_frames_and_duration() {
+    use image::codecs::gif::GifEncoder;
+    use image::{Delay, Frame, RgbaImage};
+
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let gif_path = dir.path().join("animated.gif");
+
+    let file = std::fs::File::create(&gif_path).expect("create GIF");
+    let mut encoder = GifEncoder::new(file);
+    let frames = (0..5u8).map(|i| {
+        Frame::from_parts(
+            RgbaImage::from_pixel(16, 12, image::Rgba([i * 50, 0, 0, 255])),
+            0,
+            0,
+            Delay::from_numer_denom_ms(50, 1),
+        )
+    });
+    encoder.encode_frames(frames).expect("encode GIF");
+    drop(encoder);
+
+    let config = Config::default();
+    let processor = ImageProcessor::new(&config).unwrap();
+    let result = processor
+        .process(&gif_path)
+        .await
+        .expect("animated GIF should process");
+
+    assert_eq!(result.format, "gif");
+    assert_eq!((result.width, result.height), (16, 12));
+    assert_eq!(result.frame_count, Some(5));
+    assert_eq!(result.duration_ms, Some(250));
+    assert!(result.frame_embeddings.is_none());
+}
+
+// ---------------------------------------------------------------------------
+// Boundary: image area over the megapixel limit
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn test_image_over_megapixel_limit() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let img_path = dir.path().join("over_megapixels.png");
+
+    // 1001x1000 is within the side limit but just over 1 MP
+    let img = image::RgbImage::new(1001, 1000);
+    img.save(&img_path).expect("save PNG");
+
+    let mut config = Config::default();
+    config.limits.max_megapixels = 1;
+
+    let processor = ImageProcessor::new(&config).unwrap();
+    let result = processor.process(img_path.as_path()).await;
+
+    match result {
+        Err(PhotonError::Pipeline(PipelineError::ImageTooLarge {
+            width,
+            height,
+            max_megapixels,
+            ..
+        })) => {
+            assert_eq!((width, height), (1001, 1000));
+            assert_eq!(max_megapixels, 1);
+        }
+        other => panic!("expected ImageTooLarge, got: {other:?}"),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // ProcessOptions: all skips enabled (F7)
 // ---------------------------------------------------------------------------
 
 #[tokio::test]
 async fn test_process_with_all_skips() {
-    let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let config = config_with_optional_stages();
+    let processor = ImageProcessor::new(&config).unwrap();
 
     let options = ProcessOptions {
         skip_thumbnail: true,
         skip_perceptual_hash: true,
+        skip_placeholders: true,
+        skip_palette: false,
+        skip_quality: false,
         skip_embedding: true,
         skip_tagging: true,
+        min_sharpness: None,
     };
 
     let result = processor
@@ -629,6 +1045,7 @@ async fn test_process_with_all_skips() {
 
     // All optional stages skipped
     assert!(result.thumbnail.is_none(), "thumbnail should be skipped");
+    assert!(result.blurhash.is_none() && result.thumbhash.is_none());
     assert!(
         result.perceptual_hash.is_none(),
         "perceptual hash should be skipped"
@@ -644,13 +1061,17 @@ async fn test_process_with_all_skips() {
 #[tokio::test]
 async fn test_process_with_selective_skips() {
     let config = Config::default();
-    let processor = ImageProcessor::new(&config);
+    let processor = ImageProcessor::new(&config).unwrap();
 
     let options = ProcessOptions {
         skip_thumbnail: true,
         skip_perceptual_hash: false,
+        skip_placeholders: false,
+        skip_palette: false,
+        skip_quality: false,
         skip_embedding: true,
         skip_tagging: false, // tagging depends on embedding → should produce no tags
+        min_sharpness: None,
     };
 
     let result = processor
@@ -678,3 +1099,47 @@ async fn test_process_with_selective_skips() {
         "tags should be empty without embedding"
     );
 }
+
+// ---------------------------------------------------------------------------
+// Privacy mode: redaction happens inside the processor
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn privacy_mode_hashes_paths_before_output() {
+    let mut config = Config::default();
+    config.output.privacy.enabled = true;
+    config.output.privacy.path_hash_key = "integration-secret".to_string();
+    let processor = ImageProcessor::new(&config).unwrap();
+
+    let path = fixture("beach.jpg");
+    let result = processor
+        .process(path.as_path())
+        .await
+        .expect("beach.jpg should process in privacy mode");
+
+    assert_eq!(result.source_path.as_deref(), Some(path.as_path()));
+    assert_eq!(
+        result.file_path,
+        Some(processor.redactor().unwrap().redact_path(&path))
+    );
+    assert!(result
+        .exif
+        .as_ref()
+        .is_none_or(|e| e.gps_latitude.is_none()));
+
+    let json = serde_json::to_string(&result).expect("serialize");
+    assert!(!json.contains("beach"));
+    assert!(!json.contains("source_path"));
+}
+
+#[tokio::test]
+async fn privacy_mode_without_key_refuses_to_process() {
+    let mut config = Config::default();
+    config.output.privacy.enabled = true;
+    config.output.privacy.path_hash_key = "${PHOTON_TEST_UNSET_PRIVACY_KEY}".to_string();
+
+    let err = ImageProcessor::new(&config)
+        .err()
+        .expect("unredactable output must not be produced");
+    assert!(err.to_string().contains("path_hash_key"));
+}
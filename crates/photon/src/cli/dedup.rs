@@ -0,0 +1,437 @@
+//! The `photon dedup` command: find duplicates in a `photon process` output file.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::{Args, ValueEnum};
+use photon_core::{
+    find_duplicates, Config, DedupOptions, DuplicateCluster, DuplicateKind, DuplicateMember,
+    OutputRecord, ProcessedImage,
+};
+
+/// Arguments for the `dedup` command.
+#[derive(Args, Debug)]
+pub struct DedupArgs {
+    /// Output of `photon process` (JSONL or JSON array)
+    pub input: PathBuf,
+
+    /// Report file (defaults to stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Report format
+    #[arg(short, long, value_enum, default_value = "json")]
+    pub format: DedupFormat,
+
+    /// Perceptual hash to compare [default: first `[[hashing.perceptual]]` entry]
+    #[arg(long)]
+    pub algorithm: Option<String>,
+
+    /// Maximum Hamming distance between perceptual hashes for near-duplicates
+    #[arg(long, default_value = "10")]
+    pub max_distance: u32,
+
+    /// Minimum embedding cosine similarity for semantic duplicates
+    #[arg(long, default_value = "0.95")]
+    pub min_similarity: f32,
+
+    /// Skip near-duplicate detection
+    #[arg(long)]
+    pub no_near: bool,
+
+    /// Skip semantic duplicate detection
+    #[arg(long)]
+    pub no_semantic: bool,
+
+    /// Directory the script moves near and semantic duplicates into
+    #[arg(long, default_value = "duplicates")]
+    pub move_to: PathBuf,
+}
+
+/// Report formats.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum DedupFormat {
+    /// Clusters as a JSON array
+    Json,
+    /// Shell script that removes or moves the duplicates
+    Script,
+}
+
+/// Execute the dedup command.
+pub async fn execute(args: DedupArgs) -> anyhow::Result<()> {
+    if !(0.0..=1.0).contains(&args.min_similarity) {
+        anyhow::bail!("--min-similarity must be between 0 and 1");
+    }
+
+    let algorithm = match args.algorithm {
+        Some(algorithm) => algorithm,
+        None => Config::load()?
+            .hashing
+            .perceptual
+            .first()
+            .map(|entry| entry.algorithm.clone())
+            .unwrap_or_else(|| DedupOptions::default().algorithm),
+    };
+    let options = DedupOptions {
+        algorithm,
+        max_distance: (!args.no_near).then_some(args.max_distance),
+        min_similarity: (!args.no_semantic).then_some(args.min_similarity),
+    };
+
+    let images = load_images(&args.input)?;
+    tracing::info!("Comparing {} images", images.len());
+    let clusters = find_duplicates(&images, &options);
+    print_summary(&clusters);
+
+    let report = match args.format {
+        DedupFormat::Json => serde_json::to_string_pretty(&clusters)? + "\n",
+        DedupFormat::Script => render_script(&clusters, &args.input, &args.move_to),
+    };
+    match &args.output {
+        Some(path) => std::fs::write(path, report)?,
+        None => std::io::stdout().lock().write_all(report.as_bytes())?,
+    }
+    Ok(())
+}
+
+/// Read core records from a JSONL or JSON array output file, ignoring
+/// enrichment patches.
+///
+/// An output file appended to by several runs can hold more than one record
+/// for a path; only the last one is kept, so a file is never reported as a
+/// duplicate of itself.
+fn load_images(path: &Path) -> anyhow::Result<Vec<ProcessedImage>> {
+    read_images(path).map(latest_per_path)
+}
+
+fn read_images(path: &Path) -> anyhow::Result<Vec<ProcessedImage>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+
+    if let Ok(records) = serde_json::from_str::<Vec<OutputRecord>>(&content) {
+        return Ok(records.into_iter().filter_map(core_image).collect());
+    }
+    if let Ok(images) = serde_json::from_str::<Vec<ProcessedImage>>(&content) {
+        return Ok(images);
+    }
+
+    let mut images = Vec::new();
+    let mut skipped_lines = 0u64;
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Ok(record) = serde_json::from_str::<OutputRecord>(line) {
+            images.extend(core_image(record));
+        } else if let Ok(image) = serde_json::from_str::<ProcessedImage>(line) {
+            images.push(image);
+        } else {
+            skipped_lines += 1;
+        }
+    }
+    if skipped_lines > 0 {
+        tracing::warn!(
+            "{skipped_lines} lines in {} could not be parsed",
+            path.display()
+        );
+    }
+    Ok(images)
+}
+
+/// Collapse records sharing a `file_path` to the last one, in the position
+/// of the first. Records without a path are all kept.
+fn latest_per_path(images: Vec<ProcessedImage>) -> Vec<ProcessedImage> {
+    let mut latest: Vec<ProcessedImage> = Vec::with_capacity(images.len());
+    let mut index_by_path: HashMap<PathBuf, usize> = HashMap::new();
+    let mut replaced = 0usize;
+    for image in images {
+        let Some(path) = image.file_path.clone() else {
+            latest.push(image);
+            continue;
+        };
+        match index_by_path.entry(path) {
+            Entry::Occupied(slot) => {
+                latest[*slot.get()] = image;
+                replaced += 1;
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(latest.len());
+                latest.push(image);
+            }
+        }
+    }
+    if replaced > 0 {
+        tracing::info!("Ignoring {replaced} older records for paths listed more than once");
+    }
+    latest
+}
+
+fn core_image(record: OutputRecord) -> Option<ProcessedImage> {
+    match record {
+        OutputRecord::Core(image) => Some(*image),
+        OutputRecord::Enrichment(_) => None,
+    }
+}
+
+/// Render a POSIX shell script acting on every duplicate.
+///
+/// Byte-identical copies of the kept image are removed; anything else is only
+/// a likely duplicate, so it is moved into `move_to` for review instead. Files
+/// that aren't on disk (archive entries, redacted paths) are listed as
+/// comments, and so is a member that resolves to the kept file itself (the
+/// same file recorded under another spelling of its path, or twice).
+///
+/// The script only acts on a cluster whose kept file is on disk. When the
+/// chosen copy isn't (moved since processing, an archive entry, a redacted
+/// path), an on-disk byte-identical copy is kept in its place; with none,
+/// every member is written as a comment.
+fn render_script(clusters: &[DuplicateCluster], input: &Path, move_to: &Path) -> String {
+    let mut script = String::from("#!/bin/sh\n");
+    let _ = writeln!(
+        script,
+        "# Generated by `photon dedup` from {}",
+        input.display()
+    );
+    script.push_str("set -e\n");
+    let moves = clusters.iter().any(|cluster| {
+        cluster
+            .duplicates
+            .iter()
+            .any(|member| member.content_hash != cluster.keep.content_hash)
+    });
+    if moves {
+        let _ = writeln!(script, "mkdir -p -- {}", quote(move_to));
+    }
+
+    for cluster in clusters {
+        let kind = match cluster.kind {
+            DuplicateKind::Exact => "exact",
+            DuplicateKind::Near => "near",
+            DuplicateKind::Semantic => "semantic",
+        };
+        let members = || std::iter::once(&cluster.keep).chain(&cluster.duplicates);
+        let kept = members()
+            .filter(|member| member.content_hash == cluster.keep.content_hash)
+            .find_map(|member| on_disk(member).map(|path| (member, path)));
+        let Some((kept, keep_path)) = kept else {
+            let _ = writeln!(script, "\n# {kind}: kept file not on disk, nothing to do");
+            for member in members() {
+                let _ = writeln!(script, "#   {}", describe(member));
+            }
+            continue;
+        };
+
+        let _ = writeln!(script, "\n# {kind}: keep {}", describe(kept));
+        for member in members().filter(|member| !std::ptr::eq(*member, kept)) {
+            let Some(path) = member.file_path.as_deref().filter(|p| p.is_file()) else {
+                let _ = writeln!(script, "# not on disk: {}", describe(member));
+                continue;
+            };
+            if std::fs::canonicalize(path).ok().as_ref() == Some(&keep_path) {
+                let _ = writeln!(script, "# same file as kept: {}", describe(member));
+                continue;
+            }
+            if member.content_hash == cluster.keep.content_hash {
+                let _ = writeln!(script, "rm -- {}", quote(path));
+            } else {
+                // Prefix with the content hash so same-named files don't collide
+                let hash_prefix = &member.content_hash[..member.content_hash.len().min(12)];
+                let target = move_to.join(format!("{hash_prefix}_{}", member.file_name));
+                let _ = writeln!(script, "mv -- {} {}", quote(path), quote(&target));
+            }
+        }
+    }
+    script
+}
+
+/// Canonical path of a member that exists as a regular file.
+fn on_disk(member: &DuplicateMember) -> Option<PathBuf> {
+    member
+        .file_path
+        .as_deref()
+        .filter(|path| path.is_file())
+        .and_then(|path| std::fs::canonicalize(path).ok())
+}
+
+/// Path (or file name, when there is none) for a script comment.
+fn describe(member: &DuplicateMember) -> String {
+    match &member.file_path {
+        Some(path) => path.display().to_string(),
+        None => member.file_name.clone(),
+    }
+    .replace('\n', " ")
+}
+
+/// Single-quote a path for the shell.
+fn quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', r"'\''"))
+}
+
+fn print_summary(clusters: &[DuplicateCluster]) {
+    let count = |kind| clusters.iter().filter(|c| c.kind == kind).count();
+    let duplicates: usize = clusters.iter().map(|c| c.duplicates.len()).sum();
+    let bytes: u64 = clusters
+        .iter()
+        .flat_map(|c| &c.duplicates)
+        .map(|m| m.file_size)
+        .sum();
+
+    eprintln!(
+        "Found {} duplicate clusters ({} exact, {} near, {} semantic)",
+        clusters.len(),
+        count(DuplicateKind::Exact),
+        count(DuplicateKind::Near),
+        count(DuplicateKind::Semantic),
+    );
+    eprintln!(
+        "{duplicates} duplicates, {:.1} MB reclaimable",
+        bytes as f64 / 1_000_000.0
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(path: &Path, content_hash: &str) -> DuplicateMember {
+        DuplicateMember {
+            file_path: Some(path.to_path_buf()),
+            file_name: path.file_name().unwrap().to_string_lossy().into_owned(),
+            content_hash: content_hash.to_string(),
+            width: 10,
+            height: 10,
+            file_size: 100,
+            sharpness: None,
+            hamming_distance: None,
+            similarity: None,
+        }
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote(Path::new("/a b/c.jpg")), "'/a b/c.jpg'");
+        assert_eq!(quote(Path::new("it's.jpg")), r"'it'\''s.jpg'");
+    }
+
+    #[test]
+    fn test_script_removes_copies_and_moves_lookalikes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, b"x").unwrap();
+            path
+        };
+        let clusters = vec![
+            DuplicateCluster {
+                kind: DuplicateKind::Near,
+                keep: member(&path("keep.jpg"), "aaaa"),
+                duplicates: vec![
+                    member(&path("copy.jpg"), "aaaa"),
+                    member(&path("resized.jpg"), "bbbbbbbbbbbbbbbbbbbb"),
+                ],
+            },
+            DuplicateCluster {
+                kind: DuplicateKind::Exact,
+                keep: member(&path("x.jpg"), "cccc"),
+                duplicates: vec![member(Path::new("/sets/a.zip!/x.jpg"), "cccc")],
+            },
+        ];
+
+        let script = render_script(&clusters, Path::new("out.jsonl"), Path::new("dups"));
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("mkdir -p -- 'dups'"));
+        assert!(script.contains(&format!("rm -- {}", quote(&dir.path().join("copy.jpg")))));
+        assert!(script.contains(&format!(
+            "mv -- {} 'dups/bbbbbbbbbbbb_resized.jpg'",
+            quote(&dir.path().join("resized.jpg"))
+        )));
+        assert!(script.contains("# not on disk: /sets/a.zip!/x.jpg"));
+        assert!(!script.contains(&format!("rm -- {}", quote(&dir.path().join("keep.jpg")))));
+    }
+
+    #[test]
+    fn test_script_never_removes_the_only_copy_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let copy = dir.path().join("x.jpg");
+        std::fs::write(&copy, b"x").unwrap();
+        let moved_away = dir.path().join("gone.jpg");
+
+        // The kept image lives in an archive, or has been moved since
+        let clusters = vec![
+            DuplicateCluster {
+                kind: DuplicateKind::Exact,
+                keep: member(Path::new("/sets/a.zip!/x.jpg"), "cccc"),
+                duplicates: vec![member(&copy, "cccc")],
+            },
+            DuplicateCluster {
+                kind: DuplicateKind::Near,
+                keep: member(&moved_away, "dddd"),
+                duplicates: vec![member(&copy, "eeee")],
+            },
+        ];
+        let script = render_script(&clusters, Path::new("out.jsonl"), Path::new("dups"));
+        assert!(!script.contains("rm --"), "{script}");
+        assert!(!script.contains("mv --"), "{script}");
+        // An on-disk exact copy stands in for the archived one
+        assert!(script.contains(&format!("# exact: keep {}", copy.display())));
+        assert!(script.contains("# not on disk: /sets/a.zip!/x.jpg"));
+        assert!(script.contains("# near: kept file not on disk, nothing to do"));
+    }
+
+    fn record(path: &str, content_hash: &str) -> String {
+        format!(
+            r#"{{"file_path":"{path}","file_name":"a.jpg","content_hash":"{content_hash}","width":1,"height":1,"format":"jpeg","file_size":1,"embedding":[],"tags":[]}}"#
+        )
+    }
+
+    #[test]
+    fn test_load_images_jsonl_and_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = record("/a.jpg", "h");
+        let other = record("/b.jpg", "h");
+        let enrichment = r#"{"type":"enrichment","content_hash":"h","description":"d","llm_model":"m","llm_latency_ms":1}"#;
+
+        let jsonl = dir.path().join("out.jsonl");
+        let core = format!(r#"{{"type":"core",{}"#, &image[1..]);
+        std::fs::write(&jsonl, format!("{core}\n{enrichment}\n{other}\nnot json\n")).unwrap();
+        assert_eq!(load_images(&jsonl).unwrap().len(), 2);
+
+        let json = dir.path().join("out.json");
+        std::fs::write(&json, format!("[{image},{other}]")).unwrap();
+        assert_eq!(load_images(&json).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_repeated_path_is_not_its_own_duplicate() {
+        // Two runs appended to one output file: the same path twice
+        let dir = tempfile::tempdir().unwrap();
+        let photo = dir.path().join("a.jpg");
+        std::fs::write(&photo, b"x").unwrap();
+        let photo_str = photo.to_str().unwrap();
+        let jsonl = dir.path().join("out.jsonl");
+        std::fs::write(
+            &jsonl,
+            format!(
+                "{}\n{}\n",
+                record(photo_str, "old"),
+                record(photo_str, "new")
+            ),
+        )
+        .unwrap();
+
+        let images = load_images(&jsonl).unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].content_hash, "new");
+        assert!(find_duplicates(&images, &DedupOptions::default()).is_empty());
+
+        // The script still never removes the kept file under another spelling
+        let clusters = vec![DuplicateCluster {
+            kind: DuplicateKind::Exact,
+            keep: member(&photo, "new"),
+            duplicates: vec![member(&dir.path().join(".").join("a.jpg"), "new")],
+        }];
+        let script = render_script(&clusters, &jsonl, Path::new("dups"));
+        assert!(!script.contains("rm --"));
+        assert!(script.contains("# same file as kept:"));
+    }
+}
@@ -0,0 +1,176 @@
+//! The `photon embed-text` command: embed search queries in the image space.
+
+use std::fs::File;
+use std::io::{BufRead, BufWriter, IsTerminal, Write};
+use std::path::PathBuf;
+
+use clap::Args;
+use photon_core::{Config, OutputFormat as CoreOutputFormat, OutputWriter, TextEmbedder};
+use serde::Serialize;
+
+use super::process::{apply_quality, OutputFormat, Quality};
+
+/// Queries embedded and written per step when streaming JSONL.
+const CHUNK_SIZE: usize = 256;
+
+/// Arguments for the `embed-text` command.
+#[derive(Args, Debug)]
+pub struct EmbedTextArgs {
+    /// Queries to embed; with none, one query per line is read from stdin
+    pub queries: Vec<String>,
+
+    /// Output file (defaults to stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "json")]
+    pub format: OutputFormat,
+
+    /// The `--quality` preset used for `photon process`, so queries land in
+    /// the same embedding space as the images
+    #[arg(long, value_enum, default_value = "fast")]
+    pub quality: Quality,
+}
+
+/// One embedded query. `model` identifies the embedding space, so vectors
+/// can be checked against the model an image index was built with.
+#[derive(Debug, Serialize)]
+struct TextEmbedding<'a> {
+    model: &'a str,
+    text: &'a str,
+    embedding: Vec<f32>,
+}
+
+/// Execute the embed-text command.
+pub async fn execute(args: EmbedTextArgs) -> anyhow::Result<()> {
+    let queries = if args.queries.is_empty() {
+        let stdin = std::io::stdin();
+        if stdin.is_terminal() {
+            anyhow::bail!(
+                "No queries given.\n\n  \
+                 Hint: pass them as arguments, or pipe one per line on stdin."
+            );
+        }
+        read_queries(stdin.lock())?
+    } else {
+        args.queries
+    };
+    if queries.is_empty() {
+        anyhow::bail!("No queries to embed (stdin had no non-empty lines)");
+    }
+
+    let mut config = Config::load()?;
+    apply_quality(&mut config, args.quality);
+    let model_dir = config.model_dir();
+    if !TextEmbedder::model_exists(&config.embedding, &model_dir) {
+        anyhow::bail!(
+            "Text encoder for model '{}' not found.\n\n  \
+             Hint: Run `photon models download` first.",
+            config.embedding.model
+        );
+    }
+    let embedder = TextEmbedder::load(&config.embedding, &model_dir)?;
+
+    let sink: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(std::io::stdout().lock())),
+    };
+
+    match args.format {
+        OutputFormat::Json => {
+            let embeddings = embedder.encode(&queries)?;
+            let records = to_records(embedder.model(), &queries, embeddings);
+            let mut writer = OutputWriter::new(sink, CoreOutputFormat::Json, true);
+            writer.write_all(&records)?;
+            writer.flush()?;
+        }
+        OutputFormat::Jsonl => {
+            // Stream so bulk input never holds every vector at once
+            let mut writer = OutputWriter::new(sink, CoreOutputFormat::JsonLines, false);
+            for chunk in queries.chunks(CHUNK_SIZE) {
+                let embeddings = embedder.encode(chunk)?;
+                writer.write_all(&to_records(embedder.model(), chunk, embeddings))?;
+            }
+            writer.flush()?;
+        }
+    }
+
+    tracing::info!(
+        "Embedded {} quer{} with {} ({}-d)",
+        queries.len(),
+        if queries.len() == 1 { "y" } else { "ies" },
+        embedder.model(),
+        embedder.embedding_dim()
+    );
+    Ok(())
+}
+
+/// One query per line; surrounding whitespace trimmed, blank lines skipped.
+fn read_queries(reader: impl BufRead) -> anyhow::Result<Vec<String>> {
+    let mut queries = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let query = line.trim();
+        if !query.is_empty() {
+            queries.push(query.to_string());
+        }
+    }
+    Ok(queries)
+}
+
+/// Pair each query with its vector.
+fn to_records<'a>(
+    model: &'a str,
+    queries: &'a [String],
+    embeddings: Vec<Vec<f32>>,
+) -> Vec<TextEmbedding<'a>> {
+    queries
+        .iter()
+        .zip(embeddings)
+        .map(|(text, embedding)| TextEmbedding {
+            model,
+            text,
+            embedding,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: EmbedTextArgs,
+    }
+
+    #[test]
+    fn test_quality_defaults_to_fast() {
+        let cli = Cli::parse_from(["embed-text", "red car"]);
+        assert!(matches!(cli.args.quality, Quality::Fast));
+
+        let cli = Cli::parse_from(["embed-text", "--quality", "high", "red car"]);
+        assert!(matches!(cli.args.quality, Quality::High));
+    }
+
+    #[test]
+    fn test_read_queries_skips_blank_lines() {
+        let input = "red car at night\n\n  a dog on a beach  \r\n\t\nsunset\n";
+        let queries = read_queries(input.as_bytes()).unwrap();
+        assert_eq!(queries, ["red car at night", "a dog on a beach", "sunset"]);
+    }
+
+    #[test]
+    fn test_records_carry_model_id() {
+        let queries = vec!["a".to_string(), "b".to_string()];
+        let records = to_records("siglip-base-patch16", &queries, vec![vec![1.0], vec![0.0]]);
+        let json = serde_json::to_string(&records[1]).unwrap();
+        assert_eq!(
+            json,
+            r#"{"model":"siglip-base-patch16","text":"b","embedding":[0.0]}"#
+        );
+    }
+}
@@ -1,8 +1,8 @@
 //! Guided model management — check install status and offer downloads.
 
 use crate::cli::models::{
-    check_installed, download_shared, download_vision, install_vocabulary, InstalledModels,
-    VARIANT_LABELS,
+    check_installed, download_geocoding, download_shared, download_vision, install_vocabulary,
+    InstalledModels,
 };
 use console::Style;
 use dialoguer::Select;
@@ -22,17 +22,23 @@ pub async fn guided_models(config: &Config) -> anyhow::Result<()> {
         let mut actions: Vec<ModelAction> = Vec::new();
 
         // Offer downloads for missing components
-        if !status.vision_224 {
-            items.push("Download Base (224) vision model".to_string());
-            actions.push(ModelAction::DownloadVision(vec![0]));
+        let missing: Vec<String> = status
+            .vision
+            .iter()
+            .filter(|v| !v.installed && v.manifest.download.is_some())
+            .map(|v| v.manifest.name.clone())
+            .collect();
+        for v in status
+            .vision
+            .iter()
+            .filter(|v| missing.contains(&v.manifest.name))
+        {
+            items.push(format!("Download {} vision model", v.manifest.label));
+            actions.push(ModelAction::DownloadVision(vec![v.manifest.name.clone()]));
         }
-        if !status.vision_384 {
-            items.push("Download Base (384) vision model".to_string());
-            actions.push(ModelAction::DownloadVision(vec![1]));
-        }
-        if !status.vision_224 && !status.vision_384 {
-            items.push("Download both vision models".to_string());
-            actions.push(ModelAction::DownloadVision(vec![0, 1]));
+        if missing.len() > 1 && !status.vision.iter().any(|v| v.installed) {
+            items.push("Download all vision models".to_string());
+            actions.push(ModelAction::DownloadVision(missing));
         }
         if !status.text_encoder || !status.tokenizer {
             items.push("Download text encoder + tokenizer".to_string());
@@ -42,6 +48,10 @@ pub async fn guided_models(config: &Config) -> anyhow::Result<()> {
             items.push("Install vocabulary files".to_string());
             actions.push(ModelAction::InstallVocabulary);
         }
+        if !status.geocoding {
+            items.push("Download geocoding dataset".to_string());
+            actions.push(ModelAction::DownloadGeocoding);
+        }
 
         // Always available
         items.push("Show model directory".to_string());
@@ -113,6 +123,21 @@ pub async fn guided_models(config: &Config) -> anyhow::Result<()> {
                     }
                     eprintln!();
                 }
+                ModelAction::DownloadGeocoding => {
+                    let client = reqwest::Client::new();
+                    match download_geocoding(config, &client).await {
+                        Ok(()) => {
+                            let done = Style::new().for_stderr().green();
+                            eprintln!("{}", done.apply_to("  Geocoding dataset installed."));
+                        }
+                        Err(e) => {
+                            let err_style = Style::new().for_stderr().red();
+                            eprintln!("  {} Download failed: {e}", err_style.apply_to("✗"));
+                            eprintln!("  Check your network connection and try again.");
+                        }
+                    }
+                    eprintln!();
+                }
                 ModelAction::ShowPath => {
                     eprintln!("  {}", config.model_dir().display());
                     eprintln!();
@@ -157,8 +182,13 @@ fn print_status(status: &InstalledModels, config: &Config) {
     );
     eprintln!();
 
-    check(status.vision_224, VARIANT_LABELS[0], "visual.onnx ~348 MB");
-    check(status.vision_384, VARIANT_LABELS[1], "visual.onnx ~348 MB");
+    for v in &status.vision {
+        let detail = match v.manifest.download.as_ref().and_then(|d| d.size_mb) {
+            Some(size_mb) => format!("visual.onnx ~{size_mb} MB"),
+            None => "visual.onnx".to_string(),
+        };
+        check(v.installed, &v.manifest.label, &detail);
+    }
     check(
         status.text_encoder,
         "Text encoder",
@@ -166,14 +196,16 @@ fn print_status(status: &InstalledModels, config: &Config) {
     );
     check(status.tokenizer, "Tokenizer", "tokenizer.json ~1 MB");
     check(status.vocabulary, "Vocabulary", "wordnet + supplemental");
+    check(status.geocoding, "Geocoding", "GeoNames + countries ~35 MB");
     eprintln!();
 }
 
 /// Internal action type for the model menu.
 enum ModelAction {
-    DownloadVision(Vec<usize>),
+    DownloadVision(Vec<String>),
     DownloadShared,
     InstallVocabulary,
+    DownloadGeocoding,
     ShowPath,
     Back,
 }
@@ -205,7 +205,7 @@ pub async fn guided_process(config: &Config) -> anyhow::Result<()> {
         // ── Step 8: Build ProcessArgs and delegate ──────────────────────────────
 
         let args = ProcessArgs {
-            input,
+            input: Some(input),
             output,
             format,
             quality,
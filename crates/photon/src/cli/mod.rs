@@ -1,6 +1,8 @@
 //! CLI command modules.
 
 pub mod config;
+pub mod dedup;
+pub mod embed_text;
 pub mod interactive;
 pub mod models;
 pub mod process;
@@ -1,7 +1,7 @@
 //! The `photon models` command for managing AI models.
 
 use clap::{Args, Subcommand};
-use photon_core::{Config, Hasher};
+use photon_core::{Config, Geocoder, Hasher, ModelManifest, ModelRegistry};
 use std::path::Path;
 
 /// Arguments for the `models` command.
@@ -15,7 +15,11 @@ pub struct ModelsArgs {
 #[derive(Subcommand, Debug)]
 pub enum ModelsCommand {
     /// Download required models (SigLIP vision + text encoder + tokenizer)
-    Download,
+    Download {
+        /// Also download the offline geocoding dataset (GeoNames + country outlines)
+        #[arg(long)]
+        geocoding: bool,
+    },
 
     /// List installed models
     List,
@@ -24,32 +28,6 @@ pub enum ModelsCommand {
     Path,
 }
 
-/// Available SigLIP vision model variants.
-struct ModelVariant {
-    name: &'static str,
-    label: &'static str,
-    repo: &'static str,
-    remote_path: &'static str,
-    blake3: &'static str,
-}
-
-const VISION_VARIANTS: &[ModelVariant] = &[
-    ModelVariant {
-        name: "siglip-base-patch16",
-        label: "Base (224)",
-        repo: "Xenova/siglip-base-patch16-224",
-        remote_path: "onnx/vision_model.onnx",
-        blake3: "05cd313b67db70acd8e800cd4c16105c3ebc4c385fe6002108d24ea806a248be",
-    },
-    ModelVariant {
-        name: "siglip-base-patch16-384",
-        label: "Base (384)",
-        repo: "Xenova/siglip-base-patch16-384",
-        remote_path: "onnx/vision_model.onnx",
-        blake3: "9a4dcfd0c21b8e4d143652d1e566da52222605b564979723383f6012b53dd0df",
-    },
-];
-
 /// Shared models (always downloaded alongside vision models).
 const TEXT_ENCODER_REPO: &str = "Xenova/siglip-base-patch16-224";
 const TEXT_ENCODER_REMOTE: &str = "onnx/text_model.onnx";
@@ -60,6 +38,47 @@ const TEXT_ENCODER_BLAKE3: &str =
     "fe62b4096a9e5c3ce735b771472c9e3faac6ddeceebab5794a0a5ce17ee171dd";
 const TOKENIZER_BLAKE3: &str = "cf171f3552992f467891b9d59be5bde1256ffe1344c62030d4bf0f87df583906";
 
+/// A geocoding dataset file and where to fetch it.
+struct GeocodingSource {
+    /// Local (and remote) filename.
+    name: &'static str,
+    /// Download URL.
+    url: &'static str,
+    /// Expected BLAKE3 checksum, once the source is pinned to a snapshot.
+    blake3: Option<&'static str>,
+}
+
+/// Geocoding dataset sources (GeoNames dump + country outlines).
+///
+/// Neither upstream publishes versioned snapshots (the GeoNames dump is
+/// regenerated daily and geo-countries only has `main`), so these URLs move
+/// under us and carry no checksum until they point at a mirrored snapshot.
+/// Because of that the dataset is only fetched on request (`--geocoding`).
+/// When pinning, set the URL and checksum together; `download_file` verifies
+/// any checksum given here.
+const GEOCODING_SOURCES: [GeocodingSource; 4] = [
+    GeocodingSource {
+        name: Geocoder::ADMIN1_FILE,
+        url: "https://download.geonames.org/export/dump/admin1CodesASCII.txt",
+        blake3: None,
+    },
+    GeocodingSource {
+        name: Geocoder::COUNTRY_INFO_FILE,
+        url: "https://download.geonames.org/export/dump/countryInfo.txt",
+        blake3: None,
+    },
+    GeocodingSource {
+        name: Geocoder::CITIES_ARCHIVE,
+        url: "https://download.geonames.org/export/dump/cities1000.zip",
+        blake3: None,
+    },
+    GeocodingSource {
+        name: Geocoder::COUNTRIES_FILE,
+        url: "https://raw.githubusercontent.com/datasets/geo-countries/main/data/countries.geojson",
+        blake3: None,
+    },
+];
+
 /// Local filenames.
 const VISUAL_MODEL_LOCAL_NAME: &str = "visual.onnx";
 const TEXT_MODEL_LOCAL_NAME: &str = "text_model.onnx";
@@ -67,19 +86,26 @@ const TOKENIZER_LOCAL_NAME: &str = "tokenizer.json";
 
 // ── Reusable public API (used by both flag-based CLI and interactive module) ──
 
+/// Install state of one vision model from the registry.
+pub struct VisionModelStatus {
+    pub manifest: ModelManifest,
+    pub installed: bool,
+}
+
 /// Status of each model file on disk.
 pub struct InstalledModels {
-    pub vision_224: bool,
-    pub vision_384: bool,
+    /// Every registered vision model, built-ins first.
+    pub vision: Vec<VisionModelStatus>,
     pub text_encoder: bool,
     pub tokenizer: bool,
     pub vocabulary: bool,
+    pub geocoding: bool,
 }
 
 impl InstalledModels {
     /// Returns true if the minimum required models are present for processing.
     pub fn can_process(&self) -> bool {
-        (self.vision_224 || self.vision_384) && self.text_encoder && self.tokenizer
+        self.vision.iter().any(|v| v.installed) && self.text_encoder && self.tokenizer
     }
 }
 
@@ -88,35 +114,45 @@ pub fn check_installed(config: &Config) -> InstalledModels {
     let model_dir = config.model_dir();
     let vocab_dir = config.vocabulary_dir();
 
+    let vision = ModelRegistry::load(&model_dir)
+        .models()
+        .iter()
+        .map(|manifest| VisionModelStatus {
+            installed: model_dir
+                .join(&manifest.name)
+                .join(VISUAL_MODEL_LOCAL_NAME)
+                .exists(),
+            manifest: manifest.clone(),
+        })
+        .collect();
+
     InstalledModels {
-        vision_224: model_dir
-            .join(VISION_VARIANTS[0].name)
-            .join(VISUAL_MODEL_LOCAL_NAME)
-            .exists(),
-        vision_384: model_dir
-            .join(VISION_VARIANTS[1].name)
-            .join(VISUAL_MODEL_LOCAL_NAME)
-            .exists(),
+        vision,
         text_encoder: model_dir.join(TEXT_MODEL_LOCAL_NAME).exists(),
         tokenizer: model_dir.join(TOKENIZER_LOCAL_NAME).exists(),
         vocabulary: vocab_dir.join("wordnet_nouns.txt").exists()
             && vocab_dir.join("supplemental.txt").exists(),
+        geocoding: Geocoder::dataset_exists(&config.geocoding_dir()),
     }
 }
 
-/// Download vision model variant(s) by index (0 = Base 224, 1 = Base 384).
+/// Download vision models by registry name (e.g. "siglip-base-patch16").
 ///
-/// Skips already-downloaded files.
+/// Skips already-downloaded files. Models without a download source in their
+/// manifest must be installed by hand.
 pub async fn download_vision(
-    variant_indices: &[usize],
+    names: &[String],
     config: &Config,
     client: &reqwest::Client,
 ) -> anyhow::Result<()> {
     let model_dir = config.model_dir();
+    let registry = ModelRegistry::load(&model_dir);
 
-    for &idx in variant_indices {
-        let variant = &VISION_VARIANTS[idx];
-        let variant_dir = model_dir.join(variant.name);
+    for name in names {
+        let variant = registry
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown model '{name}'"))?;
+        let variant_dir = model_dir.join(&variant.name);
         let dest = variant_dir.join(VISUAL_MODEL_LOCAL_NAME);
 
         if dest.exists() {
@@ -124,18 +160,25 @@ pub async fn download_vision(
             continue;
         }
 
+        let Some(source) = &variant.download else {
+            anyhow::bail!(
+                "Model '{name}' has no download source; place its vision encoder at {}",
+                dest.display()
+            );
+        };
+
         std::fs::create_dir_all(&variant_dir)?;
 
         let url = format!(
             "https://huggingface.co/{}/resolve/main/{}",
-            variant.repo, variant.remote_path
+            source.repo, source.remote_path
         );
 
         tracing::info!("Downloading {} vision encoder...", variant.label);
         tracing::info!("  Source: {}", url);
         tracing::info!("  Destination: {:?}", dest);
 
-        download_file(client, &url, &dest, Some(variant.blake3)).await?;
+        download_file(client, &url, &dest, source.blake3.as_deref()).await?;
 
         let file_size = std::fs::metadata(&dest)?.len();
         tracing::info!(
@@ -192,22 +235,55 @@ pub async fn download_shared(config: &Config, client: &reqwest::Client) -> anyho
     Ok(())
 }
 
-/// Public variant labels for display in interactive mode.
-pub const VARIANT_LABELS: &[&str] = &["Base (224)", "Base (384)"];
+/// Download the offline geocoding dataset into the geocoding directory.
+///
+/// The cities dump is kept zipped; the geocoder reads it in place.
+/// Skips files that are already present.
+pub async fn download_geocoding(config: &Config, client: &reqwest::Client) -> anyhow::Result<()> {
+    let geo_dir = config.geocoding_dir();
+    std::fs::create_dir_all(&geo_dir)?;
+
+    for source in &GEOCODING_SOURCES {
+        let dest = geo_dir.join(source.name);
+        // An already-extracted cities dump makes the archive unnecessary
+        let extracted =
+            source.name == Geocoder::CITIES_ARCHIVE && geo_dir.join(Geocoder::CITIES_FILE).exists();
+        if dest.exists() || extracted {
+            tracing::info!("{} already exists at {:?}", source.name, dest);
+            continue;
+        }
+        tracing::info!("Downloading {}...", source.name);
+        tracing::info!("  Source: {}", source.url);
+        tracing::info!("  Destination: {:?}", dest);
+        if let Err(e) = download_file(client, source.url, &dest, source.blake3).await {
+            // Don't leave a partial file that would pass `dataset_exists`
+            let _ = std::fs::remove_file(&dest);
+            return Err(e);
+        }
+    }
+
+    Ok(())
+}
 
 /// Execute the models command.
 pub async fn execute(args: ModelsArgs) -> anyhow::Result<()> {
     let config = Config::load()?;
 
     match args.command {
-        ModelsCommand::Download => {
-            tracing::info!("Downloading Base (224) vision encoder...");
-
+        ModelsCommand::Download { geocoding } => {
             let client = reqwest::Client::new();
 
-            download_vision(&[0], &config, &client).await?;
+            download_vision(
+                std::slice::from_ref(&config.embedding.model),
+                &config,
+                &client,
+            )
+            .await?;
             download_shared(&config, &client).await?;
             install_vocabulary(&config)?;
+            if geocoding {
+                download_geocoding(&config, &client).await?;
+            }
 
             tracing::info!("All downloads complete.");
         }
@@ -226,8 +302,8 @@ pub async fn execute(args: ModelsArgs) -> anyhow::Result<()> {
 
             // Vision encoders
             println!("  Vision encoders:");
-            for variant in VISION_VARIANTS {
-                let variant_dir = model_dir.join(variant.name);
+            for variant in ModelRegistry::load(&model_dir).models() {
+                let variant_dir = model_dir.join(&variant.name);
                 let visual_path = variant_dir.join(VISUAL_MODEL_LOCAL_NAME);
                 let status = if visual_path.exists() {
                     "ready"
@@ -239,7 +315,11 @@ pub async fn execute(args: ModelsArgs) -> anyhow::Result<()> {
                 } else {
                     ""
                 };
-                println!("    - {:30} {:14}{}", variant.name, status, default_marker);
+                let shape = format!("{}-d {}px", variant.embedding_dim, variant.image_size);
+                println!(
+                    "    - {:30} {:14} {:12}{}",
+                    variant.name, status, shape, default_marker
+                );
             }
 
             // Shared models
@@ -278,6 +358,16 @@ pub async fn execute(args: ModelsArgs) -> anyhow::Result<()> {
                 "not installed"
             };
             println!("    - {:30} {}", "supplemental.txt", supp_status);
+
+            // Geocoding dataset
+            let geo_dir = config.geocoding_dir();
+            let geo_status = if Geocoder::dataset_exists(&geo_dir) {
+                "ready"
+            } else {
+                "not installed"
+            };
+            println!("\n  Geocoding:");
+            println!("    - {:30} {}", geo_dir.display(), geo_status);
         }
 
         ModelsCommand::Path => {
@@ -449,14 +539,27 @@ mod tests {
 
     // ── InstalledModels::can_process tests ──────────────────────────────
 
+    /// Install states for the built-in vision models, in registry order.
+    fn vision(installed: &[bool]) -> Vec<VisionModelStatus> {
+        ModelRegistry::builtin()
+            .models()
+            .iter()
+            .zip(installed)
+            .map(|(manifest, &installed)| VisionModelStatus {
+                manifest: manifest.clone(),
+                installed,
+            })
+            .collect()
+    }
+
     #[test]
     fn can_process_all_present() {
         let m = InstalledModels {
-            vision_224: true,
-            vision_384: true,
+            vision: vision(&[true, true]),
             text_encoder: true,
             tokenizer: true,
             vocabulary: true,
+            geocoding: true,
         };
         assert!(m.can_process());
     }
@@ -464,11 +567,11 @@ mod tests {
     #[test]
     fn can_process_only_224_with_shared() {
         let m = InstalledModels {
-            vision_224: true,
-            vision_384: false,
+            vision: vision(&[true, false]),
             text_encoder: true,
             tokenizer: true,
             vocabulary: false,
+            geocoding: false,
         };
         assert!(m.can_process());
     }
@@ -476,11 +579,11 @@ mod tests {
     #[test]
     fn can_process_only_384_with_shared() {
         let m = InstalledModels {
-            vision_224: false,
-            vision_384: true,
+            vision: vision(&[false, true]),
             text_encoder: true,
             tokenizer: true,
             vocabulary: false,
+            geocoding: false,
         };
         assert!(m.can_process());
     }
@@ -488,11 +591,11 @@ mod tests {
     #[test]
     fn can_process_no_vision_models() {
         let m = InstalledModels {
-            vision_224: false,
-            vision_384: false,
+            vision: vision(&[false, false]),
             text_encoder: true,
             tokenizer: true,
             vocabulary: true,
+            geocoding: true,
         };
         assert!(!m.can_process());
     }
@@ -500,11 +603,11 @@ mod tests {
     #[test]
     fn can_process_vision_but_no_text_encoder() {
         let m = InstalledModels {
-            vision_224: true,
-            vision_384: false,
+            vision: vision(&[true, false]),
             text_encoder: false,
             tokenizer: true,
             vocabulary: true,
+            geocoding: true,
         };
         assert!(!m.can_process());
     }
@@ -512,11 +615,11 @@ mod tests {
     #[test]
     fn can_process_vision_but_no_tokenizer() {
         let m = InstalledModels {
-            vision_224: true,
-            vision_384: false,
+            vision: vision(&[true, false]),
             text_encoder: true,
             tokenizer: false,
             vocabulary: true,
+            geocoding: true,
         };
         assert!(!m.can_process());
     }
@@ -524,12 +627,48 @@ mod tests {
     #[test]
     fn can_process_all_false() {
         let m = InstalledModels {
-            vision_224: false,
-            vision_384: false,
+            vision: vision(&[false, false]),
             text_encoder: false,
             tokenizer: false,
             vocabulary: false,
+            geocoding: false,
         };
         assert!(!m.can_process());
     }
+
+    #[test]
+    fn check_installed_lists_manifest_models() {
+        let dir = tempfile::tempdir().unwrap();
+        let so400m = dir.path().join("siglip-so400m-patch14-384");
+        std::fs::create_dir_all(&so400m).unwrap();
+        std::fs::write(
+            so400m.join(photon_core::MANIFEST_FILENAME),
+            r#"{"embedding_dim": 1152, "image_size": 384,
+                "mean": [0.5, 0.5, 0.5], "std": [0.5, 0.5, 0.5],
+                "text_max_length": 64, "vision_output": "pooler_output",
+                "text_output": "pooler_output", "logit_scale": 117.33, "logit_bias": -12.93}"#,
+        )
+        .unwrap();
+        std::fs::write(so400m.join(VISUAL_MODEL_LOCAL_NAME), b"onnx").unwrap();
+
+        let mut config = Config::default();
+        config.general.model_dir = dir.path().to_path_buf();
+        let status = check_installed(&config);
+
+        let names: Vec<&str> = status
+            .vision
+            .iter()
+            .map(|v| v.manifest.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "siglip-base-patch16",
+                "siglip-base-patch16-384",
+                "siglip-so400m-patch14-384"
+            ]
+        );
+        assert!(status.vision[2].installed);
+        assert!(!status.vision[0].installed);
+    }
 }
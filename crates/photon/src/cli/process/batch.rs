@@ -2,12 +2,16 @@
 
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufWriter;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
 
 use futures_util::stream::{self, StreamExt};
-use photon_core::{DiscoveredFile, OutputRecord, OutputWriter, ProcessedImage};
+use photon_core::{
+    ArchiveEntry, ArchiveReader, DiscoveredFile, FilterCounts, OutputRecord, OutputWriter,
+    PipelineError, ProcessedImage,
+};
+use serde::{Deserialize, Serialize};
 
 use super::enrichment::{run_enrichment_collect, run_enrichment_stdout};
 use super::types::OutputFormat;
@@ -15,24 +19,33 @@ use super::{ProcessArgs, ProcessContext};
 
 /// Process a directory of images with progress tracking and optional LLM enrichment.
 ///
-/// Images are processed concurrently using `buffer_unordered(parallel)`. While one
-/// image waits on the ONNX mutex for embedding, others decode/hash/thumbnail on
-/// tokio's blocking thread pool. Results are consumed single-threaded for output.
+/// Images are processed concurrently using `buffer_unordered(parallel)`. Their
+/// embedding tensors are batched across images (`pipeline.max_batch_size`), and
+/// while a batch runs, others decode/hash/thumbnail on tokio's blocking thread
+/// pool. Results are consumed single-threaded for output.
+///
+/// `filtered` counts the files the discovery filters dropped, for the summary.
 pub async fn process_batch(
     ctx: ProcessContext,
     args: &ProcessArgs,
     files: Vec<DiscoveredFile>,
+    filtered: FilterCounts,
 ) -> anyhow::Result<()> {
     // Destructure context: processor and options go into Arc for concurrent sharing,
     // the rest is used only in the single-threaded result-handling loop and post-loop code.
     let ProcessContext {
-        processor,
-        options,
+        mut processor,
+        mut options,
         mut enricher,
         output_format,
         llm_enabled,
         config,
     } = ctx;
+    if !options.skip_embedding {
+        processor.enable_embed_batching(&config);
+    }
+    // Images under --min-sharpness are dropped, so skip their model stages
+    options.min_sharpness = args.min_sharpness;
     let processor = Arc::new(processor);
     let options = Arc::new(options);
 
@@ -55,6 +68,7 @@ pub async fn process_batch(
 
     let mut succeeded: u64 = 0;
     let mut failed: u64 = 0;
+    let mut blurry: u64 = 0;
     let mut total_bytes: u64 = 0;
     let start_time = std::time::Instant::now();
     // Collected results: needed for JSON array output (format requires all items)
@@ -80,16 +94,39 @@ pub async fn process_batch(
         None
     };
 
+    // Images dropped by --min-sharpness are logged next to the output file,
+    // so --skip-existing doesn't analyze them again
+    let mut skipped_log = match (&args.output, args.min_sharpness) {
+        (Some(output_path), Some(_)) => {
+            let log_path = skipped_log_path(output_path);
+            let file = if args.skip_existing {
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&log_path)?
+            } else {
+                File::create(&log_path)?
+            };
+            Some(BufWriter::new(file))
+        }
+        _ => None,
+    };
+
     // Pre-filter skip-existing files before the concurrent pipeline
     // so skipped files don't occupy a concurrency slot.
-    // Uses (path, size) matching — zero I/O, just a HashMap lookup.
+    // Uses (path, size) matching — zero I/O, just a HashMap lookup. In privacy
+    // mode the output holds hashed paths, so the lookup key is hashed the same way.
     let (files_to_process, skipped) = if existing_entries.is_empty() {
         (files, 0u64)
     } else {
         let mut to_process = Vec::new();
         let mut skip_count = 0u64;
         for file in files {
-            if existing_entries.contains_key(&(file.path.clone(), file.size)) {
+            let output_path = match processor.redactor() {
+                Some(redactor) => redactor.redact_path(&file.path),
+                None => file.path.clone(),
+            };
+            if existing_entries.contains_key(&(output_path, file.size)) {
                 skip_count += 1;
                 progress.inc(1);
                 continue;
@@ -100,25 +137,55 @@ pub async fn process_batch(
     };
 
     // ── Concurrent processing ──
-    // Process up to `parallel` images simultaneously. While one image
-    // waits on the ONNX mutex for embedding, others decode/hash/thumbnail
+    // Process up to `parallel` images simultaneously. While embeddings
+    // wait for their batch to fill or run, others decode/hash/thumbnail
     // on tokio's blocking thread pool.
+    //
+    // Archive entries are read one at a time, in discovery order, as the
+    // stream pulls the next file (`then` is sequential) — so tar archives are
+    // streamed front to back and at most `parallel` entries are held in
+    // memory. Reads and decompression run on the blocking pool.
     let parallel = args.parallel.max(1);
-    let mut result_stream = stream::iter(files_to_process)
-        .map(|file| {
+    let archive_reader = Arc::new(Mutex::new(ArchiveReader::new(
+        config.limits.max_file_size_mb,
+    )));
+    let result_stream = stream::iter(files_to_process)
+        .then(|file| {
+            let reader = Arc::clone(&archive_reader);
+            async move {
+                let entry_bytes = match file.archive_entry.clone() {
+                    Some(entry) => Some(read_archive_entry(reader, entry).await),
+                    None => None,
+                };
+                (file, entry_bytes)
+            }
+        })
+        .map(|(file, entry_bytes)| {
             let proc = Arc::clone(&processor);
             let opts = Arc::clone(&options);
             async move {
-                let result = proc.process_with_options(&file.path, &opts).await;
+                let result = match entry_bytes {
+                    None => proc.process_with_options(&file.path, &opts).await,
+                    Some(Ok(bytes)) => proc.process_archive_entry(bytes, &file.path, &opts).await,
+                    Some(Err(e)) => Err(e.into()),
+                };
                 (file, result)
             }
         })
         .buffer_unordered(parallel);
+    let mut result_stream = std::pin::pin!(result_stream);
 
     // Consume results single-threaded: stdout/file writes and counters
     // need no synchronization.
     while let Some((file, result)) = result_stream.next().await {
         match result {
+            Ok(image) if is_below_sharpness(&image, args.min_sharpness) => {
+                blurry += 1;
+                tracing::debug!("Below --min-sharpness: {:?}", file.path);
+                if let (Some(log), Some(record)) = (&mut skipped_log, SkippedImage::from(&image)) {
+                    writeln!(log, "{}", serde_json::to_string(&record)?)?;
+                }
+            }
             Ok(image) => {
                 succeeded += 1;
                 total_bytes += image.file_size;
@@ -159,12 +226,16 @@ pub async fn process_batch(
         progress.inc(1);
         let elapsed = start_time.elapsed().as_secs_f64();
         if elapsed > 0.0 {
-            let processed = succeeded + failed;
+            let processed = succeeded + failed + blurry;
             let rate = processed as f64 / elapsed;
             progress.set_message(format!("{:.1} img/sec", rate));
         }
     }
 
+    if let Some(log) = &mut skipped_log {
+        log.flush()?;
+    }
+
     // ── Post-loop output handling ──
 
     if stream_to_file {
@@ -281,24 +352,98 @@ pub async fn process_batch(
 
     // Finish progress bar and show summary
     let elapsed = start_time.elapsed();
-    let rate = if elapsed.as_secs_f64() > 0.0 {
-        succeeded as f64 / elapsed.as_secs_f64()
-    } else {
-        0.0
-    };
 
     progress.finish_and_clear();
 
     // Print formatted summary
-    print_summary(succeeded, failed, skipped, total_bytes, elapsed, rate);
+    BatchSummary {
+        succeeded,
+        failed,
+        skipped,
+        filtered,
+        blurry,
+        total_bytes,
+        elapsed,
+        batch_sizes: processor.embed_batch_sizes(),
+    }
+    .print();
 
     Ok(())
 }
 
+/// Read one archive entry on the blocking pool.
+async fn read_archive_entry(
+    reader: Arc<Mutex<ArchiveReader>>,
+    entry: ArchiveEntry,
+) -> Result<Vec<u8>, PipelineError> {
+    let locator = entry.locator();
+    tokio::task::spawn_blocking(move || {
+        let mut reader = reader.lock().unwrap_or_else(PoisonError::into_inner);
+        reader.read(&entry)
+    })
+    .await
+    .unwrap_or_else(|e| {
+        Err(PipelineError::Decode {
+            path: locator,
+            message: format!("Task join error: {e}"),
+        })
+    })
+}
+
+/// Whether `--min-sharpness` drops this image. Images without quality
+/// metrics are kept.
+fn is_below_sharpness(image: &ProcessedImage, min_sharpness: Option<f32>) -> bool {
+    match (min_sharpness, &image.quality) {
+        (Some(min), Some(quality)) => quality.sharpness < min,
+        _ => false,
+    }
+}
+
+/// An image `--min-sharpness` left out of the output, as logged to
+/// `<output>.skipped` (one JSON object per line).
+#[derive(Debug, Serialize, Deserialize)]
+struct SkippedImage {
+    file_path: PathBuf,
+    file_size: u64,
+    content_hash: String,
+    sharpness: f32,
+}
+
+impl SkippedImage {
+    /// The log record for a dropped image; `None` for images processed from
+    /// memory or without quality metrics.
+    fn from(image: &ProcessedImage) -> Option<Self> {
+        Some(Self {
+            file_path: image.file_path.clone()?,
+            file_size: image.file_size,
+            content_hash: image.content_hash.clone(),
+            sharpness: image.quality.as_ref()?.sharpness,
+        })
+    }
+}
+
+/// The log of images `--min-sharpness` dropped, next to the output file.
+fn skipped_log_path(output_path: &Path) -> PathBuf {
+    let mut path = output_path.as_os_str().to_owned();
+    path.push(".skipped");
+    PathBuf::from(path)
+}
+
+/// Record a previously processed image for --skip-existing.
+///
+/// Records without a `file_path` (processed from memory) can't match a
+/// discovered file and are ignored.
+fn insert_entry(entries: &mut HashMap<(PathBuf, u64), ()>, image: &ProcessedImage) {
+    if let Some(path) = &image.file_path {
+        entries.insert((path.clone(), image.file_size), ());
+    }
+}
+
 /// Load existing (path, size) entries from a JSONL/JSON output file for --skip-existing.
 ///
 /// Returns a map of `(file_path, file_size) → ()` for O(1) lookups.
 /// Uses path+size matching instead of content hashing — zero I/O during pre-filtering.
+/// Images an earlier run dropped for `--min-sharpness` count as existing too.
 fn load_existing_entries(
     output_path: &Option<PathBuf>,
 ) -> anyhow::Result<HashMap<(PathBuf, u64), ()>> {
@@ -308,6 +453,17 @@ fn load_existing_entries(
         return Ok(entries);
     };
 
+    let log_path = skipped_log_path(path);
+    if log_path.exists() {
+        let content = std::fs::read_to_string(&log_path)?;
+        for record in content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<SkippedImage>(line).ok())
+        {
+            entries.insert((record.file_path, record.file_size), ());
+        }
+    }
+
     if !path.exists() {
         return Ok(entries);
     }
@@ -318,14 +474,14 @@ fn load_existing_entries(
     if let Ok(records) = serde_json::from_str::<Vec<OutputRecord>>(&content) {
         for record in records {
             if let OutputRecord::Core(img) = record {
-                entries.insert((img.file_path, img.file_size), ());
+                insert_entry(&mut entries, &img);
             }
         }
         return Ok(entries);
     }
     if let Ok(images) = serde_json::from_str::<Vec<ProcessedImage>>(&content) {
         for image in images {
-            entries.insert((image.file_path, image.file_size), ());
+            insert_entry(&mut entries, &image);
         }
         return Ok(entries);
     }
@@ -340,12 +496,12 @@ fn load_existing_entries(
         }
         if let Ok(record) = serde_json::from_str::<OutputRecord>(line) {
             if let OutputRecord::Core(img) = record {
-                entries.insert((img.file_path, img.file_size), ());
+                insert_entry(&mut entries, &img);
             }
             continue;
         }
         if let Ok(image) = serde_json::from_str::<ProcessedImage>(line) {
-            entries.insert((image.file_path, image.file_size), ());
+            insert_entry(&mut entries, &image);
         } else {
             skipped_lines += 1;
         }
@@ -377,40 +533,95 @@ fn create_progress_bar(total: u64) -> indicatif::ProgressBar {
     pb
 }
 
-/// Print a formatted summary table after batch processing.
-fn print_summary(
+/// Counts and timings for the summary table printed after a batch.
+struct BatchSummary {
     succeeded: u64,
     failed: u64,
+    /// Already in the output (`--skip-existing`)
     skipped: u64,
+    /// Dropped by the discovery filters
+    filtered: FilterCounts,
+    /// Dropped by `--min-sharpness`
+    blurry: u64,
     total_bytes: u64,
     elapsed: std::time::Duration,
-    rate: f64,
-) {
-    let total = succeeded + failed + skipped;
-    let mb_processed = total_bytes as f64 / 1_000_000.0;
-    let throughput = if elapsed.as_secs_f64() > 0.0 {
-        mb_processed / elapsed.as_secs_f64()
-    } else {
-        0.0
-    };
+    /// Embedding batch-size histogram as `(size, count)`; empty when
+    /// batching was off
+    batch_sizes: Vec<(usize, u64)>,
+}
 
-    eprintln!();
-    eprintln!("  ====================================");
-    eprintln!("               Summary");
-    eprintln!("  ====================================");
-    eprintln!("    Succeeded:    {:>8}", succeeded);
-    if failed > 0 {
-        eprintln!("    Failed:       {:>8}", failed);
-    }
-    if skipped > 0 {
-        eprintln!("    Skipped:      {:>8}", skipped);
+impl BatchSummary {
+    /// Print the formatted summary table to stderr.
+    fn print(&self) {
+        let Self {
+            succeeded,
+            failed,
+            skipped,
+            ref filtered,
+            blurry,
+            total_bytes,
+            elapsed,
+            ref batch_sizes,
+        } = *self;
+        let total = succeeded + failed + skipped + filtered.total() + blurry;
+        let rate = if elapsed.as_secs_f64() > 0.0 {
+            succeeded as f64 / elapsed.as_secs_f64()
+        } else {
+            0.0
+        };
+        let mb_processed = total_bytes as f64 / 1_000_000.0;
+        let throughput = if elapsed.as_secs_f64() > 0.0 {
+            mb_processed / elapsed.as_secs_f64()
+        } else {
+            0.0
+        };
+
+        eprintln!();
+        eprintln!("  ====================================");
+        eprintln!("               Summary");
+        eprintln!("  ====================================");
+        eprintln!("    Succeeded:    {:>8}", succeeded);
+        if failed > 0 {
+            eprintln!("    Failed:       {:>8}", failed);
+        }
+        if skipped > 0 {
+            eprintln!("    Skipped:      {:>8}", skipped);
+        }
+        if filtered.not_modified > 0 {
+            eprintln!("    Not modified: {:>8}", filtered.not_modified);
+        }
+        if filtered.too_small > 0 {
+            eprintln!("    Under --min-size: {:>4}", filtered.too_small);
+        }
+        if filtered.too_large > 0 {
+            eprintln!("    Over --max-size:  {:>4}", filtered.too_large);
+        }
+        if blurry > 0 {
+            eprintln!("    Under --min-sharpness: {:>3}", blurry);
+        }
+        eprintln!("  ------------------------------------");
+        eprintln!("    Total:        {:>8}", total);
+        eprintln!("    Duration:     {:>7.1}s", elapsed.as_secs_f64());
+        eprintln!("    Rate:         {:>7.1} img/sec", rate);
+        eprintln!("    Throughput:   {:>7.1} MB/sec", throughput);
+        if !batch_sizes.is_empty() {
+            let batches: u64 = batch_sizes.iter().map(|&(_, count)| count).sum();
+            let images: u64 = batch_sizes
+                .iter()
+                .map(|&(size, count)| size as u64 * count)
+                .sum();
+            eprintln!("  ------------------------------------");
+            eprintln!(
+                "    Embed batches:{:>8}  (mean {:.1})",
+                batches,
+                images as f64 / batches as f64
+            );
+            for &(size, count) in batch_sizes {
+                eprintln!("      size {:>3}:   {:>8}", size, count);
+            }
+        }
+        eprintln!("  ====================================");
     }
-    eprintln!("  ------------------------------------");
-    eprintln!("    Total:        {:>8}", total);
-    eprintln!("    Duration:     {:>7.1}s", elapsed.as_secs_f64());
-    eprintln!("    Rate:         {:>7.1} img/sec", rate);
-    eprintln!("    Throughput:   {:>7.1} MB/sec", throughput);
-    eprintln!("  ====================================");
 }
 
 #[cfg(test)]
@@ -426,19 +637,14 @@ mod tests {
             .to_string_lossy()
             .to_string();
         ProcessedImage {
-            file_path: std::path::PathBuf::from(file_path),
+            file_path: Some(std::path::PathBuf::from(file_path)),
             file_name: name,
             content_hash: format!("hash_{file_size}"),
             width: 100,
             height: 100,
             format: "jpeg".to_string(),
             file_size,
-            embedding: vec![],
-            exif: None,
-            tags: vec![],
-            description: None,
-            thumbnail: None,
-            perceptual_hash: None,
+            ..ProcessedImage::default()
         }
     }
 
@@ -546,6 +752,55 @@ mod tests {
         assert!(entries.is_empty());
     }
 
+    #[test]
+    fn test_load_existing_entries_includes_skipped_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sharp.jsonl");
+        let image = sample_image("/photos/ok.jpg", 1000);
+        std::fs::write(&path, serde_json::to_string(&image).unwrap()).unwrap();
+
+        let mut blurry = sample_image("/photos/blurry.jpg", 2000);
+        blurry.quality = Some(photon_core::QualityMetrics {
+            sharpness: 12.5,
+            noise: 1.0,
+            colorfulness: 20.0,
+            mean_luminance: 120.0,
+            clipped_highlights_pct: 0.0,
+            clipped_shadows_pct: 0.0,
+            luminance_histogram: vec![],
+        });
+        let record = SkippedImage::from(&blurry).unwrap();
+        std::fs::write(
+            skipped_log_path(&path),
+            serde_json::to_string(&record).unwrap(),
+        )
+        .unwrap();
+
+        let entries = load_existing_entries(&Some(path)).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.contains_key(&(PathBuf::from("/photos/blurry.jpg"), 2000)));
+    }
+
+    #[test]
+    fn test_min_sharpness_filter() {
+        let mut image = sample_image("/photos/a.jpg", 1000);
+        // No metrics (quality disabled or skipped) → never filtered
+        assert!(!is_below_sharpness(&image, Some(100.0)));
+
+        image.quality = Some(photon_core::QualityMetrics {
+            sharpness: 42.0,
+            noise: 1.0,
+            colorfulness: 20.0,
+            mean_luminance: 120.0,
+            clipped_highlights_pct: 0.0,
+            clipped_shadows_pct: 0.0,
+            luminance_histogram: vec![],
+        });
+        assert!(is_below_sharpness(&image, Some(100.0)));
+        assert!(!is_below_sharpness(&image, Some(42.0)));
+        assert!(!is_below_sharpness(&image, None));
+    }
+
     #[test]
     fn test_prefilter_reprocesses_when_size_differs() {
         // Same path, different size → should NOT be skipped
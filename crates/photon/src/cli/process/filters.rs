@@ -0,0 +1,92 @@
+//! Parsers for the discovery filter flags: `--modified-since`, `--min-size`, `--max-size`.
+
+use std::time::SystemTime;
+
+/// Parse `--modified-since`: an RFC 3339 timestamp, or a duration back from now.
+///
+/// Durations use humantime syntax (`90m`, `24h`, `7d`, `1w 2d`).
+pub fn parse_modified_since(value: &str) -> Result<SystemTime, String> {
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(time.into());
+    }
+    let ago = humantime::parse_duration(value).map_err(|_| {
+        format!(
+            "expected an RFC 3339 timestamp (e.g. 2024-05-01T00:00:00Z) \
+             or a duration (e.g. 24h, 7d), got {value:?}"
+        )
+    })?;
+    SystemTime::now()
+        .checked_sub(ago)
+        .ok_or_else(|| format!("duration {value:?} reaches too far back"))
+}
+
+/// Parse `--min-size` / `--max-size`: bytes, with an optional `K`, `M` or `G`
+/// suffix (`KB`/`KiB` etc. are accepted too).
+///
+/// Units are binary (1 MB = 1024 × 1024 bytes), matching
+/// `limits.max_file_size_mb`.
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1024,
+        "m" | "mb" | "mib" => 1024 * 1024,
+        "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+        _ => {
+            return Err(format!(
+                "unknown size unit in {value:?} (use B, KB, MB or GB)"
+            ))
+        }
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("expected a size such as 500KB or 20MB, got {value:?}"))?;
+    Ok((number * multiplier as f64).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn modified_since_accepts_rfc3339_with_offset() {
+        let time = parse_modified_since("2024-05-01T02:00:00+02:00").unwrap();
+        let expected = SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_521_600);
+        assert_eq!(time, expected);
+    }
+
+    #[test]
+    fn modified_since_accepts_duration() {
+        let time = parse_modified_since("24h").unwrap();
+        let ago = SystemTime::now().duration_since(time).unwrap();
+        assert!(ago >= Duration::from_secs(24 * 3600));
+        assert!(ago < Duration::from_secs(24 * 3600 + 60));
+    }
+
+    #[test]
+    fn modified_since_rejects_garbage() {
+        let err = parse_modified_since("yesterday").unwrap_err();
+        assert!(err.contains("RFC 3339"));
+    }
+
+    #[test]
+    fn size_units() {
+        assert_eq!(parse_size("500").unwrap(), 500);
+        assert_eq!(parse_size("500B").unwrap(), 500);
+        assert_eq!(parse_size("2k").unwrap(), 2048);
+        assert_eq!(parse_size("1.5MB").unwrap(), 1_572_864);
+        assert_eq!(parse_size("1 GiB").unwrap(), 1 << 30);
+    }
+
+    #[test]
+    fn size_rejects_bad_input() {
+        assert!(parse_size("10TB").is_err());
+        assert!(parse_size("MB").is_err());
+        assert!(parse_size("").is_err());
+    }
+}
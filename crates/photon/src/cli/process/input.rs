@@ -0,0 +1,219 @@
+//! Input sources beyond a single path: stdin (`-`) and `--files-from` path lists.
+
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use photon_core::{DiscoveredFile, FilterCounts, ImageProcessor};
+
+/// Name given to an image read from stdin (used for `file_name` and errors).
+pub const STDIN_NAME: &str = "stdin";
+
+/// Whether a path argument is `-`, meaning stdin.
+pub fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+/// Read all of stdin into memory.
+pub fn read_stdin() -> anyhow::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    std::io::stdin().lock().read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Read one image from stdin, refusing input over `limits.max_file_size_mb`.
+pub fn read_stdin_image(max_file_size_mb: u64) -> anyhow::Result<Vec<u8>> {
+    read_limited(std::io::stdin().lock(), max_file_size_mb)
+}
+
+/// Read at most `max_file_size_mb` megabytes; anything larger is an error,
+/// detected without buffering more than one byte past the limit.
+fn read_limited(reader: impl Read, max_file_size_mb: u64) -> anyhow::Result<Vec<u8>> {
+    let max_bytes = max_file_size_mb * 1024 * 1024;
+    let mut bytes = Vec::new();
+    reader.take(max_bytes + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > max_bytes {
+        anyhow::bail!(
+            "Image on stdin is larger than {max_file_size_mb}MB.\n\n  \
+             Hint: Increase `limits.max_file_size_mb` in config."
+        );
+    }
+    Ok(bytes)
+}
+
+/// Read a `--files-from` list from a file, or from stdin when `source` is `-`.
+pub fn read_path_list(source: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let bytes = if is_stdin(source) {
+        read_stdin()?
+    } else {
+        std::fs::read(source)
+            .map_err(|e| anyhow::anyhow!("Cannot read --files-from list {:?}: {e}", source))?
+    };
+    Ok(parse_path_list(&bytes))
+}
+
+/// Split a path list into paths.
+///
+/// The list is NUL-separated if it contains any NUL byte (`find -print0`,
+/// `fd -0`), otherwise newline-separated with optional `\r\n` endings.
+/// Empty entries are skipped.
+pub fn parse_path_list(bytes: &[u8]) -> Vec<PathBuf> {
+    let separator = if bytes.contains(&0) { b'\0' } else { b'\n' };
+    bytes
+        .split(|&b| b == separator)
+        .map(|entry| {
+            if separator == b'\n' {
+                entry.strip_suffix(b"\r").unwrap_or(entry)
+            } else {
+                entry
+            }
+        })
+        .filter(|entry| !entry.is_empty())
+        .map(path_from_bytes)
+        .collect()
+}
+
+/// Resolve listed paths into discovered files.
+///
+/// Files are kept if they have a supported extension; directories are walked
+/// the same way as a directory argument. Missing paths are logged and skipped.
+/// A file reached more than once — listed twice, or both directly and through
+/// a listed directory — is kept once. Also returns how many files the
+/// discovery filters dropped.
+pub fn discover_listed(
+    processor: &ImageProcessor,
+    paths: &[PathBuf],
+) -> (Vec<DiscoveredFile>, FilterCounts) {
+    let mut files = Vec::new();
+    let mut filtered = FilterCounts::default();
+    let mut seen = HashSet::new();
+    let mut repeated = 0;
+    for path in paths {
+        if !path.exists() {
+            tracing::warn!("Skipping listed path that does not exist: {:?}", path);
+            continue;
+        }
+        let (found, counts) = processor.discover_counted(path);
+        for file in found {
+            if seen.insert(identity(&file)) {
+                files.push(file);
+            } else {
+                repeated += 1;
+            }
+        }
+        filtered += counts;
+    }
+    if repeated > 0 {
+        tracing::info!("Skipped {repeated} file(s) listed more than once");
+    }
+    (files, filtered)
+}
+
+/// What makes two discovered files the same: the canonical path on disk,
+/// plus the entry name for archive entries.
+fn identity(file: &DiscoveredFile) -> (PathBuf, Option<String>) {
+    let canonical =
+        |path: &Path| std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    match &file.archive_entry {
+        Some(entry) => (canonical(&entry.archive), Some(entry.name.clone())),
+        None => (canonical(&file.path), None),
+    }
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_stdin_only_matches_dash() {
+        assert!(is_stdin(Path::new("-")));
+        assert!(!is_stdin(Path::new("./-")));
+        assert!(!is_stdin(Path::new("photo.jpg")));
+    }
+
+    #[test]
+    fn parse_newline_separated_list() {
+        let paths = parse_path_list(b"a.jpg\r\n\nphotos/b c.png\n");
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("a.jpg"), PathBuf::from("photos/b c.png")]
+        );
+    }
+
+    #[test]
+    fn parse_nul_separated_list_keeps_newlines_in_names() {
+        let paths = parse_path_list(b"a.jpg\0odd\nname.png\0\0");
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("a.jpg"), PathBuf::from("odd\nname.png")]
+        );
+    }
+
+    #[test]
+    fn parse_empty_list() {
+        assert!(parse_path_list(b"").is_empty());
+        assert!(parse_path_list(b"\n\n").is_empty());
+    }
+
+    #[test]
+    fn read_limited_rejects_oversized_input() {
+        let exact = vec![0u8; 1024 * 1024];
+        assert_eq!(read_limited(&exact[..], 1).unwrap().len(), exact.len());
+
+        let over = vec![0u8; 1024 * 1024 + 1];
+        let err = read_limited(&over[..], 1).unwrap_err();
+        assert!(err.to_string().contains("larger than 1MB"));
+    }
+
+    #[test]
+    fn discover_listed_dedupes_repeated_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("a.png");
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests/fixtures/images/test.png");
+        std::fs::copy(fixture, &image).unwrap();
+
+        let processor = ImageProcessor::new(&photon_core::Config::default()).unwrap();
+        let (files, _) = discover_listed(
+            &processor,
+            &[
+                image.clone(),
+                dir.path().join(".").join("a.png"),
+                dir.path().to_path_buf(),
+            ],
+        );
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, image);
+    }
+
+    #[test]
+    fn discover_listed_skips_missing_and_unsupported() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("a.png");
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests/fixtures/images/test.png");
+        std::fs::copy(fixture, &image).unwrap();
+        let text = dir.path().join("notes.txt");
+        std::fs::write(&text, "hi").unwrap();
+
+        let processor = ImageProcessor::new(&photon_core::Config::default()).unwrap();
+        let (files, filtered) = discover_listed(
+            &processor,
+            &[image.clone(), text, dir.path().join("missing.jpg")],
+        );
+        assert_eq!(filtered.total(), 0);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, image);
+    }
+}
@@ -2,6 +2,8 @@
 
 mod batch;
 mod enrichment;
+mod filters;
+mod input;
 mod setup;
 pub mod types;
 
@@ -9,23 +11,65 @@ pub use types::{LlmProvider, OutputFormat, Quality};
 
 use clap::Args;
 use photon_core::{
-    Config, Enricher, ImageProcessor, OutputFormat as CoreOutputFormat, OutputRecord,
+    Config, Enricher, FilterCounts, ImageProcessor, OutputFormat as CoreOutputFormat, OutputRecord,
     ProcessOptions,
 };
 use std::fs::File;
 use std::io::BufWriter;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use batch::process_batch;
 use enrichment::{run_enrichment_collect, run_enrichment_stdout};
+pub use setup::apply_quality;
 use setup::setup_processor;
 
 /// Arguments for the `process` command.
 #[derive(Args, Debug)]
 pub struct ProcessArgs {
-    /// Image file or directory to process
-    #[arg(required = true)]
-    pub input: PathBuf,
+    /// Image file or directory to process, or `-` to read one image from stdin
+    #[arg(required_unless_present = "files_from", conflicts_with = "files_from")]
+    pub input: Option<PathBuf>,
+
+    /// Read the paths to process from a file (`-` for stdin), one per line
+    /// or NUL-separated (e.g. `find -print0`)
+    #[arg(long, value_name = "PATH")]
+    pub files_from: Option<PathBuf>,
+
+    /// Only process files matching this gitignore-style glob when walking
+    /// directories (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// Skip files and directories matching this gitignore-style glob when
+    /// walking directories (repeatable, e.g. `--exclude @eaDir`)
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Maximum directory depth to descend below the input
+    #[arg(long, value_name = "N")]
+    pub max_depth: Option<usize>,
+
+    /// Skip hidden (dot-prefixed) files and directories
+    #[arg(long)]
+    pub no_hidden: bool,
+
+    /// Do not follow symbolic links while walking directories
+    #[arg(long)]
+    pub no_follow_links: bool,
+
+    /// Only process files modified since an RFC 3339 time or a duration ago
+    /// (e.g. `2024-05-01T00:00:00Z`, `24h`, `7d`)
+    #[arg(long, value_name = "TIME|DURATION", value_parser = filters::parse_modified_since)]
+    pub modified_since: Option<SystemTime>,
+
+    /// Skip files smaller than this size (e.g. `20KB`)
+    #[arg(long, value_name = "SIZE", value_parser = filters::parse_size)]
+    pub min_size: Option<u64>,
+
+    /// Skip files larger than this size without opening them (e.g. `50MB`)
+    #[arg(long, value_name = "SIZE", value_parser = filters::parse_size)]
+    pub max_size: Option<u64>,
 
     /// Output file (defaults to stdout)
     #[arg(short, long)]
@@ -47,6 +91,19 @@ pub struct ProcessArgs {
     #[arg(long)]
     pub no_thumbnail: bool,
 
+    /// Disable BlurHash and ThumbHash placeholders
+    #[arg(long)]
+    pub no_placeholders: bool,
+
+    /// Disable color palette extraction
+    #[arg(long)]
+    pub no_palette: bool,
+
+    /// Leave images with a sharpness score below this out of batch output
+    /// (Laplacian variance; ~100 and under is usually blurry)
+    #[arg(long, value_name = "SCORE")]
+    pub min_sharpness: Option<f32>,
+
     /// Disable embedding generation
     #[arg(long)]
     pub no_embedding: bool,
@@ -59,7 +116,7 @@ pub struct ProcessArgs {
     #[arg(long)]
     pub no_description: bool,
 
-    /// Quality preset: fast (224 model) or high (384 model)
+    /// Quality preset: fast (configured model) or high (its `high_quality` variant)
     #[arg(long, value_enum, default_value = "fast")]
     pub quality: Quality,
 
@@ -95,12 +152,24 @@ pub struct ProcessArgs {
 impl Default for ProcessArgs {
     fn default() -> Self {
         Self {
-            input: PathBuf::new(),
+            input: None,
+            files_from: None,
+            include: vec![],
+            exclude: vec![],
+            max_depth: None,
+            no_hidden: false,
+            no_follow_links: false,
+            modified_since: None,
+            min_size: None,
+            max_size: None,
             output: None,
             format: OutputFormat::Json,
             parallel: 4,
             skip_existing: false,
             no_thumbnail: false,
+            no_placeholders: false,
+            no_palette: false,
+            min_sharpness: None,
             no_embedding: false,
             no_tagging: false,
             no_description: false,
@@ -129,28 +198,74 @@ pub(crate) struct ProcessContext {
 pub async fn execute(args: ProcessArgs) -> anyhow::Result<()> {
     let ctx = setup_processor(&args)?;
 
-    let files = ctx.processor.discover(&args.input);
+    if let Some(list) = &args.files_from {
+        let paths = input::read_path_list(list)?;
+        let (files, filtered) = input::discover_listed(&ctx.processor, &paths);
+        if files.is_empty() {
+            tracing::warn!(
+                "No supported image files found among {} listed path(s){}",
+                paths.len(),
+                filtered_note(&filtered)
+            );
+            return Ok(());
+        }
+        tracing::info!("Found {} image(s) to process", files.len());
+        return process_batch(ctx, &args, files, filtered).await;
+    }
+
+    let Some(input) = args.input.clone() else {
+        anyhow::bail!("No input given. Pass a path, `-` for stdin, or --files-from.");
+    };
+    if input::is_stdin(&input) {
+        return process_single(ctx, &args, &input).await;
+    }
+
+    let (files, filtered) = ctx.processor.discover_counted(&input);
     if files.is_empty() {
-        tracing::warn!("No supported image files found at {:?}", args.input);
+        tracing::warn!(
+            "No supported image files found at {:?}{}",
+            input,
+            filtered_note(&filtered)
+        );
         return Ok(());
     }
     tracing::info!("Found {} image(s) to process", files.len());
 
-    if args.input.is_file() {
-        process_single(ctx, &args).await
+    // A single archive expands to many entries, so it goes through the batch path
+    if input.is_file() && files[0].archive_entry.is_none() {
+        process_single(ctx, &args, &input).await
     } else {
-        process_batch(ctx, &args, files).await
+        process_batch(ctx, &args, files, filtered).await
+    }
+}
+
+/// Suffix for "no files found" warnings when the discovery filters dropped some.
+fn filtered_note(filtered: &FilterCounts) -> String {
+    match filtered.total() {
+        0 => String::new(),
+        n => format!(" ({n} dropped by --modified-since/--min-size/--max-size)"),
     }
 }
 
 // ── Single-file processing ─────────────────────────────────────────────────
 
-/// Process a single image file with optional LLM enrichment.
-async fn process_single(mut ctx: ProcessContext, args: &ProcessArgs) -> anyhow::Result<()> {
-    let result = ctx
-        .processor
-        .process_with_options(&args.input, &ctx.options)
-        .await?;
+/// Process a single image file (or stdin when `input` is `-`) with optional
+/// LLM enrichment.
+async fn process_single(
+    mut ctx: ProcessContext,
+    args: &ProcessArgs,
+    input: &Path,
+) -> anyhow::Result<()> {
+    let result = if input::is_stdin(input) {
+        let bytes = input::read_stdin_image(ctx.config.limits.max_file_size_mb)?;
+        ctx.processor
+            .process_bytes(bytes, input::STDIN_NAME, &ctx.options)
+            .await?
+    } else {
+        ctx.processor
+            .process_with_options(input, &ctx.options)
+            .await?
+    };
 
     if ctx.llm_enabled {
         // Dual-stream: emit core record, then enrich
@@ -248,11 +363,15 @@ mod tests {
         let args = ProcessArgs::default();
         assert!(!args.skip_existing);
         assert!(!args.no_thumbnail);
+        assert!(!args.no_placeholders);
+        assert!(!args.no_palette);
         assert!(!args.no_embedding);
         assert!(!args.no_tagging);
         assert!(!args.no_description);
         assert!(!args.show_tag_paths);
         assert!(!args.no_dedup_tags);
+        assert!(!args.no_hidden);
+        assert!(!args.no_follow_links);
     }
 
     #[test]
@@ -261,11 +380,66 @@ mod tests {
         assert!(args.output.is_none());
         assert!(args.llm.is_none());
         assert!(args.llm_model.is_none());
+        assert!(args.max_depth.is_none());
+        assert!(args.modified_since.is_none());
+        assert!(args.min_size.is_none());
+        assert!(args.max_size.is_none());
     }
 
     #[test]
-    fn process_args_default_input_is_empty_path() {
+    fn process_args_default_input_is_none() {
         let args = ProcessArgs::default();
-        assert_eq!(args.input, PathBuf::new());
+        assert!(args.input.is_none());
+        assert!(args.files_from.is_none());
+    }
+
+    #[derive(clap::Parser)]
+    struct TestCli {
+        #[command(flatten)]
+        args: ProcessArgs,
+    }
+
+    fn parse(argv: &[&str]) -> Result<ProcessArgs, clap::Error> {
+        use clap::Parser;
+        TestCli::try_parse_from(std::iter::once("process").chain(argv.iter().copied()))
+            .map(|cli| cli.args)
+    }
+
+    #[test]
+    fn process_args_accept_stdin_dash() {
+        let args = parse(&["-"]).unwrap();
+        assert_eq!(args.input, Some(PathBuf::from("-")));
+    }
+
+    #[test]
+    fn process_args_files_from_replaces_input() {
+        let args = parse(&["--files-from", "list.txt"]).unwrap();
+        assert!(args.input.is_none());
+        assert_eq!(args.files_from, Some(PathBuf::from("list.txt")));
+    }
+
+    #[test]
+    fn process_args_require_input_or_files_from() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["photos/", "--files-from", "-"]).is_err());
+    }
+
+    #[test]
+    fn process_args_repeatable_globs() {
+        let args = parse(&[
+            "photos/",
+            "--exclude",
+            "@eaDir",
+            "--exclude",
+            "._*",
+            "--include",
+            "*.jpg",
+            "--max-depth",
+            "3",
+        ])
+        .unwrap();
+        assert_eq!(args.exclude, ["@eaDir", "._*"]);
+        assert_eq!(args.include, ["*.jpg"]);
+        assert_eq!(args.max_depth, Some(3));
     }
 }
@@ -1,25 +1,49 @@
 //! Processor setup: config overrides, model loading, enricher creation.
 
 use photon_core::{
-    Config, EmbeddingEngine, ImageProcessor, OutputFormat as CoreOutputFormat, ProcessOptions,
+    ArchiveKind, Config, DiscoveryFilters, EmbeddingEngine, Geocoder, ImageProcessor,
+    ModelRegistry, OutputFormat as CoreOutputFormat, ProcessOptions,
 };
 
+use super::input::is_stdin;
 use super::types::{LlmProvider, OutputFormat, Quality};
 use super::{ProcessArgs, ProcessContext};
 
 /// Validate input, load config/models, and assemble everything needed for processing.
 pub fn setup_processor(args: &ProcessArgs) -> anyhow::Result<ProcessContext> {
-    // Validate input path exists
-    if !args.input.exists() {
-        anyhow::bail!(
-            "Input path does not exist: {:?}\n\n  Hint: Check the file path and try again.",
-            args.input
-        );
+    // Validate input path exists (`-` reads from stdin)
+    if let Some(input) = args.input.as_deref().filter(|p| !is_stdin(p)) {
+        if !input.exists() {
+            anyhow::bail!(
+                "Input path does not exist: {:?}\n\n  Hint: Check the file path and try again.",
+                input
+            );
+        }
+    }
+    if let Some(list) = args.files_from.as_deref().filter(|p| !is_stdin(p)) {
+        if !list.exists() {
+            anyhow::bail!(
+                "--files-from list does not exist: {:?}\n\n  Hint: Use `-` to read the list from stdin.",
+                list
+            );
+        }
     }
 
     // Load configuration
     let mut config = Config::load()?;
 
+    // Size the embedding session pool (`pool_size = 0`) to the workers that
+    // will share it; a single image only ever needs one, but an archive is
+    // a batch of its entries
+    let is_archive =
+        |p: &std::path::Path| config.processing.archives && ArchiveKind::from_path(p).is_some();
+    let single_file = args.files_from.is_none()
+        && args
+            .input
+            .as_deref()
+            .is_some_and(|p| is_stdin(p) || (p.is_file() && !is_archive(p)));
+    config.processing.parallel_workers = if single_file { 1 } else { args.parallel.max(1) };
+
     // Override thumbnail size if specified
     config.thumbnail.size = args.thumbnail_size;
 
@@ -28,28 +52,38 @@ pub fn setup_processor(args: &ProcessArgs) -> anyhow::Result<ProcessContext> {
         config.thumbnail.enabled = false;
     }
 
+    // The sharpness filter needs the quality stage, which is off by default
+    if args.min_sharpness.is_some() {
+        config.quality.enabled = true;
+    }
+
     // Apply quality preset — select model variant and image size
-    match args.quality {
-        Quality::High => {
-            let high_model = "siglip-base-patch16-384".to_string();
-            if EmbeddingEngine::model_exists(
-                &photon_core::config::EmbeddingConfig {
-                    model: high_model.clone(),
-                    image_size: 384,
-                },
-                &config.model_dir(),
-            ) {
-                config.embedding.model = high_model;
-                config.embedding.image_size = 384;
-            } else {
-                tracing::warn!(
-                    "Base 384 model not found. Falling back to 224. \
-                     Run `photon models download` to install additional models."
-                );
-            }
+    apply_quality(&mut config, args.quality);
+
+    // Apply discovery filters (globs add to the config lists)
+    config
+        .processing
+        .include
+        .extend(args.include.iter().cloned());
+    config
+        .processing
+        .exclude
+        .extend(args.exclude.iter().cloned());
+    if let Some(depth) = args.max_depth {
+        if depth == 0 {
+            anyhow::bail!("--max-depth must be > 0");
         }
-        Quality::Fast => {
-            // Default — 224 model, no changes needed
+        config.processing.max_depth = depth;
+    }
+    if args.no_hidden {
+        config.processing.include_hidden = false;
+    }
+    if args.no_follow_links {
+        config.processing.follow_links = false;
+    }
+    if let (Some(min), Some(max)) = (args.min_size, args.max_size) {
+        if min > max {
+            anyhow::bail!("--min-size ({min} bytes) is larger than --max-size ({max} bytes)");
         }
     }
 
@@ -62,7 +96,12 @@ pub fn setup_processor(args: &ProcessArgs) -> anyhow::Result<ProcessContext> {
     }
 
     // Create processor
-    let mut processor = ImageProcessor::new(&config);
+    let mut processor = ImageProcessor::new(&config)?;
+    processor.set_discovery_filters(DiscoveryFilters {
+        modified_since: args.modified_since,
+        min_size: args.min_size,
+        max_size: args.max_size,
+    });
 
     // Load embedding model unless disabled
     if !args.no_embedding {
@@ -86,12 +125,32 @@ pub fn setup_processor(args: &ProcessArgs) -> anyhow::Result<ProcessContext> {
         }
     }
 
+    // Load reverse geocoder if the dataset is installed
+    if config.geocoding.enabled {
+        if Geocoder::dataset_exists(&config.geocoding_dir()) {
+            match processor.load_geocoding(&config) {
+                Ok(()) => tracing::info!("Geocoding dataset loaded"),
+                Err(e) => tracing::warn!("Failed to load geocoding dataset: {e}"),
+            }
+        } else {
+            tracing::info!(
+                "Geocoding dataset not found; place names are skipped. \
+                 Run `photon models download --geocoding` to install it."
+            );
+        }
+    }
+
     // Create process options
     let options = ProcessOptions {
         skip_thumbnail: args.no_thumbnail,
         skip_perceptual_hash: false,
+        skip_placeholders: args.no_placeholders,
+        skip_palette: args.no_palette,
+        skip_quality: false,
         skip_embedding: args.no_embedding || !processor.has_embedding(),
         skip_tagging: args.no_tagging || !processor.has_tagging(),
+        // Applied by batch runs only, where the image is dropped anyway
+        min_sharpness: None,
     };
 
     // Determine if LLM enrichment is enabled
@@ -120,6 +179,46 @@ pub fn setup_processor(args: &ProcessArgs) -> anyhow::Result<ProcessContext> {
     })
 }
 
+/// Select the model variant (and its image size) for a quality preset.
+///
+/// `high` switches to the `high_quality` variant named in the configured
+/// model's manifest, and keeps the configured model when that variant isn't
+/// installed. `embed-text` uses this too, so query vectors come from the
+/// same model as the images processed with the same preset.
+pub fn apply_quality(config: &mut Config, quality: Quality) {
+    match quality {
+        Quality::High => {
+            let model_dir = config.model_dir();
+            let registry = ModelRegistry::load(&model_dir);
+            let Some(variant) = registry.high_quality_variant(&config.embedding.model) else {
+                tracing::debug!(
+                    "Model '{}' has no high-quality variant; using it as is",
+                    config.embedding.model
+                );
+                return;
+            };
+            let embedding = photon_core::config::EmbeddingConfig {
+                model: variant.name.clone(),
+                image_size: variant.image_size,
+                ..config.embedding.clone()
+            };
+            if EmbeddingEngine::model_exists(&embedding, &model_dir) {
+                config.embedding = embedding;
+            } else {
+                tracing::warn!(
+                    "{} model not found. Falling back to '{}'. \
+                     Run `photon models download` to install additional models.",
+                    variant.label,
+                    config.embedding.model
+                );
+            }
+        }
+        Quality::Fast => {
+            // Default — configured model, no changes needed
+        }
+    }
+}
+
 /// Create an LLM enricher from CLI args and config, if --llm was specified.
 fn create_enricher(
     args: &ProcessArgs,
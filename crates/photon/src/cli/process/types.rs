@@ -20,13 +20,13 @@ impl std::fmt::Display for OutputFormat {
     }
 }
 
-/// Quality preset for SigLIP vision model resolution.
+/// Quality preset selecting the vision model variant.
 #[derive(Clone, Copy, Debug, ValueEnum, Default)]
 pub enum Quality {
-    /// Fast processing with base 224 model (default)
+    /// Use the configured model as-is (default)
     #[default]
     Fast,
-    /// Higher detail with base 384 model (~3-4x slower)
+    /// Switch to the manifest's `high_quality` variant of the configured model
     High,
 }
 
@@ -13,6 +13,12 @@
 //! # Process a directory
 //! photon process ./photos/ --output results.jsonl
 //!
+//! # Find duplicates in the output
+//! photon dedup results.jsonl --format script > dedup.sh
+//!
+//! # Embed search queries in the same space as the images
+//! photon embed-text "red car at night"
+//!
 //! # View configuration
 //! photon config show
 //!
@@ -49,13 +55,19 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Process images and generate embeddings, tags, and metadata
-    Process(cli::process::ProcessArgs),
+    Process(Box<cli::process::ProcessArgs>),
 
     /// Manage AI models (download, list, etc.)
     Models(cli::models::ModelsArgs),
 
     /// View and manage configuration
     Config(cli::config::ConfigArgs),
+
+    /// Find exact, near and semantic duplicates in a process output file
+    Dedup(cli::dedup::DedupArgs),
+
+    /// Embed text queries in the same vector space as image embeddings
+    EmbedText(cli::embed_text::EmbedTextArgs),
 }
 
 #[tokio::main]
@@ -80,9 +92,11 @@ async fn main() -> anyhow::Result<()> {
 
     // Dispatch to the appropriate command handler
     match cli.command {
-        Some(Commands::Process(args)) => cli::process::execute(args).await,
+        Some(Commands::Process(args)) => cli::process::execute(*args).await,
         Some(Commands::Models(args)) => cli::models::execute(args).await,
         Some(Commands::Config(args)) => cli::config::execute(args).await,
+        Some(Commands::Dedup(args)) => cli::dedup::execute(args).await,
+        Some(Commands::EmbedText(args)) => cli::embed_text::execute(args).await,
         None => {
             if std::io::stdin().is_terminal() {
                 cli::interactive::run(&config).await